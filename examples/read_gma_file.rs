@@ -1,5 +1,4 @@
 /// This example shows how to read a gma file and print out some information about it
-use gma;
 fn main() {
     let archive = gma::open("myaddon.gma").unwrap();
     println!("Version : {}", archive.version());
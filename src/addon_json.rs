@@ -0,0 +1,40 @@
+use crate::{AddonTag, AddonType, Error, Result};
+use nanoserde::DeJson;
+use std::{convert::TryFrom, path::Path};
+
+/// The addon.json file gmad reads when creating an addon from a folder
+#[derive(Debug, Default, DeJson)]
+pub struct AddonJson {
+    #[nserde(default)]
+    pub title: String,
+    #[nserde(default)]
+    pub description: String,
+    #[nserde(default)]
+    #[nserde(rename = "type")]
+    pub addon_type: String,
+    #[nserde(default)]
+    pub tags: Vec<String>,
+    #[nserde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl AddonJson {
+    /// Reads and parses `path`
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::deserialize_json(&contents).map_err(|e| Error::InvalidAddonJson(e.to_string()))
+    }
+
+    /// The addon type, if it is a known one
+    pub fn addon_type(&self) -> Option<AddonType> {
+        AddonType::try_from(self.addon_type.as_str()).ok()
+    }
+
+    /// The known tags, unknown ones are left out
+    pub fn tags(&self) -> Vec<AddonTag> {
+        self.tags
+            .iter()
+            .filter_map(|t| AddonTag::try_from(t.as_str()).ok())
+            .collect()
+    }
+}
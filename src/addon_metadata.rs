@@ -1,12 +1,32 @@
 use crate::{AddonTag, AddonType};
 use nanoserde::{self, DeJson, SerJson};
+use std::fmt::Write;
+
+/// How the metadata json embedded in the header is formatted, see
+/// [`crate::GMABuilder::metadata_format`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MetadataFormat {
+    /// A single line with the title, description, type and tags, the format this crate has
+    /// always written
+    #[default]
+    Compact,
+    /// The same fields as [`MetadataFormat::Compact`], one per line and indented with tabs
+    Canonical,
+    /// Byte for byte what gmad writes: the description, type and tags laid out like
+    /// [`MetadataFormat::Canonical`] but without the title, so archives built from the same
+    /// files match the ones built by gmad
+    GmadCompatible,
+}
 
 #[derive(Debug, SerJson, DeJson)]
 pub struct AddonMetadata {
     title: Option<String>,
+    // sharpGMad and hand written metadata can leave out the description or the tags
+    #[nserde(default)]
     description: String,
     #[nserde(rename = "type")]
     addon_type: String,
+    #[nserde(default)]
     tags: Vec<String>,
 }
 
@@ -20,12 +40,12 @@ impl AddonMetadata {
     ) -> Self {
         let mut string_tags = Vec::new();
         for t in addon_tags {
-            string_tags.push(Self::tag_to_string(&t))
+            string_tags.push(Self::tag_to_string(t))
         }
         Self {
             title: Some(title),
             description,
-            addon_type: Self::type_to_string(&addon_type),
+            addon_type: Self::type_to_string(addon_type),
             tags: string_tags,
         }
     }
@@ -38,6 +58,39 @@ impl AddonMetadata {
         self.serialize_json()
     }
 
+    pub fn to_json_with(&self, format: MetadataFormat) -> String {
+        let title = match format {
+            MetadataFormat::Compact => return self.to_json(),
+            MetadataFormat::Canonical => self.title.as_deref(),
+            MetadataFormat::GmadCompatible => None,
+        };
+        let mut json = String::from("{\n");
+        if let Some(title) = title {
+            let _ = writeln!(json, "\t\"title\": {},", json_string(title));
+        }
+        let _ = writeln!(
+            json,
+            "\t\"description\": {},",
+            json_string(&self.description)
+        );
+        let _ = writeln!(json, "\t\"type\": {},", json_string(&self.addon_type));
+        match self.tags.is_empty() {
+            true => json.push_str("\t\"tags\": []\n"),
+            false => {
+                json.push_str("\t\"tags\": [\n");
+                let tags: Vec<String> = self
+                    .tags
+                    .iter()
+                    .map(|tag| format!("\t\t{}", json_string(tag)))
+                    .collect();
+                json.push_str(&tags.join(",\n"));
+                json.push_str("\n\t]\n");
+            }
+        }
+        json.push('}');
+        json
+    }
+
     pub fn set_description(&mut self, desc: String) {
         self.description = desc;
     }
@@ -46,8 +99,7 @@ impl AddonMetadata {
         self.addon_type = Self::type_to_string(&addon_type)
     }
     pub fn set_tags(&mut self, tag1: AddonTag, tag2: AddonTag) {
-        self.tags[0] = Self::tag_to_string(&tag1);
-        self.tags[1] = Self::tag_to_string(&tag2);
+        self.tags = vec![Self::tag_to_string(&tag1), Self::tag_to_string(&tag2)];
     }
 
     pub fn get_description(&self) -> &str {
@@ -58,8 +110,16 @@ impl AddonMetadata {
         Self::string_to_type(&self.addon_type)
     }
 
+    pub fn get_type_raw(&self) -> &str {
+        &self.addon_type
+    }
+
+    pub fn get_tags_raw(&self) -> &[String] {
+        &self.tags
+    }
+
     pub fn get_tags(&self) -> (Option<AddonTag>, Option<AddonTag>) {
-        let opt_t1 = self.tags.get(0).map(|s| Self::string_to_tag(s));
+        let opt_t1 = self.tags.first().map(|s| Self::string_to_tag(s));
         let opt_t2 = self.tags.get(1).map(|s| Self::string_to_tag(s));
         (opt_t1.unwrap_or(None), opt_t2.unwrap_or(None))
     }
@@ -127,3 +187,23 @@ impl AddonMetadata {
         .to_owned()
     }
 }
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
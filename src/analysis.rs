@@ -0,0 +1,231 @@
+//! Analysis of the contents of an archive
+use crate::{EntryView, GMAFile, Result};
+use std::io::{BufRead, Seek};
+
+const LARGEST_FILES: usize = 10;
+
+/// Where a lua file runs, guessed from garry's mod loading conventions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Realm {
+    Server,
+    Client,
+    Shared,
+    /// The path doesn't follow any known convention
+    Unknown,
+}
+
+impl Realm {
+    /// Guesses the realm of a lua file from its path.
+    ///
+    /// Looks at `lua/autorun/{server,client}`, `server`/`client` directories, the `sv_`, `cl_`
+    /// and `sh_` prefixes and the `init.lua`, `cl_init.lua` and `shared.lua` files used by
+    /// gamemodes and scripted entities
+    pub fn of(filename: &str) -> Self {
+        let filename = filename.to_lowercase();
+        let components: Vec<&str> = filename.split('/').filter(|c| !c.is_empty()).collect();
+        let (file_name, directories) = match components.split_last() {
+            Some(split) => split,
+            None => return Realm::Unknown,
+        };
+        if directories.contains(&"server") || file_name.starts_with("sv_") {
+            return Realm::Server;
+        }
+        if directories.contains(&"client") || file_name.starts_with("cl_") {
+            return Realm::Client;
+        }
+        if file_name.starts_with("sh_") || *file_name == "shared.lua" {
+            return Realm::Shared;
+        }
+        match (directories, *file_name) {
+            (["lua", "autorun"], _) => Realm::Shared,
+            ([.., "gamemode"], "init.lua") => Realm::Server,
+            ([.., "entities" | "weapons", _], "init.lua") => Realm::Server,
+            _ => Realm::Unknown,
+        }
+    }
+}
+
+/// Line counts and complexity indicators of a single lua file
+#[derive(Debug, Clone)]
+pub struct LuaFileStats {
+    filename: String,
+    realm: Realm,
+    size: u64,
+    lines: usize,
+    code_lines: usize,
+    comment_lines: usize,
+    blank_lines: usize,
+    functions: usize,
+    branches: usize,
+}
+
+impl LuaFileStats {
+    /// The filename inside the archive
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// The realm the file is expected to run in
+    pub fn realm(&self) -> Realm {
+        self.realm
+    }
+    /// The file size in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// Total number of lines
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+    /// Lines that are not blank or comments
+    pub fn code_lines(&self) -> usize {
+        self.code_lines
+    }
+    /// Lines that only contain a comment
+    pub fn comment_lines(&self) -> usize {
+        self.comment_lines
+    }
+    /// Lines that only contain whitespace
+    pub fn blank_lines(&self) -> usize {
+        self.blank_lines
+    }
+    /// Number of `function` keywords
+    pub fn functions(&self) -> usize {
+        self.functions
+    }
+    /// Number of `if`, `elseif`, `for`, `while` and `repeat` keywords
+    pub fn branches(&self) -> usize {
+        self.branches
+    }
+}
+
+/// Summary returned by [`lua_stats`]
+#[derive(Debug, Clone, Default)]
+pub struct LuaStats {
+    files: Vec<LuaFileStats>,
+}
+
+impl LuaStats {
+    /// The stats of every lua file, in archive order
+    pub fn files(&self) -> &[LuaFileStats] {
+        &self.files
+    }
+    /// The number of lines of code, not counting blank lines and comments, of every file
+    pub fn code_lines(&self) -> usize {
+        self.files.iter().map(|f| f.code_lines).sum()
+    }
+    /// The number of lines of every file
+    pub fn lines(&self) -> usize {
+        self.files.iter().map(|f| f.lines).sum()
+    }
+    /// The number of files in the given realm
+    pub fn file_count(&self, realm: Realm) -> usize {
+        self.files.iter().filter(|f| f.realm == realm).count()
+    }
+    /// The number of `function` keywords in every file
+    pub fn functions(&self) -> usize {
+        self.files.iter().map(|f| f.functions).sum()
+    }
+    /// The number of branching keywords in every file
+    pub fn branches(&self) -> usize {
+        self.files.iter().map(|f| f.branches).sum()
+    }
+    /// The files with the most lines of code, biggest first. At most 10 are returned
+    pub fn largest_files(&self) -> Vec<&LuaFileStats> {
+        let mut files: Vec<&LuaFileStats> = self.files.iter().collect();
+        files.sort_by_key(|f| std::cmp::Reverse(f.code_lines));
+        files.truncate(LARGEST_FILES);
+        files
+    }
+}
+
+/// Reads every `.lua` entry of the archive and counts its lines, functions and branches
+pub fn lua_stats<ReaderType>(archive: &GMAFile<ReaderType>) -> Result<LuaStats>
+where
+    ReaderType: BufRead + Seek,
+{
+    let mut stats = LuaStats::default();
+    for entry in archive.entry_views() {
+        if !entry
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lua"))
+        {
+            continue;
+        }
+        let contents = archive.try_read_entry(&entry.to_entry(), |_, reader| {
+            let mut contents = Vec::new();
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })?;
+        stats
+            .files
+            .push(analyze(entry, &String::from_utf8_lossy(&contents)));
+    }
+    Ok(stats)
+}
+
+fn analyze(entry: EntryView, source: &str) -> LuaFileStats {
+    let mut stats = LuaFileStats {
+        filename: entry.filename().to_owned(),
+        realm: Realm::of(entry.filename()),
+        size: entry.size(),
+        lines: 0,
+        code_lines: 0,
+        comment_lines: 0,
+        blank_lines: 0,
+        functions: 0,
+        branches: 0,
+    };
+    let mut in_block_comment = false;
+    for line in source.lines() {
+        stats.lines += 1;
+        let trimmed = line.trim();
+        if in_block_comment {
+            stats.comment_lines += 1;
+            in_block_comment = !trimmed.contains("]]");
+            continue;
+        }
+        if trimmed.is_empty() {
+            stats.blank_lines += 1;
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix("--") {
+            stats.comment_lines += 1;
+            in_block_comment = comment.starts_with("[[") && !comment[2..].contains("]]");
+            continue;
+        }
+        stats.code_lines += 1;
+        //good enough for an estimate, keywords inside strings are counted too
+        let code = trimmed.split("--").next().unwrap_or_default();
+        for word in code.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            match word {
+                "function" => stats.functions += 1,
+                "if" | "elseif" | "for" | "while" | "repeat" => stats.branches += 1,
+                _ => {}
+            }
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn realm_of() {
+        assert_eq!(Realm::of("lua/autorun/server/sv.lua"), Realm::Server);
+        assert_eq!(Realm::of("lua/autorun/client/menu.lua"), Realm::Client);
+        assert_eq!(Realm::of("lua/autorun/init.lua"), Realm::Shared);
+        assert_eq!(Realm::of("lua/myaddon/sv_net.lua"), Realm::Server);
+        assert_eq!(Realm::of("lua/myaddon/cl_hud.lua"), Realm::Client);
+        assert_eq!(Realm::of("lua/myaddon/sh_config.lua"), Realm::Shared);
+        assert_eq!(Realm::of("gamemodes/gm/gamemode/init.lua"), Realm::Server);
+        assert_eq!(
+            Realm::of("gamemodes/gm/gamemode/cl_init.lua"),
+            Realm::Client
+        );
+        assert_eq!(Realm::of("lua/weapons/gun/shared.lua"), Realm::Shared);
+        assert_eq!(Realm::of("lua/entities/ent/init.lua"), Realm::Server);
+        assert_eq!(Realm::of("lua/myaddon/util.lua"), Realm::Unknown);
+    }
+}
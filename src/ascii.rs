@@ -0,0 +1,110 @@
+use crate::GMAFile;
+use std::io::{BufRead, Seek};
+
+/// A filename with non ascii characters, see [`GMAFile::non_ascii_entries`] and
+/// [`crate::GMABuilder::strict_ascii`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonAsciiName {
+    filename: String,
+    suggestion: String,
+}
+
+impl NonAsciiName {
+    /// Returns None if `filename` is ascii
+    pub(crate) fn check(filename: &str) -> Option<Self> {
+        match filename.is_ascii() {
+            true => None,
+            false => Some(Self {
+                filename: filename.to_owned(),
+                suggestion: transliterate(filename),
+            }),
+        }
+    }
+    /// The offending filename
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// An ascii version of the filename. Ex : `materials/café.vmt` becomes `materials/cafe.vmt`.
+    ///
+    /// Accented latin letters lose their accents and characters without an ascii
+    /// equivalent become '_'
+    pub fn suggestion(&self) -> &str {
+        &self.suggestion
+    }
+}
+
+/// Replaces every non ascii character of `text` with its closest ascii equivalent, or '_'
+pub fn transliterate(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            c if c.is_ascii() => result.push(c),
+            'À'..='Å' => result.push('A'),
+            'à'..='å' => result.push('a'),
+            'Æ' => result.push_str("AE"),
+            'æ' => result.push_str("ae"),
+            'Ç' => result.push('C'),
+            'ç' => result.push('c'),
+            'È'..='Ë' => result.push('E'),
+            'è'..='ë' => result.push('e'),
+            'Ì'..='Ï' => result.push('I'),
+            'ì'..='ï' => result.push('i'),
+            'Ð' => result.push('D'),
+            'ð' => result.push('d'),
+            'Ñ' => result.push('N'),
+            'ñ' => result.push('n'),
+            'Ò'..='Ö' | 'Ø' => result.push('O'),
+            'ò'..='ö' | 'ø' => result.push('o'),
+            'Ù'..='Ü' => result.push('U'),
+            'ù'..='ü' => result.push('u'),
+            'Ý' => result.push('Y'),
+            'ý' | 'ÿ' => result.push('y'),
+            'Þ' => result.push_str("TH"),
+            'þ' => result.push_str("th"),
+            'ß' => result.push_str("ss"),
+            'Œ' => result.push_str("OE"),
+            'œ' => result.push_str("oe"),
+            'Š' => result.push('S'),
+            'š' => result.push('s'),
+            'Ž' => result.push('Z'),
+            'ž' => result.push('z'),
+            'Ł' => result.push('L'),
+            'ł' => result.push('l'),
+            '‘' | '’' => result.push('\''),
+            '“' | '”' => result.push('"'),
+            '–' | '—' => result.push('-'),
+            _ => result.push('_'),
+        }
+    }
+    result
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Every entry whose filename contains non ascii characters, which garry's mod and some
+    /// server setups mishandle, together with a suggested ascii name
+    pub fn non_ascii_entries(&self) -> Vec<NonAsciiName> {
+        self.entry_views()
+            .filter_map(|entry| NonAsciiName::check(entry.filename()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transliterate;
+
+    #[test]
+    fn transliterate_latin() {
+        assert_eq!(transliterate("materials/café.vmt"), "materials/cafe.vmt");
+        assert_eq!(transliterate("Straße/Ærø"), "Strasse/AEro");
+        assert_eq!(transliterate("lua/init.lua"), "lua/init.lua");
+    }
+
+    #[test]
+    fn transliterate_unknown() {
+        assert_eq!(transliterate("sound/音.wav"), "sound/_.wav");
+    }
+}
@@ -1,8 +1,11 @@
-use std::io::{BufRead, Write};
+use std::io::{BufRead, Read, Write};
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum Error {
     InvalidCString,
+    /// A string was longer than the allowed maximum, contains the maximum
+    CStringTooLong(usize),
     InvalidUTF8(std::string::FromUtf8Error),
     IOError(std::io::Error),
 }
@@ -25,7 +28,8 @@ pub trait BinaryReader {
     fn read_u8(&mut self) -> Result<(usize, u8)>;
     fn read_u32(&mut self) -> Result<(usize, u32)>;
     fn read_u64(&mut self) -> Result<(usize, u64)>;
-    fn read_c_string(&mut self) -> Result<(usize, String)>;
+    /// Reads a null terminated string of at most `max_len` bytes, not counting the terminator
+    fn read_c_string(&mut self, max_len: usize) -> Result<(usize, String)>;
 }
 
 impl<T> BinaryReader for T
@@ -50,11 +54,17 @@ where
         Ok((buf.len(), u64::from_le_bytes(buf)))
     }
 
-    fn read_c_string(&mut self) -> Result<(usize, String)> {
+    fn read_c_string(&mut self, max_len: usize) -> Result<(usize, String)> {
         let mut buf = Vec::new();
-        self.read_until(0, &mut buf)?;
+        let limit = (max_len as u64).saturating_add(1);
+        Read::take(&mut *self, limit).read_until(0, &mut buf)?;
         let bytes_read = buf.len();
-        buf.pop(); //we dont need the null terminator
+        if buf.pop() != Some(0) {
+            return match bytes_read > max_len {
+                true => Err(Error::CStringTooLong(max_len)),
+                false => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+            };
+        }
         Ok((bytes_read, String::from_utf8(buf)?))
     }
 }
@@ -94,7 +104,7 @@ where
             return Err(Error::InvalidCString);
         }
 
-        self.write_all(&str_bytes)?;
+        self.write_all(str_bytes)?;
         //write null terminator
         self.write_all(&[0])?;
         Ok(str_bytes.len() + 1)
@@ -165,8 +175,29 @@ mod tests {
     #[test]
     fn read_c_string() {
         let mut memory: &[u8] = b"Hello\0";
-        let (len, val) = memory.read_c_string().unwrap();
+        let (len, val) = memory.read_c_string(16).unwrap();
+        assert_eq!(len, 6);
+        assert_eq!(val, "Hello");
+    }
+    #[test]
+    fn read_c_string_max_len() {
+        let mut memory: &[u8] = b"Hello\0";
+        let (len, val) = memory.read_c_string(5).unwrap();
         assert_eq!(len, 6);
         assert_eq!(val, "Hello");
+
+        let mut memory: &[u8] = b"Hello\0";
+        match memory.read_c_string(4) {
+            Err(Error::CStringTooLong(4)) => {}
+            r => panic!("expected CStringTooLong, got {:?}", r),
+        }
+    }
+    #[test]
+    fn read_c_string_unterminated() {
+        let mut memory: &[u8] = b"Hello";
+        match memory.read_c_string(16) {
+            Err(Error::IOError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+            r => panic!("expected UnexpectedEof, got {:?}", r),
+        }
     }
 }
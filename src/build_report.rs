@@ -0,0 +1,174 @@
+use crate::Result;
+use nanoserde::SerJson;
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Why a file was left out while adding a directory to a [`crate::GMABuilder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The path contains a component starting with a '.'
+    Hidden,
+    /// The file is larger than the configured maximum, contains the file size
+    TooLarge(u64),
+    /// The path filter callback rejected the path
+    Filtered,
+    /// The path matches an ignored pattern, contains the pattern, see [`crate::GMABuilder::ignore`]
+    Ignored(String),
+}
+
+/// A file that was left out of the archive
+#[derive(Debug, Clone)]
+pub struct SkippedFile {
+    pub(crate) filename: String,
+    pub(crate) reason: SkipReason,
+}
+
+impl SkippedFile {
+    /// The filename the file would have inside the archive
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// Why the file was skipped
+    pub fn reason(&self) -> &SkipReason {
+        &self.reason
+    }
+}
+
+/// A file written to the archive
+#[derive(Debug, Clone)]
+pub struct BuiltFile {
+    pub(crate) filename: String,
+    pub(crate) size: u64,
+    pub(crate) crc: u32,
+    pub(crate) modified: Option<SystemTime>,
+}
+
+impl BuiltFile {
+    /// The filename inside the archive
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// The size of the contents
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The crc32 of the contents
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+    /// When the source file was last modified, only captured for files added from disk with
+    /// [`crate::GMABuilder::capture_mtimes`]
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+/// Something in the archive that the game or the workshop might not handle well.
+/// The archive is still written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildWarning {
+    /// The description is `len` bytes long, more than the `max` the workshop and the game display.
+    /// If `truncated` it was cut to fit, see [`crate::GMABuilder::truncate_description`]
+    LongDescription {
+        len: usize,
+        max: usize,
+        truncated: bool,
+    },
+}
+
+/// Where an interrupted [`crate::GMABuilder::write_to_with_report`] stopped, see [`crate::GMABuilder::resume`]
+#[derive(Debug, Clone)]
+pub struct ResumeToken {
+    // offset of the archive inside the writer
+    pub(crate) start: u64,
+    // size and crc32 of the header and file table, to check the resumed builder matches
+    pub(crate) header_len: u64,
+    pub(crate) header_crc: u32,
+    pub(crate) timestamp: u64,
+    pub(crate) position: u64,
+    pub(crate) files: Vec<BuiltFile>,
+}
+
+impl ResumeToken {
+    /// The offset in the output where the first file that wasn't completely written starts,
+    /// everything before it is kept when resuming
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+    /// The files written completely before the interruption
+    pub fn files(&self) -> &[BuiltFile] {
+        &self.files
+    }
+}
+
+/// Summary of an archive written by [`crate::GMABuilder::write_to_with_report`]
+#[derive(Debug, Clone, Default)]
+pub struct BuildReport {
+    pub(crate) files: Vec<BuiltFile>,
+    pub(crate) skipped: Vec<SkippedFile>,
+    pub(crate) warnings: Vec<BuildWarning>,
+    pub(crate) resume_token: Option<ResumeToken>,
+}
+
+impl BuildReport {
+    /// The files written to the archive, in the order they were written
+    pub fn files(&self) -> &[BuiltFile] {
+        &self.files
+    }
+    /// The files left out while adding directories
+    pub fn skipped(&self) -> &[SkippedFile] {
+        &self.skipped
+    }
+    /// The problems found while writing the archive
+    pub fn warnings(&self) -> &[BuildWarning] {
+        &self.warnings
+    }
+    /// What [`crate::GMABuilder::resume`] needs to finish the archive, only for the partial
+    /// report of an [`crate::Error::BuildInterrupted`] error
+    pub fn resume_token(&self) -> Option<&ResumeToken> {
+        self.resume_token.as_ref()
+    }
+
+    /// Writes a json manifest of the files written, to keep next to the archive.
+    ///
+    /// The archive itself has no per file timestamps, tools syncing it can compare the
+    /// `modified` times, in seconds since the unix epoch, with the ones of their own files.
+    /// `modified` is left out for files without a captured time
+    /// ```text
+    /// {"files":[{"filename":"lua/init.lua","size":42,"crc":3735928559,"modified":1595515015}]}
+    /// ```
+    pub fn write_manifest<W: Write>(&self, mut writer: W) -> Result<()> {
+        let manifest = Manifest {
+            files: self
+                .files
+                .iter()
+                .map(|file| ManifestFile {
+                    filename: file.filename.clone(),
+                    size: file.size,
+                    crc: file.crc,
+                    modified: file
+                        .modified
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map(|since| since.as_secs()),
+                })
+                .collect(),
+        };
+        writer.write_all(manifest.serialize_json().as_bytes())?;
+        Ok(())
+    }
+}
+
+#[derive(SerJson)]
+struct Manifest {
+    files: Vec<ManifestFile>,
+}
+
+#[derive(SerJson)]
+struct ManifestFile {
+    filename: String,
+    size: u64,
+    crc: u32,
+    modified: Option<u64>,
+}
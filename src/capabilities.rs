@@ -0,0 +1,100 @@
+use crate::Compression;
+use nanoserde::SerJson;
+
+// (name, enabled) of every cargo feature of this crate
+const FEATURES: &[(&str, bool)] = &[
+    ("brotli", cfg!(feature = "brotli")),
+    ("bytes", cfg!(feature = "bytes")),
+    ("crc32fast", cfg!(feature = "crc32fast")),
+    ("serde", cfg!(feature = "serde")),
+    ("chrono", cfg!(feature = "chrono")),
+    ("difftest", cfg!(feature = "difftest")),
+    ("fuse", cfg!(feature = "fuse")),
+];
+
+/// What this build of the crate supports, see [`capabilities`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    version: &'static str,
+    compression_formats: Vec<Compression>,
+    features: Vec<&'static str>,
+}
+
+impl Capabilities {
+    /// The version of the crate
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+    /// The compression formats archives can be read and written with, including
+    /// [`Compression::None`]
+    pub fn compression_formats(&self) -> &[Compression] {
+        &self.compression_formats
+    }
+    /// The names of the cargo features this build was compiled with
+    pub fn features(&self) -> &[&'static str] {
+        &self.features
+    }
+    /// True if this build was compiled with the cargo feature `name`. Unknown names, including
+    /// features of newer versions, are false
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.contains(&name)
+    }
+    /// The capabilities as a json object, for wrappers in other languages
+    /// ```text
+    /// {"version":"1.0.0","compression_formats":["none","lzma"],"features":["crc32fast"]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let json = CapabilitiesJson {
+            version: self.version.to_owned(),
+            compression_formats: self
+                .compression_formats
+                .iter()
+                .map(|format| compression_name(*format).to_owned())
+                .collect(),
+            features: self.features.iter().map(|f| (*f).to_owned()).collect(),
+        };
+        json.serialize_json()
+    }
+}
+
+#[derive(SerJson)]
+struct CapabilitiesJson {
+    version: String,
+    compression_formats: Vec<String>,
+    features: Vec<String>,
+}
+
+fn compression_name(format: Compression) -> &'static str {
+    match format {
+        Compression::None => "none",
+        Compression::Lzma => "lzma",
+        #[cfg(feature = "brotli")]
+        Compression::Brotli => "brotli",
+    }
+}
+
+/// The compression formats and optional features compiled into this build, so programs and
+/// wrappers can adapt at runtime instead of failing on a missing feature
+/// ```
+/// let capabilities = gma::capabilities();
+/// if !capabilities.has_feature("brotli") {
+///     println!("brotli archives are not supported, {}", capabilities.to_json());
+/// }
+/// ```
+pub fn capabilities() -> Capabilities {
+    let compression_formats = vec![
+        Compression::None,
+        Compression::Lzma,
+        #[cfg(feature = "brotli")]
+        Compression::Brotli,
+    ];
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        compression_formats,
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+    }
+}
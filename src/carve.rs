@@ -0,0 +1,78 @@
+use crate::{GMAFile, LoadOptions, IDENT};
+use std::{convert::TryFrom, io::Cursor};
+
+/// Iterator over the archives found in a larger blob, see [`carve`]
+#[derive(Debug, Clone)]
+pub struct Carver<'a> {
+    data: &'a [u8],
+    position: usize,
+    options: LoadOptions,
+}
+
+impl<'a> Iterator for Carver<'a> {
+    type Item = (u64, GMAFile<Cursor<&'a [u8]>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let found = self
+                .data
+                .get(self.position..)?
+                .windows(IDENT.len())
+                .position(|window| window == IDENT)?;
+            let start = self.position + found;
+            match self.load_at(start) {
+                Some((len, archive)) => {
+                    self.position = start + len;
+                    return Some((start as u64, archive));
+                }
+                None => self.position = start + 1,
+            }
+        }
+    }
+}
+
+impl<'a> Carver<'a> {
+    // The archive starting at `start` and its length in bytes, including the crc if it matches
+    fn load_at(&self, start: usize) -> Option<(usize, GMAFile<Cursor<&'a [u8]>>)> {
+        let rest = &self.data[start..];
+        let archive = self.options.load_from_memory(rest).ok()?;
+        let end = usize::try_from(archive.archive_end()).ok()?;
+        // whatever follows an embedded archive is read as its crc, only keep it if it matches
+        let len = match archive.verify_archive_crc() {
+            Ok(()) => end + 4,
+            Err(_) => end,
+        };
+        let archive = self.options.load_from_memory(&rest[..len]).ok()?;
+        Some((len, archive))
+    }
+}
+
+/// Searches `data` for embedded archives, like the ones in cache files, memory dumps or other
+/// container formats, and returns each one with the offset where it starts.
+///
+/// Every occurrence of the `GMAD` ident is tried and the ones that don't parse are skipped.
+/// After an archive is found the search continues after its end, so archives stored inside
+/// another archive's entries are not returned. Compressed archives have no ident and can't be
+/// found. See [`LoadOptions::carve`] to load them with other options
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let mut blob = b"some cache header".to_vec();
+/// blob.extend_from_slice(dummy_buffer);
+/// for (offset, archive) in gma::carve(&blob) {
+///     println!("{} at {}", archive.name(), offset);
+/// }
+/// ```
+pub fn carve(data: &[u8]) -> Carver<'_> {
+    LoadOptions::new().carve(data)
+}
+
+impl LoadOptions {
+    /// Searches `data` for embedded archives and loads them with these options, see [`carve`]
+    pub fn carve<'a>(&self, data: &'a [u8]) -> Carver<'a> {
+        Carver {
+            data,
+            position: 0,
+            options: self.clone(),
+        }
+    }
+}
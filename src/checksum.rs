@@ -0,0 +1,93 @@
+#[cfg(not(feature = "crc32fast"))]
+use crc::{Crc, Digest, CRC_32_ISO_HDLC};
+use std::io::Read;
+
+#[cfg(not(feature = "crc32fast"))]
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Incremental crc32 used for the entry checksums.
+///
+/// With the `crc32fast` feature this uses the simd/hardware accelerated implementation from
+/// the crc32fast crate, otherwise the table based one from the crc crate
+pub struct Crc32Hasher {
+    #[cfg(not(feature = "crc32fast"))]
+    digest: Digest<'static, u32>,
+    #[cfg(feature = "crc32fast")]
+    hasher: crc32fast::Hasher,
+}
+
+#[cfg(not(feature = "crc32fast"))]
+impl Crc32Hasher {
+    pub fn new() -> Self {
+        Self {
+            digest: CRC32.digest(),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.digest.update(bytes);
+    }
+
+    pub fn finalize(self) -> u32 {
+        self.digest.finalize()
+    }
+}
+
+#[cfg(feature = "crc32fast")]
+impl Crc32Hasher {
+    pub fn new() -> Self {
+        Self {
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+
+    pub fn finalize(self) -> u32 {
+        self.hasher.finalize()
+    }
+}
+
+/// Reader that computes the crc32 of every byte read through it
+pub struct Crc32Reader<R> {
+    reader: R,
+    hasher: Crc32Hasher,
+}
+
+impl<R: Read> Crc32Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            hasher: Crc32Hasher::new(),
+        }
+    }
+
+    /// Reads whatever is left in the underlying reader and returns the final crc32
+    pub fn finish(mut self) -> std::io::Result<u32> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for Crc32Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Crc32Hasher;
+
+    #[test]
+    fn check_value() {
+        let mut hasher = Crc32Hasher::new();
+        hasher.update(b"1234");
+        hasher.update(b"56789");
+        assert_eq!(hasher.finalize(), 0xCBF43926);
+    }
+}
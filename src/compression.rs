@@ -0,0 +1,248 @@
+use crate::{disk::TempFile, Error, Result};
+use std::io::{self, BufRead, BufWriter, Seek, SeekFrom, Write};
+
+/// The compression applied to a whole archive.
+///
+/// More formats can be added by features, so matches on it need a wildcard arm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    None,
+    /// The lzma format used by some files downloaded from the workshop
+    Lzma,
+    /// Brotli, used to serve archives to web tooling. Requires the `brotli` feature
+    #[cfg(feature = "brotli")]
+    Brotli,
+}
+
+/// Decompresses a whole archive into memory, trying every supported format.
+/// The reader is expected to be positioned at the start of the compressed data
+pub fn decompress<R: BufRead + Seek>(reader: &mut R) -> Result<(Compression, Vec<u8>)> {
+    decompress_limited(reader, u64::MAX)
+}
+
+/// Same as [`decompress`] but fails with [`Error::DecompressedTooLarge`] once the decompressed
+/// data exceeds `max_size` bytes, without decompressing the rest
+pub fn decompress_limited<R: BufRead + Seek>(
+    reader: &mut R,
+    max_size: u64,
+) -> Result<(Compression, Vec<u8>)> {
+    match decompress_spilling(reader, max_size, None)? {
+        (format, Decompressed::Memory(data)) => Ok((format, data)),
+        (_, Decompressed::Spilled(_)) => unreachable!("nothing is spilled without a threshold"),
+    }
+}
+
+/// Where [`decompress_spilling`] put the decompressed archive
+#[derive(Debug)]
+pub enum Decompressed {
+    Memory(Vec<u8>),
+    /// A temporary file, positioned at its start
+    Spilled(TempFile),
+}
+
+/// Same as [`decompress_limited`] but once the decompressed data grows past `spill_threshold`
+/// bytes it is moved to a temporary file and the rest is decompressed into it
+pub fn decompress_spilling<R: BufRead + Seek>(
+    reader: &mut R,
+    max_size: u64,
+    spill_threshold: Option<u64>,
+) -> Result<(Compression, Decompressed)> {
+    let start = reader.stream_position()?;
+    // reject what the header already declares too large, the size is checked again while decoding
+    if let Ok(Some(size)) = lzma_unpacked_size(reader) {
+        if size > max_size && probe(reader).ok() == Some(Compression::Lzma) {
+            return Err(Error::DecompressedTooLarge(max_size));
+        }
+    }
+    let mut output = DecompressWriter::new(max_size, spill_threshold);
+    let lzma_error = match lzma_rs::lzma_decompress(reader, &mut output) {
+        Ok(()) => return Ok((Compression::Lzma, output.finish()?)),
+        Err(e) => match output.error.take() {
+            Some(error) => return Err(error),
+            None => e,
+        },
+    };
+
+    #[cfg(feature = "brotli")]
+    {
+        reader.seek(SeekFrom::Start(start))?;
+        let mut output = DecompressWriter::new(max_size, spill_threshold);
+        let mut decompressor = brotli::Decompressor::new(&mut *reader, 4096);
+        let result = io::copy(&mut decompressor, &mut output);
+        if let Some(error) = output.error.take() {
+            if output.head == crate::IDENT {
+                return Err(error);
+            }
+        }
+        if result.is_ok() && output.head == crate::IDENT {
+            return Ok((Compression::Brotli, output.finish()?));
+        }
+    }
+
+    reader.seek(SeekFrom::Start(start))?;
+    Err(Error::CompressionError(lzma_error))
+}
+
+// Collects the decompressed data, in memory until it grows past `spill_threshold` and in a
+// temporary file after that. Fails once it grows past `max_size`
+struct DecompressWriter {
+    buffer: Vec<u8>,
+    file: Option<BufWriter<TempFile>>,
+    written: u64,
+    // the first bytes written, to check for the ident
+    head: Vec<u8>,
+    max_size: u64,
+    spill_threshold: Option<u64>,
+    // why writing failed, the decompressors only return a generic io error
+    error: Option<Error>,
+}
+
+impl DecompressWriter {
+    fn new(max_size: u64, spill_threshold: Option<u64>) -> Self {
+        Self {
+            buffer: Vec::new(),
+            file: None,
+            written: 0,
+            head: Vec::with_capacity(crate::IDENT.len()),
+            max_size,
+            spill_threshold,
+            error: None,
+        }
+    }
+
+    fn finish(self) -> Result<Decompressed> {
+        match self.file {
+            Some(file) => {
+                let mut file = file.into_inner().map_err(|e| e.into_error())?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok(Decompressed::Spilled(file))
+            }
+            None => Ok(Decompressed::Memory(self.buffer)),
+        }
+    }
+
+    // `written` is the total including `buf`
+    fn store(&mut self, buf: &[u8], written: u64) -> io::Result<()> {
+        let over_threshold = self.spill_threshold.is_some_and(|t| written > t);
+        if self.file.is_none() && over_threshold {
+            let mut file = BufWriter::new(TempFile::new()?);
+            file.write_all(&self.buffer)?;
+            self.buffer = Vec::new();
+            self.file = Some(file);
+        }
+        match self.file.as_mut() {
+            Some(file) => file.write_all(buf),
+            None => {
+                self.buffer.extend_from_slice(buf);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for DecompressWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.written.saturating_add(buf.len() as u64);
+        if written > self.max_size {
+            self.error = Some(Error::DecompressedTooLarge(self.max_size));
+            return Err(io::Error::other("the decompressed size limit was exceeded"));
+        }
+        let missing = crate::IDENT.len().saturating_sub(self.head.len());
+        self.head.extend_from_slice(&buf[..missing.min(buf.len())]);
+        if let Err(e) = self.store(buf, written) {
+            self.error = Some(Error::IOError(io::Error::new(e.kind(), e.to_string())));
+            return Err(e);
+        }
+        self.written = written;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Detects the compression of an archive by decompressing just enough to find the ident,
+/// without decompressing the whole archive. The reader is left where it started
+pub fn probe<R: BufRead + Seek>(reader: &mut R) -> Result<Compression> {
+    let start = reader.stream_position()?;
+    if reader.fill_buf()?.starts_with(&crate::IDENT) {
+        return Ok(Compression::None);
+    }
+
+    // ignore the unpacked size in the header and stop after the ident
+    let options = lzma_rs::decompress::Options {
+        unpacked_size: lzma_rs::decompress::UnpackedSize::ReadHeaderButUseProvided(Some(
+            crate::IDENT.len() as u64,
+        )),
+        ..Default::default()
+    };
+    let mut ident = Vec::with_capacity(crate::IDENT.len());
+    let lzma_result = lzma_rs::lzma_decompress_with_options(reader, &mut ident, &options);
+    reader.seek(SeekFrom::Start(start))?;
+    let lzma_error = match lzma_result {
+        Ok(()) if ident == crate::IDENT => return Ok(Compression::Lzma),
+        Ok(()) => lzma_rs::error::Error::LzmaError("the data is not a gma archive".to_owned()),
+        Err(e) => e,
+    };
+
+    #[cfg(feature = "brotli")]
+    {
+        use std::io::Read;
+        let mut ident = [0u8; 4];
+        let brotli_result = brotli::Decompressor::new(&mut *reader, 4096).read_exact(&mut ident);
+        reader.seek(SeekFrom::Start(start))?;
+        if brotli_result.is_ok() && ident == crate::IDENT {
+            return Ok(Compression::Brotli);
+        }
+    }
+
+    Err(Error::CompressionError(lzma_error))
+}
+
+/// The uncompressed size stored in the header of lzma data, None if the encoder didn't record it.
+/// The header isn't validated, see [`probe`]. The reader is left where it started
+pub fn lzma_unpacked_size<R: BufRead + Seek>(reader: &mut R) -> Result<Option<u64>> {
+    use std::convert::TryInto;
+    // properties byte and dictionary size, followed by the unpacked size
+    const SIZE_OFFSET: usize = 5;
+    let start = reader.stream_position()?;
+    let mut header = [0u8; SIZE_OFFSET + 8];
+    let result = reader.read_exact(&mut header);
+    reader.seek(SeekFrom::Start(start))?;
+    result?;
+    let size = u64::from_le_bytes(header[SIZE_OFFSET..].try_into().unwrap());
+    Ok(match size {
+        u64::MAX => None,
+        size => Some(size),
+    })
+}
+
+/// Compresses `input` into `output` with the given format.
+/// Lzma data records the uncompressed size in its header, see [`lzma_unpacked_size`]
+pub fn compress<W: Write>(format: Compression, input: &[u8], output: &mut W) -> Result<()> {
+    match format {
+        Compression::None => {
+            output.write_all(input)?;
+        }
+        Compression::Lzma => {
+            let options = lzma_rs::compress::Options {
+                unpacked_size: lzma_rs::compress::UnpackedSize::WriteToHeader(Some(
+                    input.len() as u64
+                )),
+            };
+            lzma_rs::lzma_compress_with_options(&mut &input[..], output, &options)?;
+        }
+        #[cfg(feature = "brotli")]
+        Compression::Brotli => {
+            let mut compressor = brotli::CompressorWriter::new(output, 4096, 9, 22);
+            compressor.write_all(input)?;
+            compressor.flush()?;
+        }
+    }
+    Ok(())
+}
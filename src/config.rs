@@ -0,0 +1,144 @@
+use std::sync::RwLock;
+
+static GLOBAL: RwLock<Config> = RwLock::new(Config::new());
+
+/// Process-wide defaults, read by [`crate::LoadOptions::new`], [`crate::GMABuilder::new`] and
+/// every function that opens or creates files.
+///
+/// Set them once at startup with [`Config::set_global`] or [`Config::update_global`], options
+/// passed to a single call still override them. Changing the defaults doesn't affect options and
+/// builders that were already created
+/// ```
+/// gma::Config::update_global(|config| {
+///     config.max_string_length(4096).strict_file_numbers(true);
+/// });
+/// // starts from the global defaults
+/// let mut options = gma::LoadOptions::new();
+/// // overrides them for this call only
+/// options.strict_file_numbers(false);
+/// # gma::Config::set_global(gma::Config::new());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub(crate) max_string_length: usize,
+    pub(crate) strict_file_numbers: bool,
+    pub(crate) case_insensitive_lookups: bool,
+    pub(crate) strict_ascii: bool,
+    pub(crate) strict_whitelist: bool,
+    pub(crate) lenient: bool,
+    pub(crate) verify_crc: bool,
+    pub(crate) max_decompressed_size: Option<u64>,
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) buffer_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    /// The library defaults, also the initial global defaults
+    pub const fn new() -> Self {
+        Self {
+            max_string_length: 1024 * 1024,
+            strict_file_numbers: false,
+            case_insensitive_lookups: false,
+            strict_ascii: false,
+            strict_whitelist: false,
+            lenient: false,
+            verify_crc: false,
+            max_decompressed_size: None,
+            max_entries: None,
+            buffer_size: 8 * 1024,
+        }
+    }
+
+    /// A copy of the current global defaults
+    pub fn global() -> Self {
+        // a Config is always valid so a poisoned lock is still usable
+        *GLOBAL
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Replaces the global defaults
+    pub fn set_global(config: Config) {
+        *GLOBAL
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+    }
+
+    /// Changes the global defaults in place, other threads see either all of the changes or none
+    pub fn update_global<F: FnOnce(&mut Config)>(update: F) {
+        update(
+            &mut GLOBAL
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+    }
+
+    /// Default for [`crate::LoadOptions::max_string_length`]. Default : 1 MiB
+    pub fn max_string_length(&mut self, max_len: usize) -> &mut Self {
+        self.max_string_length = max_len;
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::strict_file_numbers`]. Default : false
+    pub fn strict_file_numbers(&mut self, strict: bool) -> &mut Self {
+        self.strict_file_numbers = strict;
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::case_insensitive_lookups`]. Default : false
+    pub fn case_insensitive_lookups(&mut self, ignore_case: bool) -> &mut Self {
+        self.case_insensitive_lookups = ignore_case;
+        self
+    }
+
+    /// Default for [`crate::GMABuilder::strict_ascii`]. Default : false
+    pub fn strict_ascii(&mut self, strict: bool) -> &mut Self {
+        self.strict_ascii = strict;
+        self
+    }
+
+    /// Default for [`crate::GMABuilder::strict_whitelist`]. Default : false
+    pub fn strict_whitelist(&mut self, strict: bool) -> &mut Self {
+        self.strict_whitelist = strict;
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::lenient`]. Default : false
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::verify_crc`]. Default : false
+    pub fn verify_crc(&mut self, verify: bool) -> &mut Self {
+        self.verify_crc = verify;
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::max_decompressed_size`]. Default : no limit
+    pub fn max_decompressed_size(&mut self, max_size: u64) -> &mut Self {
+        self.max_decompressed_size = Some(max_size);
+        self
+    }
+
+    /// Default for [`crate::LoadOptions::max_entries`]. Default : no limit
+    pub fn max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// The capacity in bytes of the buffered readers and writers used for files opened or
+    /// created by this crate, like in [`crate::open`] or [`crate::GMABuilder::write_to_path`].
+    /// A capacity of 0 is raised to 1 byte, a reader without a buffer can't read anything.
+    /// Default : 8 KiB
+    pub fn buffer_size(&mut self, bytes: usize) -> &mut Self {
+        self.buffer_size = bytes.max(1);
+        self
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{FileEntry, GMAFile};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Seek},
+};
+
+/// A path present in two archives, see [`conflicts`]
+#[derive(Debug, Clone, Copy)]
+pub struct Conflict<'a, 'b> {
+    first: &'a FileEntry,
+    second: &'b FileEntry,
+}
+
+impl<'a, 'b> Conflict<'a, 'b> {
+    /// The entry in the first archive
+    pub fn first(&self) -> &'a FileEntry {
+        self.first
+    }
+    /// The entry in the second archive
+    pub fn second(&self) -> &'b FileEntry {
+        self.second
+    }
+    /// The filename, as stored in the first archive
+    pub fn filename(&self) -> &'a str {
+        self.first.filename()
+    }
+    /// True if both entries have the same size and crc32, meaning whichever addon wins
+    /// the override makes no difference
+    pub fn same_contents(&self) -> bool {
+        self.first.size() == self.second.size() && self.first.crc() == self.second.crc()
+    }
+}
+
+/// Lists the paths present in both archives, in the order of the first archive.
+///
+/// Paths are compared case-insensitively, like garry's mod does when mounting addons.
+/// Use [`Conflict::same_contents`] to tell real overrides apart from identical copies
+pub fn conflicts<'a, 'b, A, B>(
+    first: &'a GMAFile<A>,
+    second: &'b GMAFile<B>,
+) -> Vec<Conflict<'a, 'b>>
+where
+    A: BufRead + Seek,
+    B: BufRead + Seek,
+{
+    let mut second_entries: HashMap<String, &'b FileEntry> = HashMap::new();
+    for entry in second.entries() {
+        second_entries
+            .entry(entry.filename().to_lowercase())
+            .or_insert(entry);
+    }
+    first
+        .entries()
+        .filter_map(|entry| {
+            second_entries
+                .get(&entry.filename().to_lowercase())
+                .map(|second| Conflict {
+                    first: entry,
+                    second,
+                })
+        })
+        .collect()
+}
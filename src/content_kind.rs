@@ -0,0 +1,93 @@
+/// The kind of content of an entry, detected from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// Valve texture format
+    Vtf,
+    /// Studio model
+    Mdl,
+    /// Studio model vertex data
+    Vvd,
+    /// Compiled map
+    Bsp,
+    Wav,
+    Mp3,
+    Ogg,
+    Png,
+    Jpeg,
+    /// Precompiled lua or luajit bytecode
+    CompiledLua,
+    /// Another gma archive
+    Gma,
+    /// None of the known signatures matched
+    Unknown,
+}
+
+impl ContentKind {
+    /// The amount of bytes [`ContentKind::from_magic`] needs to detect every kind
+    pub const MAGIC_LEN: usize = 12;
+
+    /// Detects the content kind from the first bytes of a file
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        const SIGNATURES: &[(&[u8], ContentKind)] = &[
+            (b"VTF\0", ContentKind::Vtf),
+            (b"IDST", ContentKind::Mdl),
+            (b"IDSV", ContentKind::Vvd),
+            (b"VBSP", ContentKind::Bsp),
+            (b"OggS", ContentKind::Ogg),
+            (b"ID3", ContentKind::Mp3),
+            (b"\x89PNG\r\n\x1a\n", ContentKind::Png),
+            (b"\xff\xd8\xff", ContentKind::Jpeg),
+            (b"\x1bLJ", ContentKind::CompiledLua),
+            (b"\x1bLua", ContentKind::CompiledLua),
+            (b"GMAD", ContentKind::Gma),
+        ];
+
+        if let Some((_, kind)) = SIGNATURES
+            .iter()
+            .find(|(signature, _)| bytes.starts_with(signature))
+        {
+            return *kind;
+        }
+        if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+            return ContentKind::Wav;
+        }
+        //mp3 files without an id3 tag start straight at a frame sync
+        if bytes.len() >= 2 && bytes[0] == 0xff && bytes[1] & 0xe0 == 0xe0 {
+            return ContentKind::Mp3;
+        }
+        ContentKind::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_magic() {
+        assert_eq!(
+            ContentKind::from_magic(b"VTF\0\x07\0\0\0"),
+            ContentKind::Vtf
+        );
+        assert_eq!(ContentKind::from_magic(b"IDST0\0\0\0"), ContentKind::Mdl);
+        assert_eq!(ContentKind::from_magic(b"VBSP\x14\0\0\0"), ContentKind::Bsp);
+        assert_eq!(
+            ContentKind::from_magic(b"RIFF\x24\0\0\0WAVEfmt "),
+            ContentKind::Wav
+        );
+        assert_eq!(
+            ContentKind::from_magic(b"\xff\xfb\x90\x64"),
+            ContentKind::Mp3
+        );
+        assert_eq!(
+            ContentKind::from_magic(b"\x1bLJ\x01"),
+            ContentKind::CompiledLua
+        );
+        assert_eq!(
+            ContentKind::from_magic(b"print('hi')"),
+            ContentKind::Unknown
+        );
+        assert_eq!(ContentKind::from_magic(b"RIFF"), ContentKind::Unknown);
+        assert_eq!(ContentKind::from_magic(b""), ContentKind::Unknown);
+    }
+}
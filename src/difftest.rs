@@ -0,0 +1,395 @@
+//! Differential testing against gmad, the official tool used to create and extract addons.
+//!
+//! [`generate_corpus`] creates random but valid addon folders, [`compare`] packs each of them with
+//! both gmad and this crate, reads each archive with the other tool and reports every
+//! [`Divergence`] in the parsed header, the file table or the extracted contents.
+//! Only available with the `difftest` feature
+//! ```no_run
+//! # fn main() -> gma::Result<()> {
+//! if let Some(gmad) = gma::difftest::Gmad::find() {
+//!     let work_dir = std::env::temp_dir().join("gma-difftest");
+//!     for addon in gma::difftest::generate_corpus(1, 10) {
+//!         for divergence in gma::difftest::compare(&gmad, &addon, &work_dir)? {
+//!             println!("{}: {}", addon.title, divergence);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::{AddonSource, AddonTag, AddonType, GMABuilder, Result};
+use std::{
+    ffi::OsStr,
+    fmt::Display,
+    io::Cursor,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const TYPES: [(&str, AddonType); 5] = [
+    ("gamemode", AddonType::Gamemode),
+    ("weapon", AddonType::Weapon),
+    ("tool", AddonType::Tool),
+    ("model", AddonType::Model),
+    ("servercontent", AddonType::ServerContent),
+];
+
+const TAGS: [(&str, AddonTag); 5] = [
+    ("fun", AddonTag::Fun),
+    ("roleplay", AddonTag::Roleplay),
+    ("build", AddonTag::Build),
+    ("realism", AddonTag::Realism),
+    ("cartoon", AddonTag::Cartoon),
+];
+
+// directories and extensions that are always whitelisted
+const LOCATIONS: [(&str, &str); 6] = [
+    ("lua/autorun", "lua"),
+    ("lua/autorun/client", "lua"),
+    ("materials/difftest", "vmt"),
+    ("materials/difftest/sub", "png"),
+    ("sound/difftest", "wav"),
+    ("models/difftest", "mdl"),
+];
+
+const WORDS: [&str; 8] = [
+    "alpha",
+    "bravo",
+    "charlie",
+    "delta",
+    "echo",
+    "fox\"trot",
+    "golf\\hotel",
+    "india",
+];
+
+/// An addon folder created by [`generate_corpus`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedAddon {
+    pub title: String,
+    pub description: String,
+    pub addon_type: AddonType,
+    pub addon_tags: Vec<AddonTag>,
+    /// (filename, contents), filenames are lowercase and unique
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+impl GeneratedAddon {
+    /// Writes the files and an `addon.json` into `dir`, which must not exist yet or be empty
+    pub fn write_folder(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let type_name = TYPES
+            .iter()
+            .find(|(_, ty)| *ty == self.addon_type)
+            .map_or("tool", |(name, _)| name);
+        let tags: Vec<String> = self
+            .addon_tags
+            .iter()
+            .filter_map(|tag| TAGS.iter().find(|(_, t)| t == tag))
+            .map(|(name, _)| format!("\"{}\"", name))
+            .collect();
+        let addon_json = format!(
+            "{{\n\t\"title\": \"{}\",\n\t\"description\": \"{}\",\n\t\"type\": \"{}\",\n\t\"tags\": [{}],\n\t\"ignore\": []\n}}\n",
+            json_escape(&self.title),
+            json_escape(&self.description),
+            type_name,
+            tags.join(", ")
+        );
+        std::fs::write(dir.join("addon.json"), addon_json)?;
+        for (filename, contents) in self.files.iter() {
+            let path = dir.join(filename);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the addon in memory with [`GMABuilder`]
+    pub fn to_gma(&self) -> Result<Vec<u8>> {
+        let mut builder = GMABuilder::new();
+        builder
+            .name(self.title.as_str())
+            .description(self.description.as_str())
+            .addon_type(self.addon_type);
+        for tag in self.addon_tags.iter() {
+            builder.addon_tag(*tag);
+        }
+        for (filename, contents) in self.files.iter() {
+            builder.file_from_bytes(filename.as_str(), contents.clone());
+        }
+        let mut buffer = Vec::new();
+        builder.write_to(Cursor::new(&mut buffer))?;
+        Ok(buffer)
+    }
+}
+
+/// Generates `count` addons. The same seed always generates the same addons
+pub fn generate_corpus(seed: u64, count: usize) -> Vec<GeneratedAddon> {
+    // xorshift needs a non zero state
+    let mut rng = XorShift(seed | 1);
+    (0..count).map(|index| generate(&mut rng, index)).collect()
+}
+
+fn generate(rng: &mut XorShift, index: usize) -> GeneratedAddon {
+    let title = format!("difftest {} {}", index, rng.pick(&WORDS));
+    let description = (0..rng.below(6))
+        .map(|_| rng.pick(&WORDS).to_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let addon_tags = (0..rng.below(3)).map(|_| rng.pick(&TAGS).1).collect();
+    let mut files = Vec::new();
+    for file in 0..1 + rng.below(12) {
+        let (dir, extension) = rng.pick(&LOCATIONS);
+        let filename = format!("{}/file_{}_{}.{}", dir, file, rng.below(1000), extension);
+        // some files are empty and some span several buffer sizes
+        let len = match rng.below(4) {
+            0 => 0,
+            1 => rng.below(16),
+            _ => rng.below(20_000),
+        };
+        let contents = (0..len).map(|_| rng.next() as u8).collect();
+        files.push((filename, contents));
+    }
+    GeneratedAddon {
+        title,
+        description,
+        addon_type: rng.pick(&TYPES).1,
+        addon_tags,
+        files,
+    }
+}
+
+/// A difference between gmad and this crate found by [`compare`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// A header field of the archive created by gmad differs from the one created by this crate
+    Header {
+        field: &'static str,
+        ours: String,
+        gmad: String,
+    },
+    /// The two archives don't have the same filenames in the same order
+    Entries {
+        ours: Vec<String>,
+        gmad: Vec<String>,
+    },
+    /// The contents of an entry in the archive created by gmad, as read by this crate, are not
+    /// the contents of the file in the folder
+    Contents { filename: String },
+    /// The file tables have the same filenames but their bytes differ starting at `offset`
+    EntryTable { offset: usize },
+    /// gmad did not extract the file from the archive created by this crate as it was in the folder
+    Extracted { filename: String, reason: String },
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Header { field, ours, gmad } => write!(
+                f,
+                "header field {} is '{}' but gmad wrote '{}'",
+                field, ours, gmad
+            ),
+            Self::Entries { ours, gmad } => {
+                write!(f, "entries are {:?} but gmad wrote {:?}", ours, gmad)
+            }
+            Self::Contents { filename } => {
+                write!(f, "contents of '{}' in gmad's archive differ", filename)
+            }
+            Self::EntryTable { offset } => {
+                write!(f, "file tables differ starting at byte {}", offset)
+            }
+            Self::Extracted { filename, reason } => {
+                write!(f, "gmad extracted '{}' wrong : {}", filename, reason)
+            }
+        }
+    }
+}
+
+/// The gmad executable
+#[derive(Debug, Clone)]
+pub struct Gmad {
+    path: PathBuf,
+}
+
+impl Gmad {
+    /// Uses the executable at `path`
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Looks for `gmad`, `gmad_linux` or `gmad.exe` in the directories of the `PATH` variable
+    pub fn find() -> Option<Self> {
+        let paths = std::env::var_os("PATH")?;
+        std::env::split_paths(&paths)
+            .flat_map(|dir| {
+                ["gmad", "gmad_linux", "gmad.exe"]
+                    .iter()
+                    .map(move |name| dir.join(name))
+            })
+            .find(|path| path.is_file())
+            .map(Self::new)
+    }
+
+    /// Runs `gmad create -folder <folder> -out <out>`
+    pub fn create(&self, folder: &Path, out: &Path) -> Result<()> {
+        self.run(&[
+            OsStr::new("create"),
+            OsStr::new("-folder"),
+            folder.as_os_str(),
+            OsStr::new("-out"),
+            out.as_os_str(),
+        ])
+    }
+
+    /// Runs `gmad extract -file <file> -out <out>`
+    pub fn extract(&self, file: &Path, out: &Path) -> Result<()> {
+        self.run(&[
+            OsStr::new("extract"),
+            OsStr::new("-file"),
+            file.as_os_str(),
+            OsStr::new("-out"),
+            out.as_os_str(),
+        ])
+    }
+
+    fn run(&self, args: &[&OsStr]) -> Result<()> {
+        let output = Command::new(&self.path).args(args).output()?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "gmad exited with {} : {}",
+                output.status,
+                String::from_utf8_lossy(&output.stdout).trim()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Packs `addon` with gmad and with this crate and returns every divergence between the two.
+///
+/// `work_dir` is used for the folder and the archives and is removed and recreated on every call.
+/// Errors are returned when a tool fails to run or can't read the other's archive at all
+pub fn compare(gmad: &Gmad, addon: &GeneratedAddon, work_dir: &Path) -> Result<Vec<Divergence>> {
+    if work_dir.exists() {
+        std::fs::remove_dir_all(work_dir)?;
+    }
+    let folder = work_dir.join("addon");
+    addon.write_folder(&folder)?;
+
+    let gmad_path = work_dir.join("gmad.gma");
+    gmad.create(&folder, &gmad_path)?;
+    let theirs = crate::open(&gmad_path)?;
+    let ours = AddonSource::Folder(folder).load()?;
+
+    let mut divergences = Vec::new();
+    let mut header = |field: &'static str, ours: String, gmad: String| {
+        if ours != gmad {
+            divergences.push(Divergence::Header { field, ours, gmad });
+        }
+    };
+    header("name", ours.name().to_owned(), theirs.name().to_owned());
+    header(
+        "description",
+        ours.description().to_owned(),
+        theirs.description().to_owned(),
+    );
+    header(
+        "author",
+        ours.author().to_owned(),
+        theirs.author().to_owned(),
+    );
+    header(
+        "type",
+        format!("{:?}", ours.addon_type()),
+        format!("{:?}", theirs.addon_type()),
+    );
+    header(
+        "tags",
+        format!("{:?}", ours.addon_tags()),
+        format!("{:?}", theirs.addon_tags()),
+    );
+    header(
+        "version",
+        ours.version().to_string(),
+        theirs.version().to_string(),
+    );
+
+    let our_names: Vec<String> = ours.entries().map(|e| e.filename().to_owned()).collect();
+    let their_names: Vec<String> = theirs.entries().map(|e| e.filename().to_owned()).collect();
+    if our_names != their_names {
+        divergences.push(Divergence::Entries {
+            ours: our_names,
+            gmad: their_names,
+        });
+    } else {
+        let (our_table, their_table) = (ours.raw_entry_table()?, theirs.raw_entry_table()?);
+        if our_table != their_table {
+            let offset = our_table
+                .iter()
+                .zip(their_table.iter())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| our_table.len().min(their_table.len()));
+            divergences.push(Divergence::EntryTable { offset });
+        }
+    }
+    for (filename, contents) in addon.files.iter() {
+        let entry = match theirs.get(filename) {
+            Some(entry) => entry,
+            // already reported as an entries divergence
+            None => continue,
+        };
+        let read = theirs.read_entry_verified(entry, |_, reader| {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer).map(|_| buffer)
+        });
+        if !matches!(read, Ok(Ok(ref read)) if read == contents) {
+            divergences.push(Divergence::Contents {
+                filename: filename.clone(),
+            });
+        }
+    }
+
+    let our_path = work_dir.join("ours.gma");
+    std::fs::write(&our_path, addon.to_gma()?)?;
+    let extracted = work_dir.join("extracted");
+    gmad.extract(&our_path, &extracted)?;
+    for (filename, contents) in addon.files.iter() {
+        let reason = match std::fs::read(extracted.join(filename)) {
+            Ok(read) if &read == contents => continue,
+            Ok(read) => format!("{} bytes instead of {}", read.len(), contents.len()),
+            Err(e) => e.to_string(),
+        };
+        divergences.push(Divergence::Extracted {
+            filename: filename.clone(),
+            reason,
+        });
+    }
+    Ok(divergences)
+}
+
+fn json_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
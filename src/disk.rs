@@ -0,0 +1,147 @@
+use crate::{Config, Error, Result};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Returns the amount of bytes available to the current user on the filesystem containing `path`,
+/// or None if it can't be determined on this platform
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> io::Result<Option<u64>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    //SAFETY: c_path is a valid null terminated string and stat is a valid statvfs struct
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> io::Result<Option<u64>> {
+    Ok(None)
+}
+
+/// Fails with [`Error::InsufficientSpace`] if the filesystem containing `dir` does not have
+/// `needed` bytes available
+pub fn check_available_space(dir: &Path, needed: u64) -> Result<()> {
+    match available_space(dir)? {
+        Some(available) if available < needed => {
+            Err(Error::InsufficientSpace { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks the available space for `needed` bytes, then calls `write` with a temporary file in the
+/// same directory as `path` that is renamed to `path` once `write` succeeds.
+/// The temporary file is removed on failure, so `path` never contains a partially written file
+pub fn write_atomically<T, F>(path: &Path, needed: u64, write: F) -> Result<T>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<T>,
+{
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    check_available_space(dir, needed)?;
+    replace_file(path, write)
+}
+
+/// Calls `write` with a temporary file in the same directory as `path` that is renamed to `path`
+/// once `write` succeeds, and removed on failure.
+///
+/// Renaming replaces a link at `path` instead of writing through it, so extracting over files
+/// linked to a dedup store never changes the store
+pub fn replace_file<T, F>(path: &Path, write: F) -> Result<T>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<T>,
+{
+    let temp_path = temp_path(path);
+    let write_temp = || -> Result<T> {
+        let mut writer =
+            BufWriter::with_capacity(Config::global().buffer_size, File::create(&temp_path)?);
+        let value = write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&temp_path, path)?;
+        Ok(value)
+    };
+    let result = write_temp();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// A path next to `path` that no other writer, in this or another process, uses
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let file_name = path.file_name().map_or_else(
+        || "archive.gma".into(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// A file in the temporary directory that is removed when dropped
+#[derive(Debug)]
+pub struct TempFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new() -> io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            ".gma-spill-{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self { file, path })
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashMap, sync::Arc};
+
+/// Least recently used cache of entry contents, keyed by entry index
+#[derive(Debug)]
+pub(crate) struct EntryCache {
+    max_entry_size: u64,
+    capacity: usize,
+    size: usize,
+    tick: u64,
+    // index -> (last use, contents)
+    entries: HashMap<usize, (u64, Arc<[u8]>)>,
+}
+
+impl EntryCache {
+    pub fn new(max_entry_size: u64, capacity: usize) -> Self {
+        Self {
+            max_entry_size,
+            capacity,
+            size: 0,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// True if an entry of this size can be cached
+    pub fn accepts(&self, size: u64) -> bool {
+        size <= self.max_entry_size && size <= self.capacity as u64
+    }
+
+    pub fn get(&mut self, index: usize) -> Option<Arc<[u8]>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(&index).map(|(last_use, contents)| {
+            *last_use = tick;
+            contents.clone()
+        })
+    }
+
+    pub fn insert(&mut self, index: usize, contents: Arc<[u8]>) {
+        if !self.accepts(contents.len() as u64) {
+            return;
+        }
+        while self.size + contents.len() > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (last_use, _))| *last_use)
+                .map(|(index, _)| *index);
+            match oldest.and_then(|index| self.entries.remove(&index)) {
+                Some((_, removed)) => self.size -= removed.len(),
+                None => break,
+            }
+        }
+        self.tick += 1;
+        self.size += contents.len();
+        if let Some((_, replaced)) = self.entries.insert(index, (self.tick, contents)) {
+            self.size -= replaced.len();
+        }
+    }
+
+    /// Bytes held by the cached contents
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntryCache;
+    use std::sync::Arc;
+
+    fn contents(len: usize) -> Arc<[u8]> {
+        vec![0u8; len].into()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = EntryCache::new(10, 20);
+        cache.insert(0, contents(10));
+        cache.insert(1, contents(10));
+        assert!(cache.get(0).is_some());
+        cache.insert(2, contents(10));
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(0).is_some());
+        assert!(cache.get(2).is_some());
+        assert_eq!(cache.size(), 20);
+    }
+
+    #[test]
+    fn ignores_large_entries() {
+        let mut cache = EntryCache::new(10, 100);
+        cache.insert(0, contents(11));
+        assert!(cache.get(0).is_none());
+        assert_eq!(cache.size(), 0);
+    }
+}
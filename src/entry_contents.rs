@@ -0,0 +1,67 @@
+use crate::{FileEntry, GMAFile, Result};
+use std::io::{BufRead, Seek};
+
+/// Iterator over the entries of an archive and their contents, see
+/// [`GMAFile::entries_with_contents`]
+#[derive(Debug)]
+pub struct EntriesWithContents<'a, ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    archive: &'a GMAFile<ReaderType>,
+    position: usize,
+}
+
+impl<'a, ReaderType> Iterator for EntriesWithContents<'a, ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    type Item = Result<(&'a FileEntry, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.archive.entries().nth(self.position)?;
+        self.position += 1;
+        let contents = self.archive.try_read_entry(entry, |entry, reader| {
+            let mut contents = Vec::with_capacity(self.archive.contents_capacity(entry));
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        });
+        Some(contents.map(|contents| (entry, contents)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.archive.entry_count() - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<ReaderType> ExactSizeIterator for EntriesWithContents<'_, ReaderType> where
+    ReaderType: BufRead + Seek
+{
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Every entry together with its contents, read one at a time as the iterator advances.
+    ///
+    /// Entries are returned in the order they are stored in, so the reader only moves forward.
+    /// A failed read is returned as an error and the iteration can continue with the next entry.
+    /// The reader is only locked while an entry is read, so other entries can be read between
+    /// calls to `next`
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// for item in archive.entries_with_contents() {
+    ///     let (entry, contents) = item.unwrap();
+    ///     println!("{} : {} bytes", entry.filename(), contents.len());
+    /// }
+    /// ```
+    pub fn entries_with_contents(&self) -> EntriesWithContents<'_, ReaderType> {
+        EntriesWithContents {
+            archive: self,
+            position: 0,
+        }
+    }
+}
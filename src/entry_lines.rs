@@ -0,0 +1,103 @@
+use crate::{text, Error, FileEntry, GMAFile, Result};
+use std::io::{BufRead, Seek};
+
+/// What [`EntryLines`] does with lines that are not valid utf8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextFallback {
+    /// Fails with [`Error::UTF8Error`], the iteration can continue with the next line
+    Strict,
+    /// Replaces invalid sequences with U+FFFD
+    #[default]
+    Lossy,
+    /// Decodes the whole line as latin1, every byte becomes the char with the same value.
+    /// Matches [`TextFallback::Windows1252`] except for the bytes 0x80 to 0x9F
+    Latin1,
+    /// Decodes the whole line as windows-1252, see [`GMAFile::read_entry_text`]
+    Windows1252,
+}
+
+/// Iterator over the lines of an entry, see [`GMAFile::entry_lines`]
+pub struct EntryLines<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    fallback: TextFallback,
+}
+
+impl std::fmt::Debug for EntryLines<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntryLines")
+            .field("fallback", &self.fallback)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for EntryLines<'_> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(e.into())),
+        }
+        if line.ends_with(b"\n") {
+            line.pop();
+            if line.ends_with(b"\r") {
+                line.pop();
+            }
+        }
+        Some(decode(line, self.fallback))
+    }
+}
+
+fn decode(line: Vec<u8>, fallback: TextFallback) -> Result<String> {
+    match String::from_utf8(line) {
+        Ok(line) => Ok(line),
+        Err(e) => match fallback {
+            TextFallback::Strict => Err(Error::UTF8Error(e)),
+            TextFallback::Lossy => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+            TextFallback::Latin1 => Ok(e.as_bytes().iter().map(|&b| b as char).collect()),
+            TextFallback::Windows1252 => Ok(text::decode_windows1252(e.as_bytes()).0),
+        },
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// The lines of an entry, read as the iterator advances without buffering the whole entry.
+    ///
+    /// Lines end with `\n` or `\r\n`, which are not included. Invalid utf8 is replaced, see
+    /// [`GMAFile::entry_lines_with`] for other fallbacks.
+    ///
+    /// The reader stays locked until the iterator is dropped, in the meantime other reads on this
+    /// thread fail with [`Error::ReaderInUse`] and reads on other threads wait. Lines are read straight from the reader, without
+    /// going through the entry cache or [`crate::LoadOptions::verify_crc`]
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// for entry in archive.entries_with_extension(&["lua"]) {
+    ///     for (number, line) in archive.entry_lines(entry).unwrap().enumerate() {
+    ///         if line.unwrap().contains("RunString") {
+    ///             println!("{}:{}", entry.filename(), number + 1);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn entry_lines(&self, entry: &FileEntry) -> Result<EntryLines<'_>> {
+        self.entry_lines_with(entry, TextFallback::default())
+    }
+
+    /// Same as [`GMAFile::entry_lines`] with a fallback for lines that are not valid utf8
+    pub fn entry_lines_with(
+        &self,
+        entry: &FileEntry,
+        fallback: TextFallback,
+    ) -> Result<EntryLines<'_>> {
+        Ok(EntryLines {
+            reader: Box::new(self.lock_entry(entry)?),
+            fallback,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use std::io::{Read, Seek, SeekFrom};
+
+pub(crate) trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Reader over the contents of a single entry that can seek relative to the start of the entry,
+/// see [`crate::GMAFile::read_entry_seekable`].
+///
+/// Seeking past the end of the entry is allowed, reads from there return 0 bytes
+pub struct EntryReader<'a> {
+    stream: &'a mut dyn ReadSeek,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl<'a> EntryReader<'a> {
+    // `stream` must already be positioned at `start`
+    pub(crate) fn new(stream: &'a mut dyn ReadSeek, start: u64, len: u64) -> Self {
+        Self {
+            stream,
+            start,
+            len,
+            pos: 0,
+        }
+    }
+
+    /// The size of the entry
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// True if the entry is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The current position relative to the start of the entry
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl Read for EntryReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let max = buf.len().min(remaining.min(usize::MAX as u64) as usize);
+        if max == 0 {
+            return Ok(0);
+        }
+        let n = self.stream.read(&mut buf[..max])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for EntryReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => offset_position(self.len, offset),
+            SeekFrom::Current(offset) => offset_position(self.pos, offset),
+        };
+        let new_pos = new_pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        let absolute = self.start.checked_add(new_pos).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek position overflows")
+        })?;
+        self.stream.seek(SeekFrom::Start(absolute))?;
+        self.pos = new_pos;
+        Ok(new_pos)
+    }
+}
+
+pub(crate) fn offset_position(base: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.unsigned_abs())
+    }
+}
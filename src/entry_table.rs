@@ -0,0 +1,174 @@
+use crate::{gma_reader::path, FileEntry};
+
+/// The file table stored as parallel arrays with every filename in a single buffer,
+/// used by archives loaded with [`crate::LoadOptions::compact`]
+#[derive(Debug, Default)]
+pub(crate) struct CompactEntries {
+    names: String,
+    name_ends: Vec<usize>,
+    sizes: Vec<u64>,
+    crcs: Vec<u32>,
+    file_numbers: Vec<u32>,
+    offsets: Vec<u64>,
+}
+
+impl CompactEntries {
+    pub fn push(&mut self, filename: &str, size: u64, crc: u32, file_number: u32, offset: u64) {
+        self.names.push_str(filename);
+        self.name_ends.push(self.names.len());
+        self.sizes.push(size);
+        self.crcs.push(crc);
+        self.file_numbers.push(file_number);
+        self.offsets.push(offset);
+    }
+
+    pub fn len(&self) -> usize {
+        self.name_ends.len()
+    }
+
+    /// Panics if `index` is out of bounds, like slice indexing
+    pub fn view(&self, index: usize, data_start: u64) -> EntryView<'_> {
+        let start = match index {
+            0 => 0,
+            _ => self.name_ends[index - 1],
+        };
+        let offset = self.offsets[index];
+        EntryView {
+            filename: &self.names[start..self.name_ends[index]],
+            size: self.sizes[index],
+            crc: self.crcs[index],
+            index,
+            file_number: self.file_numbers[index],
+            offset,
+            absolute_offset: data_start.saturating_add(offset),
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.names.shrink_to_fit();
+        self.name_ends.shrink_to_fit();
+        self.sizes.shrink_to_fit();
+        self.crcs.shrink_to_fit();
+        self.file_numbers.shrink_to_fit();
+        self.offsets.shrink_to_fit();
+    }
+
+    pub fn approx_memory_usage(&self) -> usize {
+        self.names.capacity()
+            + self.name_ends.capacity() * std::mem::size_of::<usize>()
+            + self.sizes.capacity() * std::mem::size_of::<u64>()
+            + self.crcs.capacity() * std::mem::size_of::<u32>()
+            + self.file_numbers.capacity() * std::mem::size_of::<u32>()
+            + self.offsets.capacity() * std::mem::size_of::<u64>()
+    }
+}
+
+/// A borrowed entry of the file table, with the same accessors as [`FileEntry`].
+///
+/// Views don't need the archive to keep a [`FileEntry`] per entry, see [`crate::GMAFile::entry_views`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryView<'a> {
+    filename: &'a str,
+    size: u64,
+    crc: u32,
+    index: usize,
+    file_number: u32,
+    offset: u64,
+    absolute_offset: u64,
+}
+
+impl<'a> EntryView<'a> {
+    /// The full filename of this entry. Ex : lua/autorun/cl_myscript.lua
+    pub fn filename(&self) -> &'a str {
+        self.filename
+    }
+    /// The file size
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The crc32 of this entry's contents
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+    /// The position of this entry in the archive's file table, starting at 0
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// The file number stored in the file table, see [`FileEntry::file_number`]
+    pub fn file_number(&self) -> u32 {
+        self.file_number
+    }
+    /// The offset in the gma file, starting from the first file
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// The offset from the start of the gma file. See [`crate::GMAFile::data_offset`]
+    pub fn absolute_offset(&self) -> u64 {
+        self.absolute_offset
+    }
+    /// The last component of the filename. Ex : cl_myscript.lua
+    pub fn file_name(&self) -> &'a str {
+        path::file_name(self.filename)
+    }
+    /// The extension of the file name without the leading dot. Ex : lua
+    pub fn extension(&self) -> Option<&'a str> {
+        path::extension(self.filename)
+    }
+    /// The directory containing this entry. Ex : lua/autorun
+    pub fn parent(&self) -> Option<&'a str> {
+        path::parent(self.filename)
+    }
+    /// An iterator over the slash separated components of the filename. Ex : lua, autorun, cl_myscript.lua
+    pub fn components(&self) -> impl Iterator<Item = &'a str> {
+        path::components(self.filename)
+    }
+    /// An owned [`FileEntry`] with the same values, to be used with [`crate::GMAFile::read_entry`]
+    pub fn to_entry(&self) -> FileEntry {
+        FileEntry::new(
+            self.filename.into(),
+            self.size,
+            self.crc,
+            self.index,
+            self.file_number,
+            self.offset,
+            self.absolute_offset,
+        )
+    }
+}
+
+impl<'a> From<&'a FileEntry> for EntryView<'a> {
+    fn from(entry: &'a FileEntry) -> Self {
+        Self {
+            filename: entry.filename(),
+            size: entry.size(),
+            crc: entry.crc(),
+            index: entry.index(),
+            file_number: entry.file_number(),
+            offset: entry.offset(),
+            absolute_offset: entry.absolute_offset(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views() {
+        let mut table = CompactEntries::default();
+        table.push("lua/autorun/a.lua", 10, 1, 1, 0);
+        table.push("b.txt", 5, 2, 7, 10);
+        assert_eq!(table.len(), 2);
+        let a = table.view(0, 100);
+        assert_eq!(a.filename(), "lua/autorun/a.lua");
+        assert_eq!(a.parent(), Some("lua/autorun"));
+        assert_eq!(a.absolute_offset(), 100);
+        let b = table.view(1, 100);
+        assert_eq!(b.filename(), "b.txt");
+        assert_eq!(b.extension(), Some("txt"));
+        assert_eq!((b.size(), b.crc(), b.index(), b.offset()), (5, 2, 1, 10));
+        assert_eq!(b.file_number(), 7);
+        assert_eq!(EntryView::from(&b.to_entry()), b);
+    }
+}
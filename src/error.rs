@@ -1,5 +1,4 @@
-use crate::binary;
-use lzma_rs;
+use crate::{binary, BuildReport, NonAsciiName, ParseTrace};
 use std::fmt::Display;
 
 #[derive(Debug)]
@@ -15,6 +14,149 @@ pub enum Error {
     CompressionError(lzma_rs::error::Error),
     InvalidAddonType(String),
     InvalidAddonTag(String),
+    /// A string in the header was longer than the maximum allowed by [`crate::LoadOptions`],
+    /// contains the maximum length
+    StringTooLong(usize),
+    /// There is not enough disk space to write the output, sizes are in bytes
+    InsufficientSpace {
+        needed: u64,
+        available: u64,
+    },
+    /// An entry's filename is absolute or contains '..' and can't be extracted safely
+    UnsafePath(String),
+    /// An addon.json file could not be parsed, contains the parser's message
+    InvalidAddonJson(String),
+    /// A workshop item was neither an id nor the url of an item's page
+    InvalidWorkshopItem(String),
+    /// A string could not be parsed as a steam id
+    InvalidSteamID(String),
+    /// The contents of an entry did not match the crc32 stored in the archive
+    CrcMismatch {
+        filename: String,
+        expected: u32,
+        found: u32,
+    },
+    /// [`crate::GMAFile::read_entry`] was called while another entry of the same archive was being read
+    /// on the same thread
+    ReaderInUse,
+    /// The archive ended before all of its header, file table or contents could be read, this is
+    /// usually caused by an incomplete download or copy. `at` is the size of the available data and
+    /// `expected` the minimum size the archive should have had. For compressed archives both are
+    /// sizes of the decompressed data
+    Truncated {
+        expected: u64,
+        at: u64,
+    },
+    /// Some filenames contain non ascii characters and [`crate::GMABuilder::strict_ascii`] is enabled
+    NonAsciiFilenames(Vec<NonAsciiName>),
+    /// Some filenames are not allowed by the game's whitelist and
+    /// [`crate::GMABuilder::strict_whitelist`] is enabled, contains the filenames
+    NotWhitelisted(Vec<String>),
+    /// Some files were added with the filename of an earlier file and
+    /// [`crate::GMABuilder::duplicates`] rejects them, contains the filenames of the earlier files
+    DuplicateFilenames(Vec<String>),
+    /// [`crate::GMABuilder`] was written without setting a name
+    MissingName,
+    /// [`crate::GMABuilder::deterministic`] was used but `SOURCE_DATE_EPOCH` is not a unix
+    /// timestamp, contains its value
+    InvalidSourceDateEpoch(String),
+    /// The entries don't fit in a zip archive without zip64, which is not supported
+    ZipLimitExceeded,
+    /// With [`crate::LoadOptions::strict_file_numbers`], the file number of the entry at `index` is
+    /// not greater than the previous one
+    NonMonotonicFileNumber {
+        index: usize,
+        previous: u32,
+        found: u32,
+    },
+    /// The archive doesn't end with the crc32 of the whole archive that gmad appends
+    MissingArchiveCrc,
+    /// The crc32 of the whole archive does not match the one stored after the last entry
+    ArchiveCrcMismatch {
+        expected: u32,
+        found: u32,
+    },
+    /// A compressed archive decompresses to more than [`crate::LoadOptions::max_decompressed_size`],
+    /// contains the maximum size in bytes
+    DecompressedTooLarge(u64),
+    /// The file table has more entries than [`crate::LoadOptions::max_entries`], contains the maximum
+    TooManyEntries(usize),
+    /// [`crate::GMABuilder::write_to_with_report`] failed with `error` while writing the files,
+    /// `report` has the files written so far and a [`crate::ResumeToken`] to continue with
+    /// [`crate::GMABuilder::resume`]
+    BuildInterrupted {
+        error: Box<Error>,
+        report: Box<BuildReport>,
+    },
+    /// The builder given to [`crate::GMABuilder::resume`] would not write the same header and file
+    /// table as the interrupted one, or it compresses the archive
+    ResumeMismatch,
+    /// The size or modification time of a file added from disk changed between hashing and
+    /// writing it, see [`crate::GMABuilder::parallel_hashing`]. Contains the entry's filename
+    SourceChanged(String),
+    /// Processing the entry or source file `name` failed with `source`, like an io error while
+    /// reading one of the files of a build. [`Error::entry`] finds the name through other wrappers
+    InEntry {
+        name: String,
+        source: Box<Error>,
+    },
+    /// The archive is compressed and its contents can't be borrowed, see [`crate::load_view`]
+    CompressedArchive,
+    /// Loading failed with [`crate::LoadOptions::trace`] enabled, contains the error and the
+    /// fields read up to that point
+    Traced {
+        error: Box<Error>,
+        trace: ParseTrace,
+    },
+}
+
+impl Error {
+    /// The parse trace if this error comes from loading with [`crate::LoadOptions::trace`] enabled
+    pub fn trace(&self) -> Option<&ParseTrace> {
+        match self {
+            Self::Traced { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
+
+    /// The name of the entry or source file that caused this error, if it is about one
+    /// ```no_run
+    /// # fn builder() -> gma::GMABuilder { gma::GMABuilder::new() }
+    /// if let Err(e) = builder().write_to_path("addon.gma") {
+    ///     match e.entry() {
+    ///         Some(name) => println!("failed on '{}' : {}", name, e),
+    ///         None => println!("failed : {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn entry(&self) -> Option<&str> {
+        match self {
+            Self::InEntry { name, .. } => Some(name),
+            Self::CrcMismatch { filename, .. } => Some(filename),
+            Self::SourceChanged(filename) => Some(filename),
+            Self::BuildInterrupted { error, .. } | Self::Traced { error, .. } => error.entry(),
+            _ => None,
+        }
+    }
+
+    // The error that interrupted a build, for outputs that can't be resumed
+    pub(crate) fn uninterrupted(self) -> Self {
+        match self {
+            Self::BuildInterrupted { error, .. } => *error,
+            e => e,
+        }
+    }
+
+    // Adds the entry's name to errors that don't already have it
+    pub(crate) fn in_entry(self, name: &str) -> Self {
+        match self.entry() {
+            Some(_) => self,
+            None => Self::InEntry {
+                name: name.to_owned(),
+                source: Box::new(self),
+            },
+        }
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -35,6 +177,7 @@ impl From<binary::Error> for Error {
             binary::Error::IOError(e) => Self::IOError(e),
             binary::Error::InvalidUTF8(e) => Self::UTF8Error(e),
             binary::Error::InvalidCString => Self::InvalidString,
+            binary::Error::CStringTooLong(max_len) => Self::StringTooLong(max_len),
         }
     }
 }
@@ -50,8 +193,62 @@ impl Display for Error {
             Self::CompressionError(e) => write!(f, "Error while compressing/decompressing. {:?}", e),
             Self::InvalidAddonType(s) => write!(f, "The addon type '{}' is invalid.", s),
             Self::InvalidAddonTag(s) => write!(f, "The addon tag '{}' is invalid.", s),
+            Self::StringTooLong(max_len) => write!(f, "A string longer than the maximum of {} bytes was found, this might be caused by a corrupt file", max_len),
+            Self::InsufficientSpace { needed, available } => write!(f, "Not enough disk space, {} bytes are needed but only {} are available", needed, available),
+            Self::UnsafePath(s) => write!(f, "The filename '{}' is not a safe relative path", s),
+            Self::InvalidAddonJson(s) => write!(f, "The addon.json file is invalid. {}", s),
+            Self::InvalidWorkshopItem(s) => write!(f, "'{}' is not a workshop id or url", s),
+            Self::InvalidSteamID(s) => write!(f, "'{}' is not a valid steam id", s),
+            Self::CrcMismatch { filename, expected, found } => write!(f, "The crc32 of '{}' does not match, expected {:x} but found {:x}", filename, expected, found),
+            Self::ReaderInUse => write!(f, "The archive's reader is already in use by another read_entry call on this thread"),
+            Self::Truncated { expected, at } => write!(f, "The archive is truncated, it ends at {} bytes but at least {} were expected", at, expected),
+            Self::NonAsciiFilenames(names) => {
+                write!(f, "{} filenames contain non ascii characters :", names.len())?;
+                for name in names.iter() {
+                    write!(f, " '{}' (try '{}')", name.filename(), name.suggestion())?;
+                }
+                Ok(())
+            }
+            Self::NotWhitelisted(filenames) => {
+                write!(f, "{} files are not allowed by the addon whitelist :", filenames.len())?;
+                for filename in filenames.iter() {
+                    write!(f, " '{}'", filename)?;
+                }
+                Ok(())
+            }
+            Self::DuplicateFilenames(filenames) => {
+                write!(f, "{} files were added more than once :", filenames.len())?;
+                for filename in filenames.iter() {
+                    write!(f, " '{}'", filename)?;
+                }
+                Ok(())
+            }
+            Self::MissingName => write!(f, "The addon needs a name, set it with GMABuilder::name"),
+            Self::InvalidSourceDateEpoch(epoch) => write!(f, "SOURCE_DATE_EPOCH is set to '{}', which is not a unix timestamp", epoch),
+            Self::NonMonotonicFileNumber { index, previous, found } => write!(f, "The entry at index {} has the file number {} which is not greater than the previous one, {}", index, found, previous),
+            Self::MissingArchiveCrc => write!(f, "The archive does not end with a crc32 of its contents"),
+            Self::ArchiveCrcMismatch { expected, found } => write!(f, "The crc32 of the archive does not match, expected {:x} but found {:x}", expected, found),
+            Self::ZipLimitExceeded => write!(f, "The selected entries need zip64 which is not supported, zip archives are limited to 65535 files and 4 GiB"),
+            Self::DecompressedTooLarge(max_size) => write!(f, "The archive decompresses to more than the maximum of {} bytes", max_size),
+            Self::TooManyEntries(max_entries) => write!(f, "The archive has more than the maximum of {} entries, this might be caused by a corrupt file", max_entries),
+            Self::BuildInterrupted { error, report } => write!(f, "Building the archive was interrupted after {} files : {}", report.files().len(), error),
+            Self::ResumeMismatch => write!(f, "The builder does not match the interrupted build it should resume"),
+            Self::SourceChanged(filename) => write!(f, "The file '{}' changed while the archive was being built", filename),
+            Self::InEntry { name, source } => write!(f, "'{}' : {}", name, source),
+            Self::CompressedArchive => write!(f, "The archive is compressed, its contents can only be read after decompressing it"),
+            Self::Traced { error, trace } => match trace.last() {
+                Some(field) => write!(f, "{} (last field read : '{}' at offset {})", error, field.name(), field.offset()),
+                None => error.fmt(f),
+            },
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InEntry { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::{disk, wildcard, Config, Error, FileEntry, GMAFile, Result};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::SystemTime,
+};
+
+/// How duplicated files are linked to the shared store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Hardlink,
+    Symlink,
+}
+
+/// What to do with entry filenames that could write outside of the extraction directory, see
+/// [`sanitize_path`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathPolicy {
+    /// Fails with [`Error::UnsafePath`]
+    #[default]
+    Reject,
+    /// Drops the unsafe components and keeps the rest. Ex : `../../lua/a.lua` becomes `lua/a.lua`
+    Strip,
+}
+
+/// Options for [`GMAFile::extract_to`]
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    dedup_store: Option<(PathBuf, LinkKind)>,
+    prefetch: bool,
+    path_policy: PathPolicy,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    preserve_timestamps: bool,
+}
+
+impl ExtractOptions {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the contents of every extracted file in `store`, keyed by crc32 and size, and links
+    /// the extracted paths to it instead of writing copies.
+    ///
+    /// Using the same store when extracting many archives means files shared between them are
+    /// only stored once. Default : disabled
+    pub fn dedup_store<P: Into<PathBuf>>(&mut self, store: P, link: LinkKind) -> &mut Self {
+        self.dedup_store = Some((store.into(), link));
+        self
+    }
+
+    /// Writes the extracted files from a background thread, so the next entries are read while
+    /// the previous ones are written. Speeds up archives with many small files, like lua addons.
+    ///
+    /// Up to a few entries are held in memory, entries larger than 4 MiB are written directly.
+    /// Ignored with [`ExtractOptions::dedup_store`]. Default : false
+    pub fn prefetch(&mut self, prefetch: bool) -> &mut Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    /// What to do with entries whose filename is absolute, has `..` components or drive letters.
+    /// Default : [`PathPolicy::Reject`]
+    pub fn path_policy(&mut self, policy: PathPolicy) -> &mut Self {
+        self.path_policy = policy;
+        self
+    }
+
+    /// Only extracts the entries whose filename matches `pattern`, or any other pattern given
+    /// to this function. Patterns are gmad style wildcards where `*` also matches `/`, so
+    /// `lua/*` selects everything under `lua`. Default : every entry
+    /// ```no_run
+    /// # let archive = gma::open("addon.gma").unwrap();
+    /// let mut options = gma::ExtractOptions::new();
+    /// options.include("lua/*").include("maps/*").exclude("*.txt");
+    /// archive.extract_to("server/addons/myaddon", &options).unwrap();
+    /// ```
+    pub fn include<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Skips the entries whose filename matches `pattern`, even if they match an included
+    /// pattern, see [`ExtractOptions::include`]. Default : nothing is skipped
+    pub fn exclude<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Sets the modification time of the extracted files, and of the directories the extraction
+    /// creates, to [`GMAFile::timestamp`], so extracting and rebuilding an addon gives the same
+    /// files every time.
+    ///
+    /// Files linked to the [`ExtractOptions::dedup_store`] keep the time of the store, which is
+    /// shared with other extractions. Default : false
+    pub fn preserve_timestamps(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_timestamps = preserve;
+        self
+    }
+
+    fn selects(&self, filename: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| wildcard::matches(pattern, filename));
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| wildcard::matches(pattern, filename))
+    }
+}
+
+/// A file written by [`GMAFile::extract_to`]
+#[derive(Debug, Clone)]
+pub struct ExtractedFile {
+    filename: String,
+    path: PathBuf,
+    size: u64,
+    deduplicated: bool,
+}
+
+impl ExtractedFile {
+    /// The filename of the entry inside the archive
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// Where the file was written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// The size of the file
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// True if the file is a link to contents that already were in the dedup store
+    pub fn deduplicated(&self) -> bool {
+        self.deduplicated
+    }
+}
+
+/// Summary of an extraction
+#[derive(Debug, Clone, Default)]
+pub struct ExtractReport {
+    files: Vec<ExtractedFile>,
+}
+
+impl ExtractReport {
+    /// The extracted files, in the order they were written
+    pub fn files(&self) -> &[ExtractedFile] {
+        &self.files
+    }
+}
+
+/// A file [`GMAFile::extract_plan`] found would be written
+#[derive(Debug, Clone)]
+pub struct PlannedFile {
+    filename: String,
+    path: PathBuf,
+    size: u64,
+    exists: bool,
+    duplicated: bool,
+}
+
+impl PlannedFile {
+    /// The filename of the entry inside the archive
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// Where the file would be written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// The size of the file
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// True if something already exists at [`PlannedFile::path`] and would be overwritten
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+    /// True if an earlier file of the plan has the same path. Paths that only differ in case
+    /// are different, even on case-insensitive filesystems
+    pub fn duplicated(&self) -> bool {
+        self.duplicated
+    }
+}
+
+/// The files an extraction would write, see [`GMAFile::extract_plan`]
+#[derive(Debug, Clone, Default)]
+pub struct ExtractPlan {
+    files: Vec<PlannedFile>,
+}
+
+impl ExtractPlan {
+    /// The files that would be written, in order
+    pub fn files(&self) -> &[PlannedFile] {
+        &self.files
+    }
+    /// The total size of the files
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+    /// The files that would overwrite an existing file or an earlier file of the plan
+    pub fn collisions(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.files
+            .iter()
+            .filter(|file| file.exists || file.duplicated)
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Extracts every entry to `dir`, or the ones selected with [`ExtractOptions::include`] and
+    /// [`ExtractOptions::exclude`], creating directories as needed.
+    ///
+    /// Fails with [`Error::UnsafePath`] before writing anything if an entry's filename could
+    /// end up outside of `dir`, unless [`ExtractOptions::path_policy`] strips the unsafe parts,
+    /// and with [`Error::InsufficientSpace`] if the filesystem does not have room for the
+    /// extracted files.
+    pub fn extract_to<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        options: &ExtractOptions,
+    ) -> Result<ExtractReport> {
+        self.extract_mapped(dir, options, |entry| Some(entry.filename().to_owned()))
+    }
+
+    /// Same as [`GMAFile::extract_to`] but every selected entry is written to the path returned
+    /// by `map`, relative to `dir` and separated by `/`, or skipped if it returns None. Useful
+    /// to rename or flatten entries without reimplementing the extraction.
+    ///
+    /// The returned paths are checked like filenames, see [`ExtractOptions::path_policy`].
+    /// If many entries map to the same path the last one is kept
+    /// ```no_run
+    /// # let archive = gma::open("addon.gma").unwrap();
+    /// // every material in a single directory
+    /// let report = archive.extract_mapped("flat", &gma::ExtractOptions::new(), |entry| {
+    ///     match entry.filename().starts_with("materials/") {
+    ///         true => Some(entry.file_name().to_owned()),
+    ///         false => None,
+    ///     }
+    /// });
+    /// ```
+    pub fn extract_mapped<P, F>(
+        &self,
+        dir: P,
+        options: &ExtractOptions,
+        map: F,
+    ) -> Result<ExtractReport>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&FileEntry) -> Option<String>,
+    {
+        let dir = dir.as_ref();
+        let targets = self.extract_targets(dir, options, map)?;
+        let modified = self.created_at().filter(|_| options.preserve_timestamps);
+        let created_dirs = match modified {
+            Some(_) => missing_dirs(dir, &targets),
+            None => Vec::new(),
+        };
+
+        fs::create_dir_all(dir)?;
+        let needed = targets.iter().map(|(entry, _)| entry.size()).sum();
+        disk::check_available_space(dir, needed)?;
+
+        let mut report = ExtractReport::default();
+        let prefetch = options.prefetch && options.dedup_store.is_none();
+        if prefetch {
+            self.extract_prefetching(&targets)?;
+        }
+        for (entry, path) in targets {
+            let extract = || -> Result<bool> {
+                match &options.dedup_store {
+                    Some((store, link)) => {
+                        create_parent(&path)?;
+                        self.extract_deduplicated(&entry, &path, store, *link)
+                    }
+                    None if prefetch => Ok(false),
+                    None => {
+                        create_parent(&path)?;
+                        self.extract_entry(&entry, &path)?;
+                        Ok(false)
+                    }
+                }
+            };
+            let deduplicated = extract().map_err(|e| e.in_entry(entry.filename()))?;
+            report.files.push(ExtractedFile {
+                filename: entry.filename().to_owned(),
+                path,
+                size: entry.size(),
+                deduplicated,
+            });
+        }
+        if let Some(modified) = modified {
+            let written = report.files.iter().filter(|file| !file.deduplicated);
+            for path in written.map(|file| &file.path).chain(created_dirs.iter()) {
+                set_modified(path, modified)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// What [`GMAFile::extract_to`] would write with the same arguments, without writing
+    /// anything, so tools can ask for confirmation first. Fails like `extract_to` does on
+    /// unsafe filenames
+    /// ```no_run
+    /// # let archive = gma::open("addon.gma").unwrap();
+    /// let options = gma::ExtractOptions::new();
+    /// let plan = archive.extract_plan("out", &options).unwrap();
+    /// for file in plan.collisions() {
+    ///     println!("would overwrite {}", file.path().display());
+    /// }
+    /// ```
+    pub fn extract_plan<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        options: &ExtractOptions,
+    ) -> Result<ExtractPlan> {
+        let targets = self.extract_targets(dir.as_ref(), options, |entry| {
+            Some(entry.filename().to_owned())
+        })?;
+        let mut seen = HashSet::new();
+        let files = targets
+            .into_iter()
+            .map(|(entry, path)| PlannedFile {
+                filename: entry.filename().to_owned(),
+                size: entry.size(),
+                exists: fs::symlink_metadata(&path).is_ok(),
+                duplicated: !seen.insert(path.clone()),
+                path,
+            })
+            .collect();
+        Ok(ExtractPlan { files })
+    }
+
+    // The selected entries and where they are written, built from the views so compact archives
+    // only keep the selected entries around while extracting
+    fn extract_targets<F>(
+        &self,
+        dir: &Path,
+        options: &ExtractOptions,
+        mut map: F,
+    ) -> Result<Vec<(FileEntry, PathBuf)>>
+    where
+        F: FnMut(&FileEntry) -> Option<String>,
+    {
+        let mut targets = Vec::new();
+        for view in self.entry_views().filter(|e| options.selects(e.filename())) {
+            let entry = view.to_entry();
+            if let Some(destination) = map(&entry) {
+                let path = sanitize_path(&destination, options.path_policy)?;
+                targets.push((entry, dir.join(path)));
+            }
+        }
+        Ok(targets)
+    }
+
+    // Reads the entries on this thread and writes them from another one
+    fn extract_prefetching(&self, targets: &[(FileEntry, PathBuf)]) -> Result<()> {
+        // larger entries are streamed instead of buffered
+        const MAX_PREFETCH_SIZE: u64 = 4 * 1024 * 1024;
+        // entries waiting to be written
+        const PREFETCH_DEPTH: usize = 8;
+
+        let (sender, receiver) = mpsc::sync_channel::<Prefetched>(PREFETCH_DEPTH);
+        thread::scope(|scope| {
+            let writer = scope.spawn(move || -> Result<()> {
+                for prefetched in receiver {
+                    match prefetched {
+                        Prefetched::Contents(entry, path, contents) => {
+                            disk::replace_file(path, |writer| Ok(writer.write_all(&contents)?))
+                                .map_err(|e| e.in_entry(entry.filename()))?
+                        }
+                        Prefetched::Drain(drained) => {
+                            let _ = drained.send(());
+                        }
+                    }
+                }
+                Ok(())
+            });
+            let read = || -> Result<()> {
+                for (entry, path) in targets {
+                    // None if the entry was too large and already written
+                    let read_entry = || -> Result<Option<Vec<u8>>> {
+                        create_parent(path)?;
+                        if entry.size() > MAX_PREFETCH_SIZE {
+                            // the queued entries are written first, one of them could map to the
+                            // same path and the last entry is the one that has to be kept
+                            let (drained, wait) = mpsc::channel();
+                            if sender.send(Prefetched::Drain(drained)).is_ok() {
+                                let _ = wait.recv();
+                            }
+                            self.extract_entry(entry, path)?;
+                            return Ok(None);
+                        }
+                        self.try_read_entry(entry, |entry, reader| {
+                            let mut contents = Vec::with_capacity(self.contents_capacity(entry));
+                            reader.read_to_end(&mut contents)?;
+                            Ok(Some(contents))
+                        })
+                    };
+                    let contents = match read_entry().map_err(|e| e.in_entry(entry.filename()))? {
+                        Some(contents) => contents,
+                        None => continue,
+                    };
+                    // the writer only hangs up after an error, which join returns
+                    if sender
+                        .send(Prefetched::Contents(entry, path, contents))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Ok(())
+            };
+            let read_result = read();
+            drop(sender);
+            let write_result = writer
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            read_result?;
+            write_result
+        })
+    }
+
+    // Replaces whatever is at `path`, links to the dedup store are not written through
+    fn extract_entry(&self, entry: &FileEntry, path: &Path) -> Result<()> {
+        disk::replace_file(path, |writer| {
+            self.read_entry_to_writer(entry, writer)?;
+            Ok(())
+        })
+    }
+
+    // Returns true if the contents were already in the store
+    fn extract_deduplicated(
+        &self,
+        entry: &FileEntry,
+        path: &Path,
+        store: &Path,
+        link: LinkKind,
+    ) -> Result<bool> {
+        fs::create_dir_all(store)?;
+        let stored = store.join(format!("{:08x}-{}", entry.crc(), entry.size()));
+        let already_stored = stored.exists();
+        if already_stored {
+            let identical = self.try_read_entry(entry, |_, reader| {
+                Ok(same_contents(
+                    reader,
+                    BufReader::with_capacity(Config::global().buffer_size, File::open(&stored)?),
+                )?)
+            })?;
+            //crc32 collision, fall back to a plain copy
+            if !identical {
+                self.extract_entry(entry, path)?;
+                return Ok(false);
+            }
+        } else {
+            self.extract_entry(entry, &stored)?;
+        }
+
+        if fs::symlink_metadata(path).is_ok() {
+            fs::remove_file(path)?;
+        }
+        match link {
+            LinkKind::Hardlink => fs::hard_link(&stored, path)?,
+            LinkKind::Symlink => symlink(&fs::canonicalize(&stored)?, path)?,
+        }
+        Ok(already_stored)
+    }
+}
+
+// The directories that writing the targets would create, `dir` included
+fn missing_dirs(dir: &Path, targets: &[(FileEntry, PathBuf)]) -> Vec<PathBuf> {
+    let mut dirs = HashSet::new();
+    for (_, path) in targets {
+        let parents = path.ancestors().skip(1).take_while(|p| p.starts_with(dir));
+        for parent in parents {
+            if !dirs.insert(parent) {
+                break;
+            }
+        }
+    }
+    dirs.into_iter()
+        .filter(|d| fs::symlink_metadata(d).is_err())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+// messages from the reading thread to the writing one in extract_prefetching
+enum Prefetched<'a> {
+    Contents(&'a FileEntry, &'a Path, Vec<u8>),
+    // answered once every message before it was handled
+    Drain(mpsc::Sender<()>),
+}
+
+fn create_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+/// Converts an archive filename into a relative path that can't escape the directory it is
+/// joined to. Both `/` and `\\` separate components, on every platform.
+///
+/// Absolute filenames, `..` components and components with a `:`, like drive letters, are
+/// unsafe and handled according to `policy`. Empty and `.` components are dropped. Fails with
+/// [`Error::UnsafePath`] if nothing is left
+/// ```
+/// use gma::PathPolicy;
+/// use std::path::Path;
+///
+/// assert!(gma::sanitize_path("../evil.lua", PathPolicy::Reject).is_err());
+/// let path = gma::sanitize_path("C:\\../lua/a.lua", PathPolicy::Strip).unwrap();
+/// assert_eq!(path, Path::new("lua/a.lua"));
+/// ```
+pub fn sanitize_path(filename: &str, policy: PathPolicy) -> Result<PathBuf> {
+    let unsafe_path = || Error::UnsafePath(filename.to_owned());
+    let absolute = filename.starts_with(['/', '\\']);
+    if absolute && policy == PathPolicy::Reject {
+        return Err(unsafe_path());
+    }
+    let mut path = PathBuf::new();
+    for component in filename.split(['/', '\\']) {
+        let safe = component != ".." && !component.contains(':');
+        match component {
+            "" | "." => {}
+            c if safe => path.push(c),
+            _ if policy == PathPolicy::Strip => {}
+            _ => return Err(unsafe_path()),
+        }
+    }
+    if path.as_os_str().is_empty() {
+        return Err(unsafe_path());
+    }
+    Ok(path)
+}
+
+fn same_contents<A: Read, B: Read>(mut a: A, mut b: B) -> io::Result<bool> {
+    const BLOCK_SIZE: usize = 8096;
+    let mut buffer_a = [0u8; BLOCK_SIZE];
+    let mut buffer_b = [0u8; BLOCK_SIZE];
+    loop {
+        let n = read_full(&mut a, &mut buffer_a)?;
+        if n != read_full(&mut b, &mut buffer_b[..n])? || buffer_a[..n] != buffer_b[..n] {
+            return Ok(false);
+        }
+        if n < BLOCK_SIZE {
+            //a is done, b must be done too
+            return Ok(b.read(&mut buffer_b[..1])? == 0);
+        }
+    }
+}
+
+// Like read_exact but returns the amount of bytes read when the reader ends early
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut total = 0;
+    while total < buffer.len() {
+        match reader.read(&mut buffer[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
+
+// Works on directories too, opening a file for reading is enough to change its times on unix
+#[cfg(unix)]
+fn set_modified(path: &Path, time: SystemTime) -> io::Result<()> {
+    File::open(path)?.set_modified(time)
+}
+
+#[cfg(windows)]
+fn set_modified(path: &Path, time: SystemTime) -> io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    // required to open directories
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x02000000;
+    fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)?
+        .set_modified(time)
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
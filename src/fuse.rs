@@ -0,0 +1,247 @@
+//! Read-only mounting of archives as a local filesystem, with the `fuse` feature.
+//!
+//! Existing tools, like grep or model viewers, can then work on the contents of an archive
+//! without extracting it first
+//! ```no_run
+//! let archive = gma::open("addon.gma").unwrap();
+//! // blocks until the filesystem is unmounted, with `fusermount -u /mnt/addon` for example
+//! gma::fuse::mount(&archive, "/mnt/addon").unwrap();
+//! ```
+
+use crate::{EntryView, GMAFile, Result};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
+    Request,
+};
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    io::{BufRead, Read, Seek, SeekFrom},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// the archive never changes while mounted so the kernel can cache attributes for a while
+const TTL: Duration = Duration::from_secs(60);
+const ROOT_INODE: u64 = 1;
+
+/// Mounts `archive` read-only at `mountpoint`, with a file for every entry and a directory for
+/// every component of their slash separated filenames.
+///
+/// Blocks until the filesystem is unmounted, files are modified at the archive's
+/// [`GMAFile::created_at`], or the epoch if it can't be represented, and owned by the current
+/// user. When an entry's path needs a directory where an earlier entry is a file, or the other way
+/// around, the later entry is left out. Reads go through the archive's reader, entries that fail
+/// to read are reported to the caller as io errors
+pub fn mount<ReaderType, P>(archive: &GMAFile<ReaderType>, mountpoint: P) -> Result<()>
+where
+    ReaderType: BufRead + Seek,
+    P: AsRef<Path>,
+{
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("gma".to_owned()),
+        MountOption::DefaultPermissions,
+    ];
+    fuser::mount2(ArchiveFs::new(archive), mountpoint, &options)?;
+    Ok(())
+}
+
+enum Node<'a> {
+    Directory {
+        parent: u64,
+        children: BTreeMap<&'a str, u64>,
+    },
+    File(EntryView<'a>),
+}
+
+struct ArchiveFs<'a, ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    archive: &'a GMAFile<ReaderType>,
+    // indexed by inode - 1, the root is always the first node
+    nodes: Vec<Node<'a>>,
+    mtime: SystemTime,
+    uid: u32,
+    gid: u32,
+}
+
+impl<'a, ReaderType> ArchiveFs<'a, ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    fn new(archive: &'a GMAFile<ReaderType>) -> Self {
+        let mut nodes = vec![Node::Directory {
+            parent: ROOT_INODE,
+            children: BTreeMap::new(),
+        }];
+        'entries: for entry in archive.entry_views() {
+            let mut dir = ROOT_INODE;
+            let mut components = entry.filename().split('/').filter(|c| !c.is_empty());
+            let file_name = match components.next_back() {
+                Some(name) => name,
+                None => continue,
+            };
+            for name in components {
+                dir = match Self::child(&nodes, dir, name) {
+                    Some(inode) if matches!(nodes[inode as usize - 1], Node::Directory { .. }) => {
+                        inode
+                    }
+                    // a file and a directory with the same name, the first one wins
+                    Some(_) => continue 'entries,
+                    None => Self::push(
+                        &mut nodes,
+                        dir,
+                        name,
+                        Node::Directory {
+                            parent: dir,
+                            children: BTreeMap::new(),
+                        },
+                    ),
+                };
+            }
+            if Self::child(&nodes, dir, file_name).is_none() {
+                Self::push(&mut nodes, dir, file_name, Node::File(entry));
+            }
+        }
+        Self {
+            archive,
+            nodes,
+            mtime: archive.created_at().unwrap_or(UNIX_EPOCH),
+            //SAFETY: getuid and getgid can't fail
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+        }
+    }
+
+    fn child(nodes: &[Node<'a>], dir: u64, name: &str) -> Option<u64> {
+        match &nodes[dir as usize - 1] {
+            Node::Directory { children, .. } => children.get(name).copied(),
+            Node::File(_) => None,
+        }
+    }
+
+    fn push(nodes: &mut Vec<Node<'a>>, dir: u64, name: &'a str, node: Node<'a>) -> u64 {
+        nodes.push(node);
+        let inode = nodes.len() as u64;
+        if let Node::Directory { children, .. } = &mut nodes[dir as usize - 1] {
+            children.insert(name, inode);
+        }
+        inode
+    }
+
+    fn node(&self, inode: u64) -> Option<&Node<'a>> {
+        inode
+            .checked_sub(1)
+            .and_then(|index| self.nodes.get(index as usize))
+    }
+
+    fn attr(&self, inode: u64, node: &Node<'a>) -> FileAttr {
+        let (kind, size, perm, nlink) = match node {
+            Node::Directory { .. } => (FileType::Directory, 0, 0o555, 2),
+            Node::File(entry) => (FileType::RegularFile, entry.size(), 0o444, 1),
+        };
+        FileAttr {
+            ino: inode,
+            size,
+            blocks: size.div_ceil(512),
+            atime: self.mtime,
+            mtime: self.mtime,
+            ctime: self.mtime,
+            crtime: self.mtime,
+            kind,
+            perm,
+            nlink,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+
+    fn read_range(&self, entry: EntryView, offset: u64, size: u64) -> Result<Vec<u8>> {
+        self.archive
+            .read_entry_seekable(&entry.to_entry(), |_, reader| {
+                let mut contents = Vec::new();
+                reader.seek(SeekFrom::Start(offset))?;
+                reader.take(size).read_to_end(&mut contents)?;
+                Ok(contents)
+            })?
+    }
+}
+
+impl<ReaderType> Filesystem for ArchiveFs<'_, ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let inode = match (self.node(parent), name.to_str()) {
+            (Some(_), Some(name)) => Self::child(&self.nodes, parent, name),
+            _ => None,
+        };
+        match inode {
+            Some(inode) => reply.entry(&TTL, &self.attr(inode, &self.nodes[inode as usize - 1]), 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyAttr) {
+        match self.node(ino) {
+            Some(node) => reply.attr(&TTL, &self.attr(ino, node)),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let entry = match self.node(ino) {
+            Some(Node::File(entry)) => *entry,
+            Some(Node::Directory { .. }) => return reply.error(libc::EISDIR),
+            None => return reply.error(libc::ENOENT),
+        };
+        match self.read_range(entry, offset.max(0) as u64, u64::from(size)) {
+            Ok(contents) => reply.data(&contents),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let (parent, children) = match self.node(ino) {
+            Some(Node::Directory { parent, children }) => (*parent, children),
+            Some(Node::File(_)) => return reply.error(libc::ENOTDIR),
+            None => return reply.error(libc::ENOENT),
+        };
+        let listing = std::iter::once((ino, "."))
+            .chain(std::iter::once((parent, "..")))
+            .chain(children.iter().map(|(name, inode)| (*inode, *name)));
+        for (index, (inode, name)) in listing.enumerate().skip(offset.max(0) as usize) {
+            let kind = match self.nodes[inode as usize - 1] {
+                Node::Directory { .. } => FileType::Directory,
+                Node::File(_) => FileType::RegularFile,
+            };
+            // the offset passed back to us is the one of the next entry
+            if reply.add(inode, index as i64 + 1, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
@@ -1,93 +1,234 @@
 use crate::binary::BinaryWriter;
-use crate::{addon_metadata::AddonMetadata, result::Result, AddonTag, AddonType, Error, IDENT};
-use crc::Crc;
-use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use crate::checksum::{Crc32Hasher, Crc32Reader};
+use crate::{
+    addon_metadata::{AddonMetadata, MetadataFormat},
+    ascii::NonAsciiName,
+    compression, disk,
+    result::Result,
+    walk, whitelist, wildcard, AddonTag, AddonType, BuildReport, BuildWarning, BuiltFile,
+    Compression, Config, Error, GmaVersion, ResumeToken, SkipReason, SkippedFile, SteamID, IDENT,
+    MAX_DESCRIPTION_LENGTH,
+};
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom, Write};
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fs::File,
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 //Defaults
-const DEFAULT_VERSION: u8 = 3;
+const DEFAULT_VERSION: GmaVersion = GmaVersion::V3;
 const DEFAULT_STEAMID: u64 = 0;
 const DEFAULT_DESCRIPTION: &str = "";
 const DEFAULT_AUTHOR: &str = "unknown";
-const DEFAULT_COMPRESSION: bool = false;
+const DEFAULT_COMPRESSION: Compression = Compression::None;
+const DEFAULT_ADDON_VERSION: u32 = 1;
+
+/// The files gmad leaves out when creating an addon from a folder, editor projects, version
+/// control directories and files created by the os. See [`GMABuilder::ignore_defaults`]
+pub const DEFAULT_IGNORE: &[&str] = &[
+    "addon.json",
+    "*.psd",
+    "*.vcproj",
+    "*.svn*",
+    ".git/*",
+    "*/.git/*",
+    "*thumbs.db",
+    "*desktop.ini",
+    "*.ds_store",
+];
 
 enum BuilderFileReader {
     FSFile(BufReader<File>),
-    Bytes(Vec<u8>),
+    // files found while adding directories, only opened when written so that large trees don't
+    // keep thousands of files open
+    Path(PathBuf),
+    Bytes(Cow<'static, [u8]>),
+    #[cfg(feature = "bytes")]
+    Shared(bytes::Bytes),
     Reader(Box<dyn Read>),
 }
 
+impl BuilderFileReader {
+    fn is_from_disk(&self) -> bool {
+        matches!(self, Self::FSFile(_) | Self::Path(_))
+    }
+}
+
 struct BuilderFile {
     filename: String,
+    file_number: Option<u32>,
     reader: BuilderFileReader,
 }
 
+type PathFilter = Box<dyn FnMut(&Path) -> bool>;
+
+#[derive(Clone, Copy)]
 struct FilePatchInfo {
     filesize: u64,
     crc: u32,
 }
 
+// A file from disk hashed by `hash_files`, with its modification time when it was hashed
+struct HashedFile {
+    info: FilePatchInfo,
+    modified: Option<SystemTime>,
+}
+
+/// What [`GMABuilder`] does with files added under a filename that was already added, see
+/// [`GMABuilder::duplicates`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Writes every file, the archive has many entries with the same filename and the game only
+    /// loads one of them
+    #[default]
+    Keep,
+    /// Refuses to write the archive, failing with [`Error::DuplicateFilenames`]
+    Reject,
+    /// Writes only the file added last, in its own position
+    Replace,
+}
+
 /// GMA File Builder.
 ///
 /// The only required fields are 'name' and 'addon_tag'
 pub struct GMABuilder {
-    version: Option<u8>,
-    steamid: Option<u64>,
-    timestamp: Option<u64>,
+    version: GmaVersion,
+    steamid: u64,
+    timestamp: u64,
     name: Option<String>,
-    description: Option<String>,
-    author: Option<String>,
+    description: String,
+    author: String,
+    addon_version: u32,
     files: Vec<BuilderFile>,
     addon_type: AddonType,
     addon_tags: [Option<AddonTag>; 2],
-    compression: Option<bool>,
+    compression: Compression,
+    strict_ascii: bool,
+    strict_whitelist: bool,
+    duplicates: DuplicatePolicy,
+    sort_files: bool,
+    skip_hidden: bool,
+    max_file_size: Option<u64>,
+    path_filter: Option<PathFilter>,
+    ignore: Vec<String>,
+    skipped: Vec<SkippedFile>,
+    max_description_length: usize,
+    truncate_description: bool,
+    metadata_format: MetadataFormat,
+    raw_metadata: Option<String>,
+    capture_mtimes: bool,
+    parallel_hashing: bool,
+    // the value of SOURCE_DATE_EPOCH if deterministic couldn't parse it
+    invalid_source_date_epoch: Option<String>,
+}
+
+impl Default for GMABuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GMABuilder {
-    /// Creates a new gma builder
+    /// Creates a new gma builder, [`GMABuilder::strict_ascii`] and
+    /// [`GMABuilder::strict_whitelist`] start from [`crate::Config::global`]
     pub fn new() -> Self {
+        let config = Config::global();
         let current_timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|_| Duration::new(0, 0))
-            .as_secs() as u64;
+            .as_secs();
 
         Self {
-            version: Some(DEFAULT_VERSION),
-            steamid: Some(DEFAULT_STEAMID),
-            timestamp: Some(current_timestamp),
+            version: DEFAULT_VERSION,
+            steamid: DEFAULT_STEAMID,
+            timestamp: current_timestamp,
             name: None,
-            description: Some(DEFAULT_DESCRIPTION.to_owned()),
-            author: Some(DEFAULT_AUTHOR.to_owned()),
+            description: DEFAULT_DESCRIPTION.to_owned(),
+            author: DEFAULT_AUTHOR.to_owned(),
+            addon_version: DEFAULT_ADDON_VERSION,
             files: Vec::new(),
             addon_type: AddonType::Tool,
             addon_tags: [None; 2],
-            compression: Some(DEFAULT_COMPRESSION),
+            compression: DEFAULT_COMPRESSION,
+            strict_ascii: config.strict_ascii,
+            strict_whitelist: config.strict_whitelist,
+            duplicates: DuplicatePolicy::Keep,
+            sort_files: false,
+            skip_hidden: false,
+            max_file_size: None,
+            path_filter: None,
+            ignore: Vec::new(),
+            skipped: Vec::new(),
+            max_description_length: MAX_DESCRIPTION_LENGTH,
+            truncate_description: false,
+            metadata_format: MetadataFormat::Compact,
+            raw_metadata: None,
+            capture_mtimes: false,
+            parallel_hashing: false,
+            invalid_source_date_epoch: None,
         }
     }
 
-    /// Sets the gma version of the archive. Default : 3
-    pub fn version(&mut self, version: u8) -> &mut Self {
-        self.version = Some(version);
+    /// Sets the gma version of the archive, either a [`GmaVersion`] or the version byte.
+    /// The list of required content is only written if [`GmaVersion::has_required_content`]. Default : 3
+    pub fn version<V: Into<GmaVersion>>(&mut self, version: V) -> &mut Self {
+        self.version = version.into();
         self
     }
 
-    /// Sets the steamid of the author. Default : 0
-    pub fn steamid(&mut self, steamid: u64) -> &mut Self {
-        self.steamid = Some(steamid);
+    /// Sets the steamid of the author, either a [`SteamID`] or its 64 bit representation. Default : 0
+    pub fn steamid<S: Into<SteamID>>(&mut self, steamid: S) -> &mut Self {
+        self.steamid = steamid.into().as_u64();
         self
     }
 
     /// Sets the timestamp. Default : current time
     pub fn timestamp(&mut self, timestamp: u64) -> &mut Self {
-        self.timestamp = Some(timestamp);
+        self.timestamp = timestamp;
+        self.invalid_source_date_epoch = None;
         self
     }
 
-    /// Sets the name of the addon. Required
+    /// Sets the timestamp from a [`SystemTime`], times before the UNIX epoch are stored as 0.
+    /// Default : current time
+    pub fn created_at(&mut self, time: SystemTime) -> &mut Self {
+        let timestamp = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::new(0, 0))
+            .as_secs();
+        self.timestamp(timestamp)
+    }
+
+    /// Makes the archive depend only on the builder's fields and the files' names and contents,
+    /// so building the same source tree twice, on any machine, gives byte-identical archives.
+    ///
+    /// Files are written sorted by filename, byte by byte, instead of in the order they were
+    /// added, and the timestamp is taken from the `SOURCE_DATE_EPOCH` environment variable, or
+    /// set to 0 if it is missing. If it is set but is not a unix timestamp writing fails with
+    /// [`Error::InvalidSourceDateEpoch`]. Setting the timestamp after this call overrides it
+    /// ```no_run
+    /// let mut builder = gma::GMABuilder::new();
+    /// builder.name("my addon").deterministic().add_dir("addons/myaddon").unwrap();
+    /// builder.write_to_path("myaddon.gma").unwrap();
+    /// ```
+    pub fn deterministic(&mut self) -> &mut Self {
+        self.sort_files = true;
+        self.timestamp(0);
+        if let Some(epoch) = std::env::var_os("SOURCE_DATE_EPOCH") {
+            match epoch.to_str().and_then(|epoch| epoch.trim().parse().ok()) {
+                Some(timestamp) => self.timestamp = timestamp,
+                None => self.invalid_source_date_epoch = Some(epoch.to_string_lossy().into_owned()),
+            }
+        }
+        self
+    }
+
+    /// Sets the name of the addon. Required, writing fails with [`Error::MissingName`] without it
     pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
         self.name = Some(name.into());
         self
@@ -95,13 +236,51 @@ impl GMABuilder {
 
     /// Sets the description of the addon. Default : ''
     pub fn description<S: Into<String>>(&mut self, description: S) -> &mut Self {
-        self.description = Some(description.into());
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the length in bytes after which the description is reported as too long, with a
+    /// [`BuildWarning::LongDescription`] in the [`BuildReport`].
+    /// Default : [`crate::MAX_DESCRIPTION_LENGTH`]
+    pub fn max_description_length(&mut self, max_len: usize) -> &mut Self {
+        self.max_description_length = max_len;
+        self
+    }
+
+    /// Cuts descriptions longer than [`GMABuilder::max_description_length`] to fit, ending them
+    /// with "...", instead of only reporting them. Default : false
+    pub fn truncate_description(&mut self, truncate: bool) -> &mut Self {
+        self.truncate_description = truncate;
+        self
+    }
+
+    /// Sets how the metadata json with the description, type and tags is formatted.
+    /// [`MetadataFormat::GmadCompatible`] builds archives identical to gmad's.
+    /// Default : [`MetadataFormat::Compact`]
+    pub fn metadata_format(&mut self, format: MetadataFormat) -> &mut Self {
+        self.metadata_format = format;
+        self
+    }
+
+    /// Writes `json` as the metadata string instead of building it from the description, type
+    /// and tags, which are then ignored along with [`GMABuilder::metadata_format`]. Used to keep
+    /// the metadata of an existing archive as it is, see [`crate::GMAFile::metadata_json`].
+    /// Default : built from the other fields
+    pub fn raw_metadata<S: Into<String>>(&mut self, json: S) -> &mut Self {
+        self.raw_metadata = Some(json.into());
         self
     }
 
     /// Sets the name of the author. Default : 'unknown'
     pub fn author<S: Into<String>>(&mut self, author: S) -> &mut Self {
-        self.author = Some(author.into());
+        self.author = author.into();
+        self
+    }
+
+    /// Sets the addon version stored in the header. Default : 1
+    pub fn addon_version(&mut self, addon_version: u32) -> &mut Self {
+        self.addon_version = addon_version;
         self
     }
 
@@ -111,7 +290,39 @@ impl GMABuilder {
     /// Support for compressed files is mostly here to interact with files downloaded straight
     /// from the steamworkshop that could be compressed
     pub fn compression(&mut self, c: bool) -> &mut Self {
-        self.compression = Some(c);
+        self.compression = match c {
+            true => Compression::Lzma,
+            false => Compression::None,
+        };
+        self
+    }
+
+    /// Sets the compression format of the archive. Default : [`Compression::None`]
+    pub fn compression_format(&mut self, format: Compression) -> &mut Self {
+        self.compression = format;
+        self
+    }
+
+    /// Refuses to write the archive if any filename contains non ascii characters, failing with
+    /// [`Error::NonAsciiFilenames`] which lists the offending names and suggested replacements.
+    /// Default : false
+    pub fn strict_ascii(&mut self, strict: bool) -> &mut Self {
+        self.strict_ascii = strict;
+        self
+    }
+
+    /// What to do with files added with the same filename as an earlier one, compared
+    /// case-insensitively like the game does. Default : [`DuplicatePolicy::Keep`]
+    pub fn duplicates(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.duplicates = policy;
+        self
+    }
+
+    /// Refuses to write the archive if any filename is not allowed by the game's whitelist, like
+    /// gmad does, failing with [`Error::NotWhitelisted`] which lists every offending name.
+    /// See [`crate::is_whitelisted`]. Default : false
+    pub fn strict_whitelist(&mut self, strict: bool) -> &mut Self {
+        self.strict_whitelist = strict;
         self
     }
 
@@ -141,10 +352,11 @@ impl GMABuilder {
         &mut self,
         path: S,
     ) -> std::result::Result<&mut Self, std::io::Error> {
-        let file = File::open(&path)?;
+        let reader = open_buffered(path.as_ref())?;
         self.files.push(BuilderFile {
             filename: path.as_ref().to_string_lossy().as_ref().to_owned(),
-            reader: BuilderFileReader::FSFile(BufReader::new(file)),
+            file_number: None,
+            reader: BuilderFileReader::FSFile(reader),
         });
         Ok(self)
     }
@@ -154,23 +366,227 @@ impl GMABuilder {
         path: P,
         name: N,
     ) -> std::result::Result<&mut Self, std::io::Error> {
-        let file = File::open(&path)?;
+        let reader = open_buffered(path.as_ref())?;
         self.files.push(BuilderFile {
             filename: name.into(),
-            reader: BuilderFileReader::FSFile(BufReader::new(file)),
+            file_number: None,
+            reader: BuilderFileReader::FSFile(reader),
         });
         Ok(self)
     }
 
+    /// Skips hidden files and directories, the ones whose name starts with a '.',
+    /// when adding directories. Default : false
+    pub fn skip_hidden(&mut self, skip: bool) -> &mut Self {
+        self.skip_hidden = skip;
+        self
+    }
+
+    /// Records the modification time of files added from disk in the [`BuildReport`], see
+    /// [`crate::BuiltFile::modified`] and [`BuildReport::write_manifest`]. Default : false
+    pub fn capture_mtimes(&mut self, capture: bool) -> &mut Self {
+        self.capture_mtimes = capture;
+        self
+    }
+
+    /// Computes the size and crc32 of the files added from disk on all cores before writing
+    /// anything, the file table is then written complete and the contents are only copied.
+    /// Cuts the build time of addons with many files on fast disks, where hashing and not
+    /// reading is what takes the time.
+    ///
+    /// Every file is read twice, the second time usually from the os cache. A file whose size or
+    /// modification time changes in between fails the build with [`Error::SourceChanged`], a
+    /// change that keeps both is not detected and the entry keeps the crc computed first.
+    /// Default : false
+    pub fn parallel_hashing(&mut self, parallel: bool) -> &mut Self {
+        self.parallel_hashing = parallel;
+        self
+    }
+
+    /// Skips files larger than `bytes` when adding directories. Default : no limit
+    pub fn max_file_size(&mut self, bytes: u64) -> &mut Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Sets a callback that is called with the path of every file found when adding directories.
+    /// Files for which the callback returns false are skipped
+    pub fn path_filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: FnMut(&Path) -> bool + 'static,
+    {
+        self.path_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Skips the files whose path relative to the added directory matches `pattern` when adding
+    /// directories. Patterns follow the same rules as in [`GMABuilder::files_from_glob`], so
+    /// `*.txt` ignores text files in every directory. Default : nothing is ignored
+    pub fn ignore<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    /// Ignores the same files gmad does, see [`DEFAULT_IGNORE`] and [`GMABuilder::ignore`]
+    pub fn ignore_defaults(&mut self) -> &mut Self {
+        self.ignore
+            .extend(DEFAULT_IGNORE.iter().map(|pattern| pattern.to_string()));
+        self
+    }
+
+    /// Ignores the patterns listed in a file, like a `.gmaignore`, see [`GMABuilder::ignore`].
+    ///
+    /// The file has a pattern per line, blank lines and lines starting with '#' are skipped
+    /// ```no_run
+    /// let mut builder = gma::GMABuilder::new();
+    /// builder
+    ///     .name("my addon")
+    ///     .ignore_defaults()
+    ///     .ignore_file("addons/myaddon/.gmaignore")
+    ///     .unwrap()
+    ///     .ignore(".gmaignore")
+    ///     .add_dir("addons/myaddon")
+    ///     .unwrap();
+    /// ```
+    pub fn ignore_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> std::result::Result<&mut Self, std::io::Error> {
+        let contents = std::fs::read_to_string(path)?;
+        let patterns = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        self.ignore.extend(patterns.map(str::to_owned));
+        Ok(self)
+    }
+
+    /// Adds every file under `root`, recursively, in order of their path relative to `root`.
+    ///
+    /// The relative paths, using '/' as the separator, are used as the filenames inside the
+    /// archive. Ex : `root/lua/autorun/init.lua` is added as `lua/autorun/init.lua`.
+    ///
+    /// Files rejected by [`GMABuilder::skip_hidden`], [`GMABuilder::ignore`],
+    /// [`GMABuilder::max_file_size`] or [`GMABuilder::path_filter`] are listed in the
+    /// [`BuildReport`], see [`GMABuilder::files_from_glob`] to only add some of the files
+    /// ```no_run
+    /// let mut builder = gma::GMABuilder::new();
+    /// builder.name("my addon").skip_hidden(true).add_dir("addons/myaddon").unwrap();
+    /// builder.write_to_path("myaddon.gma").unwrap();
+    /// ```
+    pub fn add_dir<P: AsRef<Path>>(
+        &mut self,
+        root: P,
+    ) -> std::result::Result<&mut Self, std::io::Error> {
+        for file in walk::walk_dir(root.as_ref())? {
+            self.add_walked_file(file)?;
+        }
+        Ok(self)
+    }
+
+    /// Adds every file under `root` whose path relative to `root` matches one of `patterns`.
+    ///
+    /// The relative paths, using '/' as the separator, are used as the filenames inside the archive.
+    /// Patterns follow the same rules as the `ignore` list in addon.json: `*` matches any sequence
+    /// of characters, including '/', `?` matches a single character and case is ignored.
+    /// Ex : `lua/*.lua` adds every lua file under `root/lua`
+    ///
+    /// Files rejected by [`GMABuilder::skip_hidden`], [`GMABuilder::ignore`],
+    /// [`GMABuilder::max_file_size`] or [`GMABuilder::path_filter`] are listed in the
+    /// [`BuildReport`]
+    pub fn files_from_glob<P: AsRef<Path>, S: AsRef<str>>(
+        &mut self,
+        root: P,
+        patterns: &[S],
+    ) -> std::result::Result<&mut Self, std::io::Error> {
+        for file in walk::walk_dir(root.as_ref())? {
+            if patterns
+                .iter()
+                .any(|pattern| wildcard::matches(pattern.as_ref(), &file.relative))
+            {
+                self.add_walked_file(file)?;
+            }
+        }
+        Ok(self)
+    }
+
+    fn add_walked_file(
+        &mut self,
+        file: walk::WalkedFile,
+    ) -> std::result::Result<(), std::io::Error> {
+        let ignored = self
+            .ignore
+            .iter()
+            .find(|pattern| wildcard::matches(pattern, &file.relative));
+        let reason = if self.skip_hidden && file.relative.split('/').any(|c| c.starts_with('.')) {
+            Some(SkipReason::Hidden)
+        } else if let Some(pattern) = ignored {
+            Some(SkipReason::Ignored(pattern.clone()))
+        } else if self.max_file_size.is_some_and(|max| file.size > max) {
+            Some(SkipReason::TooLarge(file.size))
+        } else if self
+            .path_filter
+            .as_mut()
+            .is_some_and(|filter| !filter(&file.path))
+        {
+            Some(SkipReason::Filtered)
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => self.skipped.push(SkippedFile {
+                filename: file.relative,
+                reason,
+            }),
+            None => self.files.push(BuilderFile {
+                filename: file.relative,
+                file_number: None,
+                reader: BuilderFileReader::Path(file.path),
+            }),
+        }
+        Ok(())
+    }
+
     /// Adds a file with the given filename and contents
     pub fn file_from_bytes<S: Into<String>>(&mut self, filename: S, bytes: Vec<u8>) -> &mut Self {
         self.files.push(BuilderFile {
             filename: filename.into(),
+            file_number: None,
+            reader: BuilderFileReader::Bytes(Cow::Owned(bytes)),
+        });
+        self
+    }
+
+    /// Adds a file with the given filename and contents, borrowed contents are not copied
+    pub fn file_from_cow<S: Into<String>>(
+        &mut self,
+        filename: S,
+        bytes: Cow<'static, [u8]>,
+    ) -> &mut Self {
+        self.files.push(BuilderFile {
+            filename: filename.into(),
+            file_number: None,
             reader: BuilderFileReader::Bytes(bytes),
         });
         self
     }
 
+    /// Adds a file with the given filename and contents held in a shared [`bytes::Bytes`] buffer,
+    /// the buffer is not copied
+    #[cfg(feature = "bytes")]
+    pub fn file_from_bytes_shared<S: Into<String>>(
+        &mut self,
+        filename: S,
+        bytes: bytes::Bytes,
+    ) -> &mut Self {
+        self.files.push(BuilderFile {
+            filename: filename.into(),
+            file_number: None,
+            reader: BuilderFileReader::Shared(bytes),
+        });
+        self
+    }
+
     /// Adds a file with the given filename and contents are read from `reader`
     pub fn file_from_reader<S: Into<String>, R: Read + 'static>(
         &mut self,
@@ -179,121 +595,462 @@ impl GMABuilder {
     ) -> &mut Self {
         self.files.push(BuilderFile {
             filename: filename.into(),
+            file_number: None,
             reader: BuilderFileReader::Reader(Box::new(reader)),
         });
         self
     }
 
-    /// Consumes the builder and writes the gma file contents to the given `writer`
-    pub fn write_to<WriterType>(self, mut writer: WriterType) -> Result<()>
+    /// Sets the file number stored in the file table for the last added file. The game stops
+    /// reading the file table at a 0 so it is never written, the position based number is used
+    /// instead. Default : the position of the file, starting at 1
+    pub fn file_number(&mut self, number: u32) -> &mut Self {
+        if let Some(file) = self.files.last_mut() {
+            file.file_number = Some(number).filter(|n| *n != 0);
+        }
+        self
+    }
+
+    /// An estimate of the size in bytes of the uncompressed archive.
+    ///
+    /// Files added with [`GMABuilder::file_from_reader`] have an unknown size and are not counted
+    pub fn estimated_size(&self) -> u64 {
+        //fixed size header fields, the file table terminator and some room for the metadata json
+        const HEADER_SIZE: u64 = 4 + 1 + 8 + 8 + 1 + 4 + 4 + 256;
+        //file number, filesize and crc
+        const ENTRY_SIZE: u64 = 4 + 8 + 4;
+        let strings = [
+            self.name.as_deref(),
+            Some(&self.description),
+            Some(&self.author),
+        ]
+        .iter()
+        .map(|s| s.map_or(0, |s| s.len() as u64 + 1))
+        .sum::<u64>();
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let contents = match &file.reader {
+                    BuilderFileReader::FSFile(reader) => {
+                        reader.get_ref().metadata().map_or(0, |m| m.len())
+                    }
+                    BuilderFileReader::Path(path) => std::fs::metadata(path).map_or(0, |m| m.len()),
+                    BuilderFileReader::Bytes(bytes) => bytes.len() as u64,
+                    #[cfg(feature = "bytes")]
+                    BuilderFileReader::Shared(bytes) => bytes.len() as u64,
+                    BuilderFileReader::Reader(_) => 0,
+                };
+                ENTRY_SIZE + file.filename.len() as u64 + 1 + contents
+            })
+            .sum::<u64>();
+        HEADER_SIZE + strings + files
+    }
+
+    /// Consumes the builder and writes the archive to a file at `path`.
+    ///
+    /// Before writing, the available disk space is checked against [`GMABuilder::estimated_size`]
+    /// and [`Error::InsufficientSpace`] is returned if it is not enough.
+    /// The archive is written to a temporary file in the same directory that is renamed to `path`
+    /// once complete, so `path` never contains a partially written archive. The temporary file is
+    /// removed if writing fails, so errors are never [`Error::BuildInterrupted`]
+    pub fn write_to_path<P: AsRef<Path>>(self, path: P) -> Result<BuildReport> {
+        let needed = self.estimated_size();
+        disk::write_atomically(path.as_ref(), needed, |writer| {
+            self.write_to_with_report(writer)
+                .map_err(Error::uninterrupted)
+        })
+    }
+
+    /// Consumes the builder and writes the gma file contents to the given `writer`.
+    ///
+    /// See [`GMABuilder::write_to_with_report`] to also get the [`BuildReport`], or to resume
+    /// writing after an error
+    pub fn write_to<WriterType>(self, writer: WriterType) -> Result<()>
+    where
+        WriterType: Write + Seek,
+    {
+        self.write_to_with_report(writer)
+            .map(|_| ())
+            .map_err(Error::uninterrupted)
+    }
+
+    /// Like [`GMABuilder::write_to`] but returns the [`BuildReport`] of the archive written.
+    ///
+    /// If writing fails after the header, while the files are being written, the error is
+    /// [`Error::BuildInterrupted`] and the files already written don't need to be written again,
+    /// see [`GMABuilder::resume`]
+    pub fn write_to_with_report<WriterType>(mut self, mut writer: WriterType) -> Result<BuildReport>
     where
         WriterType: Write + Seek,
     {
-        match self.compression.unwrap() {
-            true => {
+        self.prepare_files()?;
+        match self.compression {
+            Compression::None => Self::write_to_gen(self, writer, None),
+            format => {
                 let buffer = Vec::with_capacity(1024 * 1024 * 32);
                 let mut bufwriter = Cursor::new(buffer);
-                Self::write_to_gen(self, &mut bufwriter)?;
-                bufwriter.seek(SeekFrom::Start(0))?;
-                lzma_rs::lzma_compress(&mut bufwriter, &mut writer).unwrap();
-                Ok(())
+                // the partial archive was only in memory, there is nothing to resume
+                let report =
+                    Self::write_to_gen(self, &mut bufwriter, None).map_err(Error::uninterrupted)?;
+                compression::compress(format, bufwriter.get_ref(), &mut writer)?;
+                Ok(report)
+            }
+        }
+    }
+
+    /// Continues writing an archive whose [`GMABuilder::write_to_with_report`] was interrupted,
+    /// using the token from the report of the [`Error::BuildInterrupted`] error. The files written
+    /// before the interruption are not read again, which saves rebuilding very large archives
+    /// after a transient failure, like a network drive going away.
+    ///
+    /// The builder must have the same fields and the same files, in the same order, as the
+    /// interrupted one, the timestamp is taken from the token. `writer` must be the output that
+    /// was being written, usually the same file opened again without truncating it, holding
+    /// everything written up to [`ResumeToken::position`]. Anything after that is overwritten.
+    ///
+    /// Fails with [`Error::ResumeMismatch`] if the builder would write a different header or file
+    /// table, or if it compresses the archive, since compressed archives are written in one go
+    /// ```no_run
+    /// # fn builder() -> gma::GMABuilder { gma::GMABuilder::new() }
+    /// let file = std::fs::File::create("big.gma").unwrap();
+    /// if let Err(gma::Error::BuildInterrupted { error, report }) = builder().write_to_with_report(file) {
+    ///     println!("interrupted : {}", error);
+    ///     let token = report.resume_token().unwrap().clone();
+    ///     let file = std::fs::OpenOptions::new().write(true).open("big.gma").unwrap();
+    ///     builder().resume(token, file).unwrap();
+    /// }
+    /// ```
+    pub fn resume<WriterType>(
+        mut self,
+        token: ResumeToken,
+        writer: WriterType,
+    ) -> Result<BuildReport>
+    where
+        WriterType: Write + Seek,
+    {
+        self.prepare_files()?;
+        if self.compression != Compression::None {
+            return Err(Error::ResumeMismatch);
+        }
+        Self::write_to_gen(self, writer, Some(token))
+    }
+
+    fn prepare_files(&mut self) -> Result<()> {
+        self.apply_duplicate_policy()?;
+        if self.sort_files {
+            self.files.sort_by(|a, b| a.filename.cmp(&b.filename));
+        }
+        self.check_filenames()
+    }
+
+    fn apply_duplicate_policy(&mut self) -> Result<()> {
+        if self.duplicates == DuplicatePolicy::Keep {
+            return Ok(());
+        }
+        // the index of the last file added with each filename
+        let mut last: HashMap<String, usize> = HashMap::with_capacity(self.files.len());
+        for (index, file) in self.files.iter().enumerate() {
+            last.insert(file.filename.to_lowercase(), index);
+        }
+        if last.len() == self.files.len() {
+            return Ok(());
+        }
+        let mut index = 0;
+        let mut replaced = Vec::new();
+        self.files.retain(|file| {
+            let keep = last[&file.filename.to_lowercase()] == index;
+            if !keep {
+                replaced.push(file.filename.clone());
+            }
+            index += 1;
+            keep
+        });
+        match self.duplicates {
+            DuplicatePolicy::Reject => Err(Error::DuplicateFilenames(replaced)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_filenames(&self) -> Result<()> {
+        if self.strict_whitelist {
+            let rejected: Vec<String> = self
+                .files
+                .iter()
+                .filter(|f| !whitelist::is_whitelisted(&f.filename))
+                .map(|f| f.filename.clone())
+                .collect();
+            if !rejected.is_empty() {
+                return Err(Error::NotWhitelisted(rejected));
+            }
+        }
+        if self.strict_ascii {
+            let non_ascii: Vec<NonAsciiName> = self
+                .files
+                .iter()
+                .filter_map(|f| NonAsciiName::check(&f.filename))
+                .collect();
+            if !non_ascii.is_empty() {
+                return Err(Error::NonAsciiFilenames(non_ascii));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_to_gen<WriterType: Write + Seek>(
+        mut self,
+        mut writer: WriterType,
+        resume: Option<ResumeToken>,
+    ) -> Result<BuildReport> {
+        if let Some(token) = &resume {
+            self.timestamp = token.timestamp;
+        }
+        // files written before the interruption aren't written again
+        let skip = resume
+            .as_ref()
+            .map_or(0, |token| token.files.len().min(self.files.len()));
+        let hashed = match self.parallel_hashing {
+            true => hash_files(&mut self.files[skip..])?,
+            false => self.files[skip..].iter().map(|_| None).collect(),
+        };
+        // what the file table can be written with, the rest is patched once the files are written
+        let resumed: Vec<Option<FilePatchInfo>> = match &resume {
+            Some(token) if self.parallel_hashing => token.files[..skip]
+                .iter()
+                .zip(&self.files)
+                .map(|(built, file)| {
+                    file.reader.is_from_disk().then_some(FilePatchInfo {
+                        filesize: built.size,
+                        crc: built.crc,
+                    })
+                })
+                .collect(),
+            _ => self.files[..skip].iter().map(|_| None).collect(),
+        };
+        let known: Vec<Option<FilePatchInfo>> = resumed
+            .into_iter()
+            .chain(hashed.iter().map(|hashed| hashed.as_ref().map(|h| h.info)))
+            .collect();
+        let mut header = Cursor::new(Vec::new());
+        let (warnings, patch_offsets) = self.write_header(&mut header, &known)?;
+        let header = header.into_inner();
+        let mut hasher = Crc32Hasher::new();
+        hasher.update(&header);
+        let header_crc = hasher.finalize();
+
+        //absolute offset of the archive inside the writer and the files already written
+        let (start, mut built) = match resume {
+            None => {
+                let start = writer.stream_position()?;
+                writer.write_all(&header)?;
+                (start, Vec::with_capacity(self.files.len()))
+            }
+            Some(token) => {
+                let same = token.header_len == header.len() as u64
+                    && token.header_crc == header_crc
+                    && token.files.len() <= self.files.len();
+                if !same {
+                    return Err(Error::ResumeMismatch);
+                }
+                writer.seek(SeekFrom::Start(token.position))?;
+                (token.start, token.files)
+            }
+        };
+
+        let capture_mtimes = self.capture_mtimes;
+        let pending: Vec<(String, Option<SystemTime>, BuilderFile)> = self
+            .files
+            .into_iter()
+            .skip(built.len())
+            .map(|f| {
+                let modified = match (&f.reader, capture_mtimes) {
+                    (BuilderFileReader::FSFile(reader), true) => {
+                        reader.get_ref().metadata().and_then(|m| m.modified()).ok()
+                    }
+                    (BuilderFileReader::Path(path), true) => {
+                        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+                    }
+                    _ => None,
+                };
+                (f.filename.clone(), modified, f)
+            })
+            .collect();
+        //end of the last file written completely
+        let mut position = start + header.len() as u64 + built.iter().map(|f| f.size).sum::<u64>();
+        let write_files = || -> Result<()> {
+            for ((filename, modified, entry), hashed) in pending.into_iter().zip(hashed) {
+                let (written, info) = Self::write_file_contents(&mut writer, entry, hashed)
+                    .map_err(|e| e.in_entry(&filename))?;
+                position += written as u64;
+                built.push(BuiltFile {
+                    filename,
+                    size: info.filesize,
+                    crc: info.crc,
+                    modified,
+                });
             }
-            false => Self::write_to_gen(self, writer),
+            for (offset, file) in patch_offsets.iter().zip(&built) {
+                // entries written complete in the file table are left alone
+                let offset = match offset {
+                    Some(offset) => offset,
+                    None => continue,
+                };
+                let info = FilePatchInfo {
+                    filesize: file.size,
+                    crc: file.crc,
+                };
+                Self::apply_file_entry_patch(&mut writer, start + offset, info)?;
+            }
+            Ok(())
+        };
+        let result = write_files();
+
+        let mut report = BuildReport {
+            files: built,
+            skipped: self.skipped,
+            warnings,
+            resume_token: None,
+        };
+        if let Err(error) = result {
+            report.resume_token = Some(ResumeToken {
+                start,
+                header_len: header.len() as u64,
+                header_crc,
+                timestamp: self.timestamp,
+                position,
+                files: report.files.clone(),
+            });
+            return Err(Error::BuildInterrupted {
+                error: Box::new(error),
+                report: Box::new(report),
+            });
         }
+        Ok(report)
     }
 
-    fn write_to_gen<WriterType: Write + Seek>(self, mut writer: WriterType) -> Result<()> {
-        let name = self
-            .name
-            .expect("You need to provided a name for the addon file");
+    //Returns the warnings and the offsets of the filesize field of every entry that isn't `known`,
+    //to patch it later
+    fn write_header<WriterType: Write + Seek>(
+        &self,
+        mut writer: WriterType,
+        known: &[Option<FilePatchInfo>],
+    ) -> Result<(Vec<BuildWarning>, Vec<Option<u64>>)> {
+        let name = self.name.as_deref().ok_or(Error::MissingName)?;
+        if let Some(epoch) = &self.invalid_source_date_epoch {
+            return Err(Error::InvalidSourceDateEpoch(epoch.clone()));
+        }
+        let mut warnings = Vec::new();
 
         Self::write_ident(&mut writer)?;
         //write version
-        writer.write_u8(self.version.unwrap())?;
+        writer.write_u8(self.version.as_u8())?;
         //write steamid
-        writer.write_u64(self.steamid.unwrap())?;
+        writer.write_u64(self.steamid)?;
         //write timestamp
-        writer.write_u64(self.timestamp.unwrap())?;
+        writer.write_u64(self.timestamp)?;
         //write required contents
         //this is unused right now so just write an empty string
-        writer.write_u8(0)?;
+        if self.version.has_required_content() {
+            writer.write_u8(0)?;
+        }
         //write addon name
-        writer.write_c_string(&name)?;
+        writer.write_c_string(name)?;
         //write metadata string
-        let tags: Vec<AddonTag> = self
-            .addon_tags
-            .iter()
-            .filter(|p| p.is_some())
-            .map(|p| p.unwrap())
-            .collect();
-        let metadata = AddonMetadata::new(
-            name.to_owned(),
-            self.description.unwrap(),
-            &self.addon_type,
-            &tags,
-        );
-        let metadata_json = metadata.to_json();
+        let metadata_json = match &self.raw_metadata {
+            Some(json) => json.clone(),
+            None => {
+                // the description is only written as part of the generated metadata
+                let mut description = self.description.clone();
+                if description.len() > self.max_description_length {
+                    warnings.push(BuildWarning::LongDescription {
+                        len: description.len(),
+                        max: self.max_description_length,
+                        truncated: self.truncate_description,
+                    });
+                    if self.truncate_description {
+                        truncate_with_ellipsis(&mut description, self.max_description_length);
+                    }
+                }
+                let tags: Vec<AddonTag> = self.addon_tags.iter().flatten().copied().collect();
+                let metadata =
+                    AddonMetadata::new(name.to_owned(), description, &self.addon_type, &tags);
+                metadata.to_json_with(self.metadata_format)
+            }
+        };
         writer.write_c_string(&metadata_json)?;
         //write author name
-        writer.write_c_string(&self.author.unwrap())?;
+        writer.write_c_string(&self.author)?;
         //write addon_version
-        //this is currently unused and should be set to 1
-        writer.write_u32(1)?;
+        //this is currently unused and is usually set to 1
+        writer.write_u32(self.addon_version)?;
 
         //write file entries
-        //absolute offsets inside the writer
         let mut patch_offsets = Vec::with_capacity(self.files.len());
-        let mut patch_info = Vec::with_capacity(self.files.len());
-        for (i, entry) in self.files.iter().enumerate() {
-            let file_number = (i + 1) as u32;
+        for (i, (entry, known)) in self.files.iter().zip(known).enumerate() {
+            let file_number = entry.file_number.unwrap_or((i + 1) as u32);
             let (_, patch_offset) =
-                Self::write_incomplete_file_entry(&mut writer, file_number, &entry)?;
+                Self::write_file_entry(&mut writer, file_number, entry, *known)?;
             patch_offsets.push(patch_offset);
         }
         //we need to write a 0 to indicate the end of file entries
         writer.write_u32(0)?;
-        for entry in self.files.into_iter() {
-            let (_, patch) = Self::write_file_contents(&mut writer, entry)?;
-            patch_info.push(patch)
-        }
-        assert_eq!(patch_info.len(), patch_offsets.len());
-        for (offset, info) in patch_offsets.into_iter().zip(patch_info.into_iter()) {
-            Self::apply_file_entry_patch(&mut writer, offset, info)?;
-        }
-
-        Ok(())
+        Ok((warnings, patch_offsets))
     }
 
     fn write_ident<WriterType: Write>(mut writer: WriterType) -> Result<usize> {
         Ok(writer.write(&IDENT)?)
     }
 
-    //Returns the amount of bytes written and the offset to the filesize field so we can patch it later
-    fn write_incomplete_file_entry<WriterType: Write + Seek>(
+    //Returns the amount of bytes written and, unless the size and crc are `known`, the offset to
+    //the filesize field so we can patch it later
+    fn write_file_entry<WriterType: Write + Seek>(
         mut writer: WriterType,
         file_number: u32,
         bfile: &BuilderFile,
-    ) -> Result<(usize, u64)> {
+        known: Option<FilePatchInfo>,
+    ) -> Result<(usize, Option<u64>)> {
         let mut bytes_written = 0;
         bytes_written += writer.write_u32(file_number)?;
         bytes_written += writer.write_c_string(&bfile.filename)?;
-        //write filesize, crc32 and offset. We will patch this values later
-        let offset_to_patch_start = writer.seek(SeekFrom::Current(0))?;
-        bytes_written += writer.write_u64(0)?;
-        bytes_written += writer.write_u32(0)?;
+        let offset_to_patch_start = match known {
+            Some(_) => None,
+            None => Some(writer.stream_position()?),
+        };
+        //write filesize and crc32, we will patch this values later if they aren't known
+        let info = known.unwrap_or(FilePatchInfo {
+            filesize: 0,
+            crc: 0,
+        });
+        bytes_written += writer.write_u64(info.filesize)?;
+        bytes_written += writer.write_u32(info.crc)?;
         Ok((bytes_written, offset_to_patch_start))
     }
 
+    // `hashed` is the size and crc computed by `hash_files`, the contents are only copied then
     fn write_file_contents<WriterType: Write + Seek>(
         mut writer: WriterType,
         bfile: BuilderFile,
+        hashed: Option<HashedFile>,
     ) -> Result<(usize, FilePatchInfo)> {
+        let filename = bfile.filename;
+        if let Some(hashed) = hashed {
+            let mut reader = match bfile.reader {
+                BuilderFileReader::FSFile(reader) => reader,
+                BuilderFileReader::Path(path) => open_buffered(&path)?,
+                _ => unreachable!("only files from disk are hashed"),
+            };
+            let written = io::copy(&mut reader, &mut writer)?;
+            let modified = reader.get_ref().metadata()?.modified().ok();
+            if written != hashed.info.filesize || modified != hashed.modified {
+                return Err(Error::SourceChanged(filename));
+            }
+            return Ok((written as usize, hashed.info));
+        }
         let mut write_contents = |reader: &mut dyn Read| -> Result<(usize, FilePatchInfo)> {
             const BLOCK_SIZE: usize = 8096;
             let mut bytes_written: usize = 0;
             let mut buffer: [u8; BLOCK_SIZE] = [0; BLOCK_SIZE];
-            let crc = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
-            let mut digest = crc.digest();
+            let mut hasher = Crc32Hasher::new();
             loop {
                 let read_result = reader.read(&mut buffer);
                 match read_result {
@@ -302,13 +1059,13 @@ impl GMABuilder {
                             bytes_written,
                             FilePatchInfo {
                                 filesize: bytes_written as u64,
-                                crc: digest.finalize() as u32,
+                                crc: hasher.finalize(),
                             },
                         ));
                     }
                     Ok(n) => {
                         let data_slice = &buffer[0..n];
-                        digest.update(data_slice);
+                        hasher.update(data_slice);
                         writer.write_all(data_slice)?;
                         bytes_written += n;
                     }
@@ -319,7 +1076,10 @@ impl GMABuilder {
         };
         match bfile.reader {
             BuilderFileReader::FSFile(mut reader) => write_contents(&mut reader),
-            BuilderFileReader::Bytes(bytes) => write_contents(&mut bytes.as_slice()),
+            BuilderFileReader::Path(path) => write_contents(&mut open_buffered(&path)?),
+            BuilderFileReader::Bytes(bytes) => write_contents(&mut bytes.as_ref()),
+            #[cfg(feature = "bytes")]
+            BuilderFileReader::Shared(bytes) => write_contents(&mut bytes.as_ref()),
             BuilderFileReader::Reader(mut reader) => write_contents(&mut reader),
         }
     }
@@ -335,3 +1095,100 @@ impl GMABuilder {
         Ok(())
     }
 }
+
+// The files from disk, hashed on other threads, which the other readers can't be sent to
+enum HashSource<'a> {
+    Open(&'a mut BufReader<File>),
+    Closed(&'a Path),
+}
+
+// The size and crc of the files read from disk, None for the others. The files are hashed on
+// as many threads as there are cores and rewound to where they were
+fn hash_files(files: &mut [BuilderFile]) -> Result<Vec<Option<HashedFile>>> {
+    let mut hashed: Vec<Option<HashedFile>> = files.iter().map(|_| None).collect();
+    let sources: Vec<_> = hashed
+        .iter_mut()
+        .zip(files.iter_mut())
+        .filter_map(|(slot, file)| match &mut file.reader {
+            BuilderFileReader::FSFile(reader) => {
+                Some((slot, file.filename.as_str(), HashSource::Open(reader)))
+            }
+            BuilderFileReader::Path(path) => {
+                Some((slot, file.filename.as_str(), HashSource::Closed(path)))
+            }
+            _ => None,
+        })
+        .collect();
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(sources.len());
+    let queue = Mutex::new(sources.into_iter());
+    thread::scope(|scope| -> Result<()> {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    loop {
+                        // a worker only panics on a bug, which is resumed below
+                        let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                        match next {
+                            Some((slot, filename, source)) => {
+                                let info = match source {
+                                    HashSource::Open(reader) => hash_file(reader),
+                                    HashSource::Closed(path) => {
+                                        open_buffered(path).and_then(|mut r| hash_file(&mut r))
+                                    }
+                                }
+                                .map_err(|e| Error::from(e).in_entry(filename))?;
+                                *slot = Some(info);
+                            }
+                            None => return Ok(()),
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+        }
+        Ok(())
+    })?;
+    Ok(hashed)
+}
+
+fn open_buffered(path: &Path) -> io::Result<BufReader<File>> {
+    Ok(BufReader::with_capacity(
+        Config::global().buffer_size,
+        File::open(path)?,
+    ))
+}
+
+fn hash_file(reader: &mut BufReader<File>) -> io::Result<HashedFile> {
+    let modified = reader.get_ref().metadata()?.modified().ok();
+    let start = reader.stream_position()?;
+    let mut hasher = Crc32Reader::new(&mut *reader);
+    let filesize = io::copy(&mut hasher, &mut io::sink())?;
+    let crc = hasher.finish()?;
+    reader.seek(SeekFrom::Start(start))?;
+    Ok(HashedFile {
+        info: FilePatchInfo { filesize, crc },
+        modified,
+    })
+}
+
+// Cuts `text` to at most `max_len` bytes, on a char boundary, ending with "..." if there is room
+fn truncate_with_ellipsis(text: &mut String, max_len: usize) {
+    const ELLIPSIS: &str = "...";
+    let ellipsis = if max_len >= ELLIPSIS.len() {
+        ELLIPSIS
+    } else {
+        ""
+    };
+    let mut end = max_len - ellipsis.len();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(ellipsis);
+}
@@ -1,23 +1,57 @@
 use crate::{
-    addon_metadata::AddonMetadata, binary::BinaryReader, AddonTag, AddonType, Error, Result, IDENT,
-    VALID_VERSIONS,
+    addon_metadata::AddonMetadata,
+    binary::{self, BinaryReader},
+    checksum::{Crc32Hasher, Crc32Reader},
+    compression::{self, Decompressed},
+    disk::TempFile,
+    entry_cache::EntryCache,
+    entry_table::CompactEntries,
+    read_at::ConcurrentSource,
+    AddonTag, AddonType, Compression, Config, ContentKind, EntryReader, EntryView, Error,
+    GmaVersion, LoadOptions, ParseTrace, Result, SteamID, TraceField, IDENT,
 };
-use lzma_rs;
 use std::{
-    cell::RefCell,
-    io::{BufRead, Cursor, Read, Seek, SeekFrom},
+    collections::HashMap,
+    fmt::{Arguments, Debug},
+    io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    ops::{Index, Range},
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+    thread::{self, ThreadId},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 /// GMA File Entry
 #[derive(Debug)]
 pub struct FileEntry {
-    filename: String,
+    filename: Arc<str>,
     filesize: u64,
     crc: u32,
+    index: usize,
+    file_number: u32,
     offset: u64,
+    absolute_offset: u64,
 }
 
 impl FileEntry {
+    pub(crate) fn new(
+        filename: Arc<str>,
+        filesize: u64,
+        crc: u32,
+        index: usize,
+        file_number: u32,
+        offset: u64,
+        absolute_offset: u64,
+    ) -> Self {
+        Self {
+            filename,
+            filesize,
+            crc,
+            index,
+            file_number,
+            offset,
+            absolute_offset,
+        }
+    }
     /// The full filename of this entry. Ex : lua/autorun/cl_myscript.lua
     pub fn filename(&self) -> &str {
         &self.filename
@@ -30,10 +64,103 @@ impl FileEntry {
     pub fn crc(&self) -> u32 {
         self.crc
     }
+    /// The position of this entry in the archive's file table, starting at 0. Entries left out
+    /// by [`LoadOptions::entry_filter`] are not counted
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// The file number stored in the file table. gmad numbers files from 1 in order but modified
+    /// archives can have any non zero value, see [`LoadOptions::strict_file_numbers`]
+    pub fn file_number(&self) -> u32 {
+        self.file_number
+    }
     /// The offset in the gma file, starting from the first file
     pub fn offset(&self) -> u64 {
         self.offset
     }
+    /// The offset from the start of the gma file. See [`GMAFile::data_offset`]
+    pub fn absolute_offset(&self) -> u64 {
+        self.absolute_offset
+    }
+    /// The last component of the filename. Ex : cl_myscript.lua
+    pub fn file_name(&self) -> &str {
+        path::file_name(&self.filename)
+    }
+    /// The extension of the file name without the leading dot. Ex : lua
+    ///
+    /// Follows the same rules as [`std::path::Path::extension`], names like `.gitignore` have no extension
+    pub fn extension(&self) -> Option<&str> {
+        path::extension(&self.filename)
+    }
+    /// The directory containing this entry. Ex : lua/autorun
+    ///
+    /// Returns None for entries at the root of the archive
+    pub fn parent(&self) -> Option<&str> {
+        path::parent(&self.filename)
+    }
+    /// An iterator over the slash separated components of the filename. Ex : lua, autorun, cl_myscript.lua
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        path::components(&self.filename)
+    }
+}
+
+/// An entry of the file table as it is parsed, given to [`LoadOptions::entry_filter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEntryHeader<'a> {
+    filename: &'a str,
+    size: u64,
+    crc: u32,
+    file_number: u32,
+    table_index: usize,
+}
+
+impl<'a> FileEntryHeader<'a> {
+    /// The full filename of the entry. Ex : lua/autorun/cl_myscript.lua
+    pub fn filename(&self) -> &'a str {
+        self.filename
+    }
+    /// The file size
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The crc32 of the entry's contents
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+    /// The file number stored in the file table, see [`FileEntry::file_number`]
+    pub fn file_number(&self) -> u32 {
+        self.file_number
+    }
+    /// The position of the entry in the file table, counting the entries that were filtered out
+    pub fn table_index(&self) -> usize {
+        self.table_index
+    }
+}
+
+// Filename helpers shared by FileEntry and EntryView
+pub(crate) mod path {
+    pub fn file_name(filename: &str) -> &str {
+        match filename.rfind('/') {
+            Some(index) => &filename[index + 1..],
+            None => filename,
+        }
+    }
+
+    pub fn extension(filename: &str) -> Option<&str> {
+        let file_name = file_name(filename);
+        match file_name.rfind('.') {
+            Some(0) | None => None,
+            Some(index) => Some(&file_name[index + 1..]),
+        }
+    }
+
+    pub fn parent(filename: &str) -> Option<&str> {
+        filename.rfind('/').map(|index| &filename[..index])
+    }
+
+    pub fn components(filename: &str) -> impl Iterator<Item = &str> {
+        filename.split('/').filter(|c| !c.is_empty())
+    }
 }
 
 #[derive(Debug)]
@@ -42,6 +169,8 @@ where
     R: BufRead + Seek,
 {
     Compressed((R, Cursor<Vec<u8>>)),
+    // decompressed into a temporary file, see LoadOptions::spill_to_disk
+    Spilled((R, BufReader<TempFile>)),
     Uncompressed(R),
 }
 impl<R> Read for StreamType<R>
@@ -51,6 +180,7 @@ where
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         match self {
             Self::Compressed((_, r)) => r.read(buf),
+            Self::Spilled((_, r)) => r.read(buf),
             Self::Uncompressed(r) => r.read(buf),
         }
     }
@@ -62,12 +192,14 @@ where
     fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
         match self {
             Self::Compressed((_, r)) => r.fill_buf(),
+            Self::Spilled((_, r)) => r.fill_buf(),
             Self::Uncompressed(r) => r.fill_buf(),
         }
     }
     fn consume(&mut self, amt: usize) {
         match self {
             Self::Compressed((_, r)) => r.consume(amt),
+            Self::Spilled((_, r)) => r.consume(amt),
             Self::Uncompressed(r) => r.consume(amt),
         }
     }
@@ -79,28 +211,165 @@ where
     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
         match self {
             Self::Compressed((_, r)) => r.seek(pos),
+            Self::Spilled((_, r)) => r.seek(pos),
             Self::Uncompressed(r) => r.seek(pos),
         }
     }
 }
 
+// The reader of an archive, reads on other threads wait for their turn while a read that starts
+// before the current one finished on the same thread fails, it would never get the lock
+#[derive(Debug)]
+struct ReaderLock<R: BufRead + Seek> {
+    stream: Mutex<StreamType<R>>,
+    owner: Mutex<Option<ThreadId>>,
+}
+impl<R> ReaderLock<R>
+where
+    R: BufRead + Seek,
+{
+    fn new(stream: StreamType<R>) -> Self {
+        Self {
+            stream: Mutex::new(stream),
+            owner: Mutex::new(None),
+        }
+    }
+    fn lock(&self) -> Result<ReaderGuard<'_, R>> {
+        let current = thread::current().id();
+        if *lock_owner(&self.owner) == Some(current) {
+            return Err(Error::ReaderInUse);
+        }
+        // every read seeks before using the stream so a poisoned one is still usable
+        let stream = self
+            .stream
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *lock_owner(&self.owner) = Some(current);
+        Ok(ReaderGuard {
+            stream,
+            owner: &self.owner,
+        })
+    }
+    fn into_inner(self) -> StreamType<R> {
+        self.stream
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// only ever holds a thread id, a poisoned lock is still usable
+fn lock_owner(owner: &Mutex<Option<ThreadId>>) -> MutexGuard<'_, Option<ThreadId>> {
+    owner
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct ReaderGuard<'a, R: BufRead + Seek> {
+    stream: MutexGuard<'a, StreamType<R>>,
+    owner: &'a Mutex<Option<ThreadId>>,
+}
+impl<R> Drop for ReaderGuard<'_, R>
+where
+    R: BufRead + Seek,
+{
+    fn drop(&mut self) {
+        *lock_owner(self.owner) = None;
+    }
+}
+impl<R> std::ops::Deref for ReaderGuard<'_, R>
+where
+    R: BufRead + Seek,
+{
+    type Target = StreamType<R>;
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+impl<R> std::ops::DerefMut for ReaderGuard<'_, R>
+where
+    R: BufRead + Seek,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+// A locked stream that can be returned and read without borrowing the guard
+struct LockedStream<'a, R: BufRead + Seek>(ReaderGuard<'a, R>);
+impl<R> Read for LockedStream<'_, R>
+where
+    R: BufRead + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+impl<R> BufRead for LockedStream<'_, R>
+where
+    R: BufRead + Seek,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+// Compares filenames ignoring case like the game does, not only ascii
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+// the cache is never left in an inconsistent state so a poisoned lock is still usable
+fn lock_cache(cache: &Mutex<EntryCache>) -> MutexGuard<'_, EntryCache> {
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// GMA File
 #[derive(Debug)]
 pub struct GMAFile<ReaderType>
 where
     ReaderType: BufRead + Seek,
 {
-    version: u8,
+    version: GmaVersion,
     steamid: u64,
     timestamp: u64,
     name: String,
     description: String,
     addon_type: Option<AddonType>,
     addon_tags: Vec<AddonTag>,
+    addon_type_raw: Option<String>,
+    addon_tags_raw: Vec<String>,
+    metadata_json: String,
     author: String,
-    entries: Vec<FileEntry>,
+    addon_version: u32,
+    // always set unless the archive is compact, then it is built on first use
+    entries: OnceLock<Vec<FileEntry>>,
+    compact: Option<CompactEntries>,
+    header_start: u64,
+    entry_table_start: u64,
     file_data_start: u64,
-    reader: RefCell<Option<StreamType<ReaderType>>>,
+    // the size of the contents of every entry in the table, including the ones entry_filter dropped
+    data_size: u64,
+    // the length of the stream when loaded, entries of lenient archives can claim to go past it
+    stream_end: u64,
+    compression: Compression,
+    decompressed_size: Option<u64>,
+    trace: Option<ParseTrace>,
+    cache: Option<Mutex<EntryCache>>,
+    ignore_case: bool,
+    verify_crc: bool,
+    archive_crc: Option<u32>,
+    // the buffer behind the reader of archives loaded with load_shared, read without locking
+    shared: Option<Arc<[u8]>>,
+    // the source of archives loaded with load_concurrent, read without locking
+    concurrent: Option<ConcurrentSource>,
+    reader: ReaderLock<ReaderType>,
 }
 
 impl<ReaderType> GMAFile<ReaderType>
@@ -108,7 +377,7 @@ where
     ReaderType: BufRead + Seek,
 {
     /// Get the gma archive versiom
-    pub fn version(&self) -> u8 {
+    pub fn version(&self) -> GmaVersion {
         self.version
     }
     /// The appid. This is always '4000', the appid of garry's mod
@@ -116,13 +385,25 @@ where
         4000 // this is the gmod appid
     }
     /// The author's steamid. This is currently unused by the game and is usually hardcoded to 0
-    pub fn author_steamid(&self) -> u64 {
-        self.steamid
+    pub fn author_steamid(&self) -> SteamID {
+        SteamID::new(self.steamid)
     }
     /// The seconds since UNIX epoch from when the file was created
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+    /// The time the file was created, from [`GMAFile::timestamp`].
+    /// None if the timestamp is too large to be represented
+    pub fn created_at(&self) -> Option<SystemTime> {
+        UNIX_EPOCH.checked_add(Duration::from_secs(self.timestamp))
+    }
+    /// The time the file was created as a utc date, None if it is out of chrono's range
+    #[cfg(feature = "chrono")]
+    pub fn created_at_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use std::convert::TryFrom;
+        let seconds = i64::try_from(self.timestamp).ok()?;
+        chrono::DateTime::from_timestamp(seconds, 0)
+    }
     /// The name of the addon
     pub fn name(&self) -> &str {
         &self.name
@@ -139,6 +420,22 @@ where
     pub fn addon_tags(&self) -> &[AddonTag] {
         &self.addon_tags
     }
+    /// The type exactly as stored in the metadata json, even if it is not a known [`AddonType`].
+    ///
+    /// None if the description is not json
+    pub fn addon_type_raw(&self) -> Option<&str> {
+        self.addon_type_raw.as_deref()
+    }
+    /// Every tag exactly as stored in the metadata json, even the ones that are not a known [`AddonTag`]
+    pub fn tags_raw(&self) -> &[String] {
+        &self.addon_tags_raw
+    }
+    /// The metadata string exactly as stored in the header, usually a json object with the
+    /// description, type and tags. Keeps fields and formatting that the parsed accessors don't,
+    /// like extra keys written by sharpGMad, see [`crate::GMABuilder::raw_metadata`]
+    pub fn metadata_json(&self) -> &str {
+        &self.metadata_json
+    }
     /// Helper function to check if this addon contains a certain tag
     pub fn contains_tag(&self, tag: AddonTag) -> bool {
         self.addon_tags.contains(&tag)
@@ -147,21 +444,404 @@ where
     pub fn author(&self) -> &str {
         &self.author
     }
+    /// The addon version stored in the header. This is currently unused by the game and is usually 1
+    pub fn addon_version(&self) -> u32 {
+        self.addon_version
+    }
+    /// The offset from the start of the gma file where the contents of the first entry begin.
+    ///
+    /// For compressed archives this is an offset into the decompressed data
+    pub fn data_offset(&self) -> u64 {
+        self.file_data_start
+    }
+    /// The byte range of the header, from the ident up to and including the addon version.
+    ///
+    /// For compressed archives this is a range of the decompressed data
+    pub fn header_range(&self) -> Range<u64> {
+        self.header_start..self.entry_table_start
+    }
+    /// The byte range of the file table, including its terminator.
+    ///
+    /// For compressed archives this is a range of the decompressed data
+    pub fn entry_table_range(&self) -> Range<u64> {
+        self.entry_table_start..self.file_data_start
+    }
+    /// The exact bytes of the header, see [`GMAFile::header_range`]
+    pub fn raw_header(&self) -> Result<Vec<u8>> {
+        self.read_range(self.header_range())
+    }
+    /// The exact bytes of the file table, see [`GMAFile::entry_table_range`]
+    pub fn raw_entry_table(&self) -> Result<Vec<u8>> {
+        self.read_range(self.entry_table_range())
+    }
+    fn read_range(&self, range: Range<u64>) -> Result<Vec<u8>> {
+        let mut stream = self.lock_reader()?;
+        stream.seek(SeekFrom::Start(range.start))?;
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+        (&mut *stream)
+            .take(range.end - range.start)
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Reads the archive into memory and returns an archive that no longer depends on `ReaderType`,
+    /// so the original reader, like a network stream, can be closed.
+    ///
+    /// Everything from the start of the stream up to the end of the last entry, and the
+    /// [`GMAFile::archive_crc`] if present, is kept, so offsets
+    /// like [`GMAFile::data_offset`] stay the same. Compressed archives are already decompressed in
+    /// memory and only drop the original reader
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap().buffer_all().unwrap();
+    /// let entry = archive.entries().next().unwrap();
+    /// archive.read_entry(entry, |_, _| ()).unwrap();
+    /// ```
+    pub fn buffer_all(self) -> Result<GMAFile<Cursor<Vec<u8>>>> {
+        let data_end = match self.archive_crc {
+            Some(_) => self.archive_end().saturating_add(4),
+            None => self.archive_end(),
+        };
+        let data = match self.reader.into_inner() {
+            StreamType::Compressed((_, decompressed)) => decompressed.into_inner(),
+            StreamType::Spilled((_, mut decompressed)) => {
+                decompressed.seek(SeekFrom::Start(0))?;
+                let mut data = Vec::new();
+                decompressed.read_to_end(&mut data)?;
+                data
+            }
+            StreamType::Uncompressed(mut reader) => {
+                reader.seek(SeekFrom::Start(0))?;
+                let mut data = Vec::new();
+                reader.take(data_end).read_to_end(&mut data)?;
+                if (data.len() as u64) < data_end {
+                    return Err(Error::Truncated {
+                        expected: data_end,
+                        at: data.len() as u64,
+                    });
+                }
+                data
+            }
+        };
+        Ok(GMAFile {
+            version: self.version,
+            steamid: self.steamid,
+            timestamp: self.timestamp,
+            name: self.name,
+            description: self.description,
+            addon_type: self.addon_type,
+            addon_tags: self.addon_tags,
+            addon_type_raw: self.addon_type_raw,
+            addon_tags_raw: self.addon_tags_raw,
+            metadata_json: self.metadata_json,
+            author: self.author,
+            addon_version: self.addon_version,
+            entries: self.entries,
+            compact: self.compact,
+            header_start: self.header_start,
+            entry_table_start: self.entry_table_start,
+            file_data_start: self.file_data_start,
+            data_size: self.data_size,
+            stream_end: data.len() as u64,
+            compression: self.compression,
+            decompressed_size: self.decompressed_size,
+            trace: self.trace,
+            cache: self.cache,
+            ignore_case: self.ignore_case,
+            verify_crc: self.verify_crc,
+            archive_crc: self.archive_crc,
+            shared: None,
+            concurrent: None,
+            reader: ReaderLock::new(StreamType::Uncompressed(Cursor::new(data))),
+        })
+    }
+    /// The crc32 of the whole archive that gmad writes after the contents of the last entry,
+    /// None if the archive ends right after the last entry
+    pub fn archive_crc(&self) -> Option<u32> {
+        self.archive_crc
+    }
+    /// Checks [`GMAFile::archive_crc`] against the crc32 of everything from the ident to the end
+    /// of the last entry. This reads the whole archive.
+    ///
+    /// Fails with [`Error::MissingArchiveCrc`] if there is no stored crc and with
+    /// [`Error::ArchiveCrcMismatch`] if it doesn't match
+    pub fn verify_archive_crc(&self) -> Result<()> {
+        let expected = self.archive_crc.ok_or(Error::MissingArchiveCrc)?;
+        let found = self.contents_crc()?;
+        if found != expected {
+            return Err(Error::ArchiveCrcMismatch { expected, found });
+        }
+        Ok(())
+    }
+    // The crc32 of everything from the ident to the end of the last entry, what gmad appends
+    pub(crate) fn contents_crc(&self) -> Result<u32> {
+        let mut stream = self.lock_reader()?;
+        stream.seek(SeekFrom::Start(self.header_start))?;
+        let archive_len = self.archive_end() - self.header_start;
+        Ok(Crc32Reader::new((&mut *stream).take(archive_len)).finish()?)
+    }
+    // The size of the input as it is stored, before decompressing it
+    pub(crate) fn stored_size(&self) -> Result<u64> {
+        let mut stream = self.lock_reader()?;
+        Ok(match &mut *stream {
+            StreamType::Compressed((source, _)) | StreamType::Spilled((source, _)) => {
+                source.seek(SeekFrom::End(0))?
+            }
+            StreamType::Uncompressed(source) => source
+                .seek(SeekFrom::End(0))?
+                .saturating_sub(self.header_start),
+        })
+    }
+    pub(crate) fn set_shared(&mut self, buffer: Arc<[u8]>) {
+        self.shared = Some(buffer);
+    }
+    pub(crate) fn shared(&self) -> Option<&Arc<[u8]>> {
+        self.shared.as_ref()
+    }
+    pub(crate) fn set_concurrent(&mut self, source: ConcurrentSource) {
+        self.concurrent = Some(source);
+    }
+    pub(crate) fn concurrent(&self) -> Option<&ConcurrentSource> {
+        self.concurrent.as_ref()
+    }
+    pub(crate) fn verifies_crc(&self) -> bool {
+        self.verify_crc
+    }
+    // Waits for reads on other threads, fails with ReaderInUse if a read on this thread is
+    // still using the reader
+    fn lock_reader(&self) -> Result<ReaderGuard<'_, ReaderType>> {
+        self.reader.lock()
+    }
+    // The length of the underlying stream, decompressed for compressed archives
+    pub(crate) fn stream_len(&self) -> Result<u64> {
+        let mut stream = self.lock_reader()?;
+        Ok(stream.seek(SeekFrom::End(0))?)
+    }
+    // How many bytes to reserve for the contents of an entry, never more than the stream has left
+    pub(crate) fn contents_capacity(&self, entry: &FileEntry) -> usize {
+        let start = self.file_data_start.saturating_add(entry.offset);
+        entry.filesize.min(self.stream_end.saturating_sub(start)) as usize
+    }
+    // Where the contents of `entry` start in the stream. The offsets of lenient archives are
+    // saturated, entries after one with an absurd size can start past what a u64 can hold
+    pub(crate) fn entry_start(&self, entry: &FileEntry) -> Result<u64> {
+        self.file_data_start
+            .checked_add(entry.offset)
+            .ok_or(Error::Truncated {
+                expected: u64::MAX,
+                at: self.stream_end,
+            })
+    }
+    // The end of the contents of the last entry, also the end of the archive unless there is a crc
+    pub(crate) fn archive_end(&self) -> u64 {
+        self.file_data_start.saturating_add(self.data_size)
+    }
     /// Returns true if the input file was compressed, false otherwise
     pub fn compressed(&self) -> bool {
-        match self
-            .reader
-            .borrow()
+        self.compression != Compression::None
+    }
+    /// The compression format of the input file
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+    /// The size in bytes of the decompressed archive, None if the input wasn't compressed.
+    /// See [`crate::probe_decompressed_size`] to get it before loading
+    pub fn decompressed_size(&self) -> Option<u64> {
+        self.decompressed_size
+    }
+    /// The fields read while loading this archive, only recorded if [`LoadOptions::trace`] was enabled
+    pub fn parse_trace(&self) -> Option<&ParseTrace> {
+        self.trace.as_ref()
+    }
+    /// An estimate in bytes of the heap and inline memory held by this archive handle.
+    ///
+    /// Counts the header strings, the entry table, the parse trace, the entry cache and, for
+    /// compressed archives, the buffer holding the decompressed archive. The memory used by `ReaderType` itself
+    /// (like a [`std::io::BufReader`]'s buffer) is not known and only its inline size is counted.
+    /// Indexes built from the archive are separate, see [`crate::NameIndex::approx_memory_usage`]
+    /// Filenames shared through [`LoadOptions::intern_names`] are counted in every archive using them.
+    pub fn approx_memory_usage(&self) -> usize {
+        let strings = self.name.capacity()
+            + self.description.capacity()
+            + self.author.capacity()
+            + self.metadata_json.capacity()
+            + self.addon_type_raw.as_ref().map_or(0, String::capacity)
+            + self.addon_tags_raw.capacity() * std::mem::size_of::<String>()
+            + self
+                .addon_tags_raw
+                .iter()
+                .map(String::capacity)
+                .sum::<usize>()
+            + self.addon_tags.capacity() * std::mem::size_of::<AddonTag>();
+        let entries = self.entries.get().map_or(0, |entries| {
+            entries.capacity() * std::mem::size_of::<FileEntry>()
+                // the filename allocation also holds the two reference counts
+                + entries
+                    .iter()
+                    .map(|e| 2 * std::mem::size_of::<usize>() + e.filename.len())
+                    .sum::<usize>()
+        }) + self
+            .compact
+            .as_ref()
+            .map_or(0, CompactEntries::approx_memory_usage);
+        let trace = self.trace.as_ref().map_or(0, |trace| {
+            trace.fields.capacity() * std::mem::size_of::<TraceField>()
+                + trace
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        f.name.capacity()
+                            + f.raw.capacity()
+                            + f.value.as_ref().map_or(0, String::capacity)
+                    })
+                    .sum::<usize>()
+        });
+        let buffer = match self.reader.stream.try_lock() {
+            Ok(stream) => match &*stream {
+                StreamType::Compressed((_, buffer)) => buffer.get_ref().capacity(),
+                StreamType::Spilled(_) | StreamType::Uncompressed(_) => 0,
+            },
+            Err(_) => 0,
+        };
+        let cache = self
+            .cache
             .as_ref()
-            .expect("The reader should not be None, this is a bug")
-        {
-            StreamType::Compressed(_) => true,
-            StreamType::Uncompressed(_) => false,
+            .map_or(0, |cache| lock_cache(cache).size());
+        std::mem::size_of::<Self>() + strings + entries + trace + buffer + cache
+    }
+    /// An iterator of the file entries of this archive.
+    ///
+    /// Entries are always yielded in the order they are stored in the file table, which is also
+    /// the order of their contents in the file. The position of each entry is [`FileEntry::index`]
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &FileEntry> + DoubleEndedIterator {
+        self.entry_list().iter()
+    }
+    /// Same as [`GMAFile::entries`] but yields borrowed [`EntryView`]s.
+    ///
+    /// For archives loaded with [`LoadOptions::compact`] this reads the compact table directly,
+    /// while the accessors that return a `&FileEntry` build one for every entry the first time
+    /// they are used
+    pub fn entry_views(
+        &self,
+    ) -> impl ExactSizeIterator<Item = EntryView<'_>> + DoubleEndedIterator {
+        (0..self.entry_count()).map(move |index| match &self.compact {
+            Some(compact) => compact.view(index, self.file_data_start),
+            None => EntryView::from(&self.entry_list()[index]),
+        })
+    }
+    /// The number of entries, without building a [`FileEntry`] per entry for compact archives
+    pub fn entry_count(&self) -> usize {
+        match &self.compact {
+            Some(compact) => compact.len(),
+            None => self.entry_list().len(),
+        }
+    }
+    /// True if the archive has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entry_count() == 0
+    }
+    fn entry_list(&self) -> &[FileEntry] {
+        self.entries.get_or_init(|| {
+            let compact = match &self.compact {
+                Some(compact) => compact,
+                None => return Vec::new(),
+            };
+            (0..compact.len())
+                .map(|index| compact.view(index, self.file_data_start).to_entry())
+                .collect()
+        })
+    }
+    /// The first entry with the given filename, compared exactly unless
+    /// [`LoadOptions::case_insensitive_lookups`] was enabled
+    pub fn get(&self, filename: &str) -> Option<&FileEntry> {
+        if self.ignore_case {
+            return self.entry_ignore_case(filename);
+        }
+        self.entry_list()
+            .iter()
+            .find(|entry| &*entry.filename == filename)
+    }
+    /// The first entry whose filename matches `filename` ignoring case, like the game does.
+    /// Ex : `LUA/Autorun/x.lua` finds `lua/autorun/x.lua`.
+    ///
+    /// This is a linear search, see [`GMAFile::build_name_index`] for many lookups
+    pub fn entry_ignore_case(&self, filename: &str) -> Option<&FileEntry> {
+        self.entry_list()
+            .iter()
+            .find(|entry| eq_ignore_case(&entry.filename, filename))
+    }
+    /// Same as [`GMAFile::get`] but returns an [`EntryView`], which for archives loaded with
+    /// [`LoadOptions::compact`] is read from the compact table. Read it with
+    /// [`EntryView::to_entry`]
+    pub fn get_view(&self, filename: &str) -> Option<EntryView<'_>> {
+        let mut views = self.entry_views();
+        match self.ignore_case {
+            true => views.find(|view| eq_ignore_case(view.filename(), filename)),
+            false => views.find(|view| view.filename() == filename),
         }
     }
-    /// An iterator of the file entries of this archive
-    pub fn entries(&self) -> impl Iterator<Item = &FileEntry> {
-        self.entries.iter()
+    /// The entries whose extension is any of `extensions`, ignoring case and a leading dot.
+    /// Ex : `&["lua", "vmt"]`
+    pub fn entries_with_extension<'a>(
+        &'a self,
+        extensions: &'a [&str],
+    ) -> impl Iterator<Item = &'a FileEntry> + 'a {
+        self.entry_list().iter().filter(move |entry| {
+            entry.extension().is_some_and(|ext| {
+                extensions
+                    .iter()
+                    .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            })
+        })
+    }
+    /// The entries inside the directory `directory` or any of its subdirectories, ignoring the
+    /// case of ascii letters. Ex : `materials/` or `materials`, which don't match
+    /// `materials_extra/a.vmt`
+    pub fn entries_under<'a>(
+        &'a self,
+        directory: &str,
+    ) -> impl Iterator<Item = &'a FileEntry> + 'a {
+        let mut prefix = directory.trim_matches('/').to_owned();
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        self.entry_list().iter().filter(move |entry| {
+            entry
+                .filename
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(&prefix))
+        })
+    }
+    /// The file entries sorted by filename. Entries with the same filename keep their on-disk order
+    pub fn entries_sorted_by_name(&self) -> Vec<&FileEntry> {
+        let mut entries: Vec<&FileEntry> = self.entry_list().iter().collect();
+        entries.sort_by(|a, b| a.filename.cmp(&b.filename));
+        entries
+    }
+    /// Groups of entries that share the same filename, compared case-insensitively.
+    /// Entries with a unique filename are not part of any group
+    pub fn duplicate_entries(&self) -> Vec<Vec<&FileEntry>> {
+        let entries = self.entry_list();
+        self.duplicate_views()
+            .into_iter()
+            .map(|group| group.iter().map(|view| &entries[view.index()]).collect())
+            .collect()
+    }
+    // The groups of duplicate_entries, without building the entries of compact archives
+    pub(crate) fn duplicate_views(&self) -> Vec<Vec<EntryView<'_>>> {
+        let mut groups: Vec<Vec<EntryView>> = Vec::new();
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        for view in self.entry_views() {
+            let key = view.filename().to_lowercase();
+            match group_index.get(&key) {
+                Some(&index) => groups[index].push(view),
+                None => {
+                    group_index.insert(key, groups.len());
+                    groups.push(vec![view]);
+                }
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
     }
     /// Function to read the contents of a given entry.
     ///
@@ -179,21 +859,250 @@ where
     ///     }).unwrap();
     ///     // do something with contents
     /// }
+    /// ```
+    ///
+    /// Errors leave the archive usable. Calling this again from inside `func` returns
+    /// [`Error::ReaderInUse`] since there is only one underlying reader, calls from other threads
+    /// wait until `func` returns. Archives loaded with [`crate::load_shared`] can be read from many
+    /// threads at once with [`GMAFile::entry_bytes`].
+    ///
+    /// Small entries can be kept in memory between calls, see [`LoadOptions::entry_cache`].
+    /// With [`LoadOptions::verify_crc`] this behaves like [`GMAFile::read_entry_verified`]
     pub fn read_entry<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
     where
         F: FnOnce(&FileEntry, &mut dyn Read) -> R,
     {
-        //this doesnt look good
-        let mut stream = self.reader.replace(None).unwrap();
-        //TODO: if there is a problem with seek we lose the reader
-        stream.seek(std::io::SeekFrom::Start(
-            self.file_data_start + entry.offset,
-        ))?;
-        let mut entry_reader = (&mut stream).take(entry.filesize);
-        let result = func(entry, &mut entry_reader);
-        self.reader.replace(Some(stream));
+        match self.verify_crc {
+            true => self.read_entry_verified(entry, func),
+            false => self.read_entry_unverified(entry, func),
+        }
+    }
+    fn read_entry_unverified<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut dyn Read) -> R,
+    {
+        if let Some(cache) = self.cache.as_ref() {
+            if lock_cache(cache).accepts(entry.filesize) {
+                let cached = lock_cache(cache).get(entry.index);
+                let contents = match cached {
+                    Some(contents) => contents,
+                    None => {
+                        let contents: Arc<[u8]> = self
+                            .read_entry_uncached(entry, |_, reader| {
+                                let mut contents =
+                                    Vec::with_capacity(self.contents_capacity(entry));
+                                reader.read_to_end(&mut contents).map(|_| contents)
+                            })??
+                            .into();
+                        lock_cache(cache).insert(entry.index, contents.clone());
+                        contents
+                    }
+                };
+                return Ok(func(entry, &mut &contents[..]));
+            }
+        }
+        self.read_entry_uncached(entry, func)
+    }
+    fn read_entry_uncached<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut dyn Read) -> R,
+    {
+        let start = self.entry_start(entry)?;
+        let mut stream = self.lock_reader()?;
+        stream.seek(std::io::SeekFrom::Start(start))?;
+        let mut entry_reader = (&mut *stream).take(entry.filesize);
+        Ok(func(entry, &mut entry_reader))
+    }
+    /// Same as [`GMAFile::read_entry`] but `func` returns a [`Result`] which is propagated,
+    /// so io errors inside it can use `?` instead of nesting results
+    /// ```
+    /// use std::io::Read;
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(&dummy_buffer).unwrap();
+    /// let entry = archive.entries().next().unwrap();
+    /// let contents = archive.try_read_entry(entry, |_, reader| {
+    ///     let mut c = String::new();
+    ///     reader.read_to_string(&mut c)?;
+    ///     Ok(c)
+    /// }).unwrap();
+    /// ```
+    pub fn try_read_entry<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut dyn Read) -> Result<R>,
+    {
+        self.read_entry(entry, func)?
+    }
+    /// Same as [`GMAFile::read_entry`] but the reader also implements [`Seek`], with positions
+    /// relative to the start of the entry. Useful to parse formats that jump around like bsp or mdl
+    /// without buffering the whole entry
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(&dummy_buffer).unwrap();
+    /// let entry = archive.entries().next().unwrap();
+    /// let last_byte = archive.read_entry_seekable(entry, |_, reader| {
+    ///     reader.seek(SeekFrom::End(-1)).unwrap();
+    ///     let mut byte = [0u8];
+    ///     reader.read_exact(&mut byte).unwrap();
+    ///     byte[0]
+    /// }).unwrap();
+    /// ```
+    pub fn read_entry_seekable<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut EntryReader) -> R,
+    {
+        let start = self.entry_start(entry)?;
+        let mut stream = self.lock_reader()?;
+        stream.seek(SeekFrom::Start(start))?;
+        let mut entry_reader = EntryReader::new(&mut *stream, start, entry.filesize);
+        Ok(func(entry, &mut entry_reader))
+    }
+    // The reader positioned at the start of `entry` and limited to its contents, the reader stays
+    // locked until the returned value is dropped
+    pub(crate) fn lock_entry(&self, entry: &FileEntry) -> Result<impl BufRead + '_> {
+        let start = self.entry_start(entry)?;
+        let mut stream = self.lock_reader()?;
+        stream.seek(SeekFrom::Start(start))?;
+        Ok(LockedStream(stream).take(entry.filesize))
+    }
+    /// Streams the contents of an entry into `writer` and returns the amount of bytes written
+    pub fn read_entry_to_writer<W: Write + ?Sized>(
+        &self,
+        entry: &FileEntry,
+        writer: &mut W,
+    ) -> Result<u64> {
+        self.try_read_entry(entry, |_, reader| Ok(std::io::copy(reader, writer)?))
+    }
+    /// Detects the kind of content of an entry by looking at its first bytes,
+    /// regardless of the extension in its filename
+    pub fn sniff(&self, entry: &FileEntry) -> Result<ContentKind> {
+        let magic = self.try_read_entry(entry, |_, reader| {
+            let mut magic = Vec::with_capacity(ContentKind::MAGIC_LEN);
+            reader
+                .take(ContentKind::MAGIC_LEN as u64)
+                .read_to_end(&mut magic)?;
+            Ok(magic)
+        })?;
+        Ok(ContentKind::from_magic(&magic))
+    }
+    /// Same as [`GMAFile::read_entry`] but the contents are checked against the entry's crc32.
+    ///
+    /// The bytes are hashed as the callback reads them, anything the callback leaves unread is
+    /// hashed afterwards. If the checksum does not match [`Error::CrcMismatch`] is returned
+    /// instead of the callback's result.
+    pub fn read_entry_verified<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut dyn Read) -> R,
+    {
+        let (result, crc) = self.read_entry_unverified(entry, |entry, reader| {
+            let mut crc_reader = Crc32Reader::new(reader);
+            let result = func(entry, &mut crc_reader);
+            (result, crc_reader.finish())
+        })?;
+        let crc = crc?;
+        if crc != entry.crc {
+            return Err(Error::CrcMismatch {
+                filename: entry.filename.to_string(),
+                expected: entry.crc,
+                found: crc,
+            });
+        }
         Ok(result)
     }
+    /// Reads the contents of many entries in a single pass, returned by [`FileEntry::index`].
+    ///
+    /// The entries are read in the order they are stored in, small gaps between them are read
+    /// through instead of seeking, so this is much faster than calling [`GMAFile::read_entry`]
+    /// for each one when they are scattered across a large archive. The entry cache is not used.
+    /// With [`LoadOptions::verify_crc`] every entry is checked and the first mismatch fails
+    /// with [`Error::CrcMismatch`]
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// let lua = archive.entries_with_extension(&["lua"]);
+    /// let contents = archive.read_entries(lua).unwrap();
+    /// for (index, contents) in contents {
+    ///     println!("{} : {} bytes", index, contents.len());
+    /// }
+    /// ```
+    pub fn read_entries<'a, I>(&self, entries: I) -> Result<HashMap<usize, Vec<u8>>>
+    where
+        I: IntoIterator<Item = &'a FileEntry>,
+    {
+        // gaps up to this size are cheaper to read through than to seek over, which would drop
+        // the buffer of a BufReader
+        const MAX_SKIP: u64 = 64 * 1024;
+
+        let mut entries: Vec<&FileEntry> = entries.into_iter().collect();
+        entries.sort_by_key(|entry| (entry.offset, entry.index));
+        entries.dedup_by_key(|entry| entry.index);
+        let mut stream = self.lock_reader()?;
+        let mut position = None;
+        let mut contents = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let start = self
+                .entry_start(entry)
+                .map_err(|e| e.in_entry(&entry.filename))?;
+            let in_entry = |e: std::io::Error| Error::from(e).in_entry(&entry.filename);
+            match position {
+                Some(position) if start >= position && start - position <= MAX_SKIP => {
+                    std::io::copy(
+                        &mut (&mut *stream).take(start - position),
+                        &mut std::io::sink(),
+                    )
+                    .map_err(in_entry)?;
+                }
+                _ => {
+                    stream.seek(SeekFrom::Start(start)).map_err(in_entry)?;
+                }
+            }
+            let mut data = Vec::with_capacity(self.contents_capacity(entry));
+            (&mut *stream)
+                .take(entry.filesize)
+                .read_to_end(&mut data)
+                .map_err(in_entry)?;
+            position = Some(start + data.len() as u64);
+            if self.verify_crc {
+                let mut hasher = Crc32Hasher::new();
+                hasher.update(&data);
+                let crc = hasher.finalize();
+                if crc != entry.crc {
+                    return Err(Error::CrcMismatch {
+                        filename: entry.filename.to_string(),
+                        expected: entry.crc,
+                        found: crc,
+                    });
+                }
+            }
+            contents.insert(entry.index, data);
+        }
+        Ok(contents)
+    }
+}
+
+impl<'a, ReaderType> IntoIterator for &'a GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    type Item = &'a FileEntry;
+    type IntoIter = std::slice::Iter<'a, FileEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entry_list().iter()
+    }
+}
+
+impl<ReaderType> Index<&str> for GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    type Output = FileEntry;
+
+    /// Panics if there is no entry with the given filename, see [`GMAFile::get`]
+    fn index(&self, filename: &str) -> &FileEntry {
+        self.get(filename)
+            .unwrap_or_else(|| panic!("no entry with the filename '{}'", filename))
+    }
 }
 
 pub struct GMAFileReader<ReaderType>
@@ -201,25 +1110,59 @@ where
     ReaderType: BufRead + Seek,
 {
     reader: StreamType<ReaderType>,
+    compression: Compression,
+    options: LoadOptions,
+    trace: Option<ParseTrace>,
+    // position of `reader`, tracked to avoid asking the reader for it on every field
+    position: u64,
 }
 
 impl<ReaderType> GMAFileReader<ReaderType>
 where
     ReaderType: BufRead + Seek,
 {
-    pub fn new(reader: ReaderType) -> Result<Self> {
+    pub fn new(reader: ReaderType, options: LoadOptions) -> Result<Self> {
+        let max_size = options.max_decompressed_size.unwrap_or(u64::MAX);
+        let (reader, compression) = get_reader_stream(reader, max_size, options.spill_threshold)?;
+        Self::from_stream(reader, compression, options)
+    }
+
+    // `reader` holds the uncompressed archive even if `compression` is not None
+    pub fn with_compression(
+        reader: ReaderType,
+        compression: Compression,
+        options: LoadOptions,
+    ) -> Result<Self> {
+        Self::from_stream(StreamType::Uncompressed(reader), compression, options)
+    }
+
+    fn from_stream(
+        mut reader: StreamType<ReaderType>,
+        compression: Compression,
+        options: LoadOptions,
+    ) -> Result<Self> {
+        let position = reader.stream_position()?;
+        let trace = match options.trace {
+            true => Some(ParseTrace::default()),
+            false => None,
+        };
         Ok(Self {
-            reader: get_reader_stream(reader)?,
+            reader,
+            compression,
+            options,
+            trace,
+            position,
         })
     }
 
     pub fn read_gma(mut self) -> Result<GMAFile<ReaderType>> {
+        let header_start = self.position;
         self.read_ident()?;
-        let version = self.read_version()?;
+        let version = GmaVersion::from(self.read_version()?);
         let steamid = self.read_steamid()?;
         let timestamp = self.read_timestamp()?;
 
-        if version > 1 {
+        if version.has_required_content() {
             //unused right now
             self.read_required_content()?;
         }
@@ -228,10 +1171,41 @@ where
         let metadata_str = self.read_desc()?;
         let author = self.read_author()?;
 
-        let _addon_version = self.read_addon_version()?;
-        let entries = self.read_file_entries()?;
-        let file_data_start = self.reader.seek(SeekFrom::Current(0))?;
+        let addon_version = self.read_addon_version()?;
+        let entry_table_start = self.position;
+        let (mut entries, compact, data_size) =
+            self.read_file_entries().map_err(|e| self.with_trace(e))?;
+        let file_data_start = self.position;
+        for entry in entries.iter_mut() {
+            entry.absolute_offset = file_data_start.saturating_add(entry.offset);
+        }
+        // saturating so that absurd sizes end up as a truncated error instead of overflowing
+        let data_end = file_data_start.saturating_add(data_size);
+        let len = self
+            .reader
+            .seek(SeekFrom::End(0))
+            .map_err(|e| self.with_trace(e.into()))?;
+        if len < data_end && !self.options.lenient {
+            return Err(self.with_trace(Error::Truncated {
+                expected: data_end,
+                at: len,
+            }));
+        }
+        let archive_crc = match len.saturating_sub(data_end) >= 4 {
+            true => {
+                self.reader
+                    .seek(SeekFrom::Start(data_end))
+                    .map_err(|e| self.with_trace(e.into()))?;
+                self.position = data_end;
+                Some(self.read_u32(format_args!("archive_crc"))?)
+            }
+            false => None,
+        };
+        let mut addon_type_raw = None;
+        let mut addon_tags_raw = Vec::new();
         let (desc, ty, tags) = if let Some(metadata) = AddonMetadata::from_json(&metadata_str) {
+            addon_type_raw = Some(metadata.get_type_raw().to_owned());
+            addon_tags_raw = metadata.get_tags_raw().to_vec();
             let ty = metadata.get_type();
             let mut tags = Vec::new();
             let (t1, t2) = metadata.get_tags();
@@ -245,7 +1219,7 @@ where
 
             (desc, ty, tags)
         } else {
-            (metadata_str, None, Vec::new())
+            (metadata_str.clone(), None, Vec::new())
         };
 
         Ok(GMAFile {
@@ -256,105 +1230,305 @@ where
             description: desc,
             addon_type: ty,
             addon_tags: tags,
+            addon_type_raw,
+            addon_tags_raw,
+            metadata_json: metadata_str,
             author,
-            entries,
-            file_data_start: file_data_start as u64,
-            reader: RefCell::new(Some(self.reader)),
+            addon_version,
+            entries: match compact {
+                Some(_) => OnceLock::new(),
+                None => OnceLock::from(entries),
+            },
+            compact,
+            header_start,
+            entry_table_start,
+            file_data_start,
+            data_size,
+            stream_end: len,
+            compression: self.compression,
+            decompressed_size: match self.compression {
+                Compression::None => None,
+                _ => Some(len),
+            },
+            trace: self.trace,
+            cache: self.options.entry_cache.map(|(max_entry_size, capacity)| {
+                Mutex::new(EntryCache::new(max_entry_size, capacity))
+            }),
+            ignore_case: self.options.ignore_case,
+            verify_crc: self.options.verify_crc,
+            archive_crc,
+            shared: None,
+            concurrent: None,
+            reader: ReaderLock::new(self.reader),
         })
     }
 
-    fn read_ident(&mut self) -> Result<()> {
-        let mut ident: [u8; 4] = [0; 4];
-        self.reader.read_exact(&mut ident)?;
-        if ident != IDENT {
-            Err(Error::InvalidIdent)
-        } else {
-            Ok(())
+    // Runs `read` and, if tracing is enabled, records the field with the bytes it consumed.
+    // Running out of data becomes Error::Truncated, assuming the field needed at least `min_size`
+    // bytes. Errors are wrapped in Error::Traced together with the trace so far
+    fn traced<T, F>(&mut self, name: Arguments, min_size: u64, read: F) -> Result<T>
+    where
+        T: Debug,
+        F: FnOnce(&mut StreamType<ReaderType>) -> Result<(usize, T)>,
+    {
+        let offset = self.position;
+        let result = match read(&mut self.reader) {
+            Ok((n, value)) => {
+                self.position += n as u64;
+                Ok(value)
+            }
+            Err(error) => Err(self.check_truncated(error, offset.saturating_add(min_size))),
+        };
+        let mut trace = match self.trace.take() {
+            Some(trace) => trace,
+            None => return result,
+        };
+        trace.fields.push(TraceField {
+            name: name.to_string(),
+            offset,
+            raw: self.raw_since(offset),
+            value: result.as_ref().ok().map(|value| format!("{:?}", value)),
+        });
+        match result {
+            Ok(value) => {
+                self.trace = Some(trace);
+                Ok(value)
+            }
+            Err(error) => Err(Error::Traced {
+                error: Box::new(error),
+                trace,
+            }),
         }
     }
 
-    fn read_version(&mut self) -> Result<u8> {
-        let version = self.reader.read_u8()?.1;
-        if !VALID_VERSIONS.contains(&version) {
-            Err(Error::InvalidVersion(version))
-        } else {
-            Ok(version)
+    // Wraps errors that are not about a single field, the ones traced doesn't already wrap, in
+    // Error::Traced together with the trace so far
+    fn with_trace(&mut self, error: Error) -> Error {
+        match (error, self.trace.take()) {
+            (error @ Error::Traced { .. }, _) | (error, None) => error,
+            (error, Some(trace)) => Error::Traced {
+                error: Box::new(error),
+                trace,
+            },
+        }
+    }
+
+    fn check_truncated(&mut self, error: Error, expected: u64) -> Error {
+        match &error {
+            Error::IOError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                match self.reader.seek(SeekFrom::End(0)) {
+                    Ok(at) => Error::Truncated {
+                        expected: expected.max(at.saturating_add(1)),
+                        at,
+                    },
+                    Err(_) => error,
+                }
+            }
+            _ => error,
+        }
+    }
+
+    // Re-reads the bytes between `offset` and the current position, best effort since this
+    // also runs after failed reads
+    fn raw_since(&mut self, offset: u64) -> Vec<u8> {
+        let mut raw = Vec::new();
+        if let Ok(end) = self.reader.stream_position() {
+            if end > offset && self.reader.seek(SeekFrom::Start(offset)).is_ok() {
+                let _ = Read::take(&mut self.reader, end - offset).read_to_end(&mut raw);
+            }
         }
+        raw
+    }
+
+    fn read_string(&mut self, name: Arguments) -> Result<String> {
+        let max_len = self.options.max_string_length;
+        let lenient = self.options.lenient;
+        self.traced(name, 1, |r| match r.read_c_string(max_len) {
+            // the string was read up to its terminator, only the invalid bytes are replaced
+            Err(binary::Error::InvalidUTF8(e)) if lenient => {
+                let bytes = e.into_bytes();
+                let string = String::from_utf8_lossy(&bytes).into_owned();
+                Ok((bytes.len() + 1, string))
+            }
+            result => Ok(result?),
+        })
+    }
+
+    fn read_u32(&mut self, name: Arguments) -> Result<u32> {
+        self.traced(name, 4, |r| Ok(r.read_u32()?))
+    }
+
+    fn read_u64(&mut self, name: Arguments) -> Result<u64> {
+        self.traced(name, 8, |r| Ok(r.read_u64()?))
+    }
+
+    fn read_ident(&mut self) -> Result<()> {
+        self.traced(format_args!("ident"), 4, |r| {
+            let mut ident: [u8; 4] = [0; 4];
+            r.read_exact(&mut ident)?;
+            if ident != IDENT {
+                Err(Error::InvalidIdent)
+            } else {
+                Ok((ident.len(), ()))
+            }
+        })
+    }
+
+    fn read_version(&mut self) -> Result<u8> {
+        let lenient = self.options.lenient;
+        self.traced(format_args!("version"), 1, |r| {
+            let (n, version) = r.read_u8()?;
+            if !lenient && !GmaVersion::from(version).is_known() {
+                Err(Error::InvalidVersion(version))
+            } else {
+                Ok((n, version))
+            }
+        })
+    }
+
+    // With strict file numbers every number must be greater than the previous one
+    fn read_file_number(&mut self, index: usize, previous: u32) -> Result<u32> {
+        let strict = self.options.strict_file_numbers;
+        self.traced(format_args!("entries[{}].number", index), 4, |r| {
+            let (n, number) = r.read_u32()?;
+            if strict && number != 0 && number <= previous {
+                Err(Error::NonMonotonicFileNumber {
+                    index,
+                    previous,
+                    found: number,
+                })
+            } else {
+                Ok((n, number))
+            }
+        })
     }
 
     fn read_steamid(&mut self) -> Result<u64> {
-        Ok(self.reader.read_u64()?.1)
+        self.read_u64(format_args!("steamid"))
     }
 
     fn read_timestamp(&mut self) -> Result<u64> {
-        Ok(self.reader.read_u64()?.1)
+        self.read_u64(format_args!("timestamp"))
     }
 
     fn read_required_content(&mut self) -> Result<Vec<String>> {
         let mut v = Vec::new();
-        while {
-            let string = self.reader.read_c_string()?.1;
+        loop {
+            let string = self.read_string(format_args!("required_content[{}]", v.len()))?;
+            if string.is_empty() {
+                return Ok(v);
+            }
             v.push(string);
-            !v.last().unwrap().is_empty()
-        } {}
-        Ok(v)
+        }
     }
 
     fn read_name(&mut self) -> Result<String> {
-        Ok(self.reader.read_c_string()?.1)
+        self.read_string(format_args!("name"))
     }
 
     fn read_desc(&mut self) -> Result<String> {
-        Ok(self.reader.read_c_string()?.1)
+        self.read_string(format_args!("description"))
     }
 
     fn read_author(&mut self) -> Result<String> {
-        Ok(self.reader.read_c_string()?.1)
+        self.read_string(format_args!("author"))
     }
 
     fn read_addon_version(&mut self) -> Result<u32> {
-        Ok(self.reader.read_u32()?.1)
+        self.read_u32(format_args!("addon_version"))
     }
 
-    fn read_file_entries(&mut self) -> Result<Vec<FileEntry>> {
+    // Returns the entries, or the compact table if enabled, and the total size of their contents
+    fn read_file_entries(&mut self) -> Result<(Vec<FileEntry>, Option<CompactEntries>, u64)> {
         let mut entries = Vec::new();
+        let mut compact = match self.options.compact {
+            true => Some(CompactEntries::default()),
+            false => None,
+        };
         let mut current_offset: u64 = 0;
-        while self.reader.read_u32()?.1 != 0 {
-            let filename = self.reader.read_c_string()?.1;
-            let filesize = self.reader.read_u64()?.1;
-            let crc = self.reader.read_u32()?.1;
+        let mut previous_number = 0;
+        for index in 0.. {
+            let number = self.read_file_number(index, previous_number)?;
+            if number == 0 {
+                break;
+            }
+            if let Some(max_entries) = self.options.max_entries {
+                if index >= max_entries {
+                    return Err(Error::TooManyEntries(max_entries));
+                }
+            }
+            previous_number = number;
+            let filename = self.read_string(format_args!("entries[{}].filename", index))?;
+            let filesize = self.read_u64(format_args!("entries[{}].size", index))?;
+            let crc = self.read_u32(format_args!("entries[{}].crc", index))?;
             let offset = current_offset;
-            current_offset += filesize;
-            entries.push(FileEntry {
+            current_offset = current_offset.saturating_add(filesize);
+            if let Some(filter) = self.options.entry_filter {
+                let header = FileEntryHeader {
+                    filename: &filename,
+                    size: filesize,
+                    crc,
+                    file_number: number,
+                    table_index: index,
+                };
+                if !filter(&header) {
+                    continue;
+                }
+            }
+            if let Some(compact) = compact.as_mut() {
+                compact.push(&filename, filesize, crc, number, offset);
+                continue;
+            }
+            let filename = match &self.options.interner {
+                Some(interner) => interner.intern(&filename),
+                None => Arc::from(filename),
+            };
+            entries.push(FileEntry::new(
                 filename,
                 filesize,
                 crc,
+                entries.len(),
+                number,
                 offset,
-            })
+                0,
+            ));
+        }
+        if let Some(compact) = compact.as_mut() {
+            compact.shrink_to_fit();
         }
-        Ok(entries)
+        Ok((entries, compact, current_offset))
     }
 }
 
-// Returns a decompression stream if the provided stream is lzma compressed,
+// Returns a decompression stream if the provided stream is compressed,
 // otherwise returns the provided stream
-fn get_reader_stream<ReaderType>(mut reader: ReaderType) -> Result<StreamType<ReaderType>>
+fn get_reader_stream<ReaderType>(
+    mut reader: ReaderType,
+    max_size: u64,
+    spill_threshold: Option<u64>,
+) -> Result<(StreamType<ReaderType>, Compression)>
 where
     ReaderType: BufRead + Seek,
 {
     let mut probe_buffer: [u8; 4] = [0; 4];
-    let stream_start_pos = reader.seek(SeekFrom::Current(0))?;
+    let stream_start_pos = reader.stream_position()?;
     reader.read_exact(&mut probe_buffer)?;
     reader.seek(SeekFrom::Start(stream_start_pos))?;
     match probe_buffer {
-        IDENT => Ok(StreamType::Uncompressed(reader)),
-        //Error decompressing, we assume this is not a lzma file
+        IDENT => Ok((StreamType::Uncompressed(reader), Compression::None)),
         _ => {
-            let file_buffer = Vec::new();
-            let mut buffer_cursor = Cursor::new(file_buffer);
-            lzma_rs::lzma_decompress(&mut reader, &mut buffer_cursor).unwrap();
-            buffer_cursor.seek(SeekFrom::Start(0))?;
-            Ok(StreamType::Compressed((reader, buffer_cursor)))
+            let (format, decompressed) =
+                compression::decompress_spilling(&mut reader, max_size, spill_threshold)?;
+            let stream = match decompressed {
+                Decompressed::Memory(buffer) => {
+                    StreamType::Compressed((reader, Cursor::new(buffer)))
+                }
+                Decompressed::Spilled(file) => StreamType::Spilled((
+                    reader,
+                    BufReader::with_capacity(Config::global().buffer_size, file),
+                )),
+            };
+            Ok((stream, format))
         }
     }
 }
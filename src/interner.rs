@@ -0,0 +1,93 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// A set of shared entry names, used to store each distinct filename once across many archives.
+///
+/// Cloning an interner is cheap and the clones share the same names, see [`crate::LoadOptions::intern_names`]
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let interner = gma::Interner::new();
+/// let mut options = gma::LoadOptions::new();
+/// options.intern_names(&interner);
+/// let first = options.load_from_memory(dummy_buffer).unwrap();
+/// let second = options.load_from_memory(dummy_buffer).unwrap();
+/// assert_eq!(interner.len(), first.entries().count());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    names: Arc<Mutex<HashSet<Arc<str>>>>,
+}
+
+impl Interner {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `name`, adding it if it isn't known yet
+    pub fn intern(&self, name: &str) -> Arc<str> {
+        let mut names = self.lock();
+        match names.get(name) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Arc<str> = Arc::from(name);
+                names.insert(shared.clone());
+                shared
+            }
+        }
+    }
+
+    /// The number of distinct names
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// True if no names were interned
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drops the names that are no longer used by any loaded archive
+    /// and returns how many were dropped
+    pub fn purge(&self) -> usize {
+        let mut names = self.lock();
+        let before = names.len();
+        names.retain(|name| Arc::strong_count(name) > 1);
+        before - names.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<Arc<str>>> {
+        // the set is never left in an inconsistent state so a poisoned lock is still usable
+        self.names
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_name_is_shared() {
+        let interner = Interner::new();
+        let a = interner.intern("lua/autorun/a.lua");
+        let b = interner.intern(&String::from("lua/autorun/a.lua"));
+        let c = interner.intern("lua/autorun/c.lua");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn purge_drops_unused() {
+        let interner = Interner::new();
+        let kept = interner.intern("kept");
+        interner.intern("dropped");
+        assert_eq!(interner.purge(), 1);
+        assert_eq!(interner.len(), 1);
+        assert!(Arc::ptr_eq(&kept, &interner.intern("kept")));
+    }
+}
@@ -1,20 +1,153 @@
 //! Crate for reading and writing gma files, the file format of garrys mod's addons.
 //! This crate currently does not support opening compressed archives.
+//!
+//! # Untrusted input
+//! Loading and reading archives never panics on malformed or malicious input, every problem is
+//! reported as an [`Error`]. The only panics left are the ones a caller asks for, like indexing
+//! an archive out of bounds or panicking inside a `read_entry` callback.
+//!
+//! # Threads
+//! A [`GMAFile`] is `Send` and `Sync` whenever its reader is `Send`, which includes the ones
+//! returned by [`open`], [`load_from_memory`] and [`GMAFile::buffer_all`], so archives can be
+//! moved into worker threads or shared behind an [`std::sync::Arc`]:
+//! - Entry metadata, like [`GMAFile::entries`] or [`GMAFile::get`], never blocks.
+//! - Reading contents goes through the single underlying reader. A read while another thread is
+//!   reading waits for it to finish, threads that need to read at the same time should each open
+//!   their own copy. A read started while another read on the same thread is in progress, like
+//!   from inside the closure of [`GMAFile::read_entry`], fails with [`Error::ReaderInUse`].
+//! - Archives loaded with [`load_shared`] serve contents with [`GMAFile::entry_bytes`] from any
+//!   number of threads at once, without locking.
+//! - Archives opened with [`open_concurrent`], or loaded from any [`ReadAt`] with
+//!   [`load_concurrent`], serve contents with [`GMAFile::read_entry_concurrent`] from any number
+//!   of threads at once, using positioned reads on the shared file.
+//!
+//! [`GMABuilder`] is not `Send` since it can hold arbitrary readers and callbacks, build archives
+//! on the thread that created the builder.
+//!
+//! # sharpGMad archives
+//! Archives written by sharpGMad follow the format but differ from gmad's in a few ways, which
+//! are handled so they load and round-trip without losing metadata:
+//! - The metadata json has its own layout and can have extra keys or leave out the description
+//!   or the tags. Missing fields are read as empty and the string is kept as it was stored in
+//!   [`GMAFile::metadata_json`].
+//! - Types and tags can be capitalized, like `"Tool"` or `"Fun"`. They are matched ignoring
+//!   case and the stored spelling is available from [`GMAFile::addon_type_raw`] and
+//!   [`GMAFile::tags_raw`].
+//! - Entries gmad would not add, like an `addon.json` or files outside the whitelist, are
+//!   loaded like any other entry, [`GMAFile::validate`] reports them.
+//! - The functions in [`transform`] write the metadata string back verbatim with
+//!   [`GMABuilder::raw_metadata`], so modified copies keep it.
 
+mod addon_json;
+// nanoserde's derive output trips this lint
+#[allow(clippy::question_mark)]
 mod addon_metadata;
+pub mod analysis;
+mod ascii;
 mod binary;
+mod build_report;
+mod capabilities;
+mod carve;
+mod checksum;
+mod compression;
+mod config;
+mod conflicts;
+mod content_kind;
+#[cfg(feature = "difftest")]
+pub mod difftest;
+mod disk;
+mod entry_cache;
+mod entry_contents;
+mod entry_lines;
+mod entry_reader;
+mod entry_table;
 mod error;
+mod extract;
+#[cfg(all(feature = "fuse", unix))]
+pub mod fuse;
 mod gma_builder;
 mod gma_reader;
+mod interner;
+mod load_options;
+mod metadata;
+mod name_index;
+mod normalize;
+pub mod pipeline;
+mod probe;
+mod raw_records;
+mod read_at;
+mod recover;
 mod result;
+mod sample;
+mod shared;
+mod snapshot;
+mod source;
+mod stats;
+mod steamid;
+mod tee;
+mod text;
+mod trace;
+pub mod transform;
+mod tree;
+mod validate;
+mod version;
+mod view;
+mod walk;
+mod whitelist;
+mod wildcard;
+pub mod workshop;
+mod zip;
 
+pub use addon_metadata::MetadataFormat;
+pub use ascii::{transliterate, NonAsciiName};
+pub use build_report::{
+    BuildReport, BuildWarning, BuiltFile, ResumeToken, SkipReason, SkippedFile,
+};
+pub use capabilities::{capabilities, Capabilities};
+pub use carve::{carve, Carver};
+pub use compression::Compression;
+pub use config::Config;
+pub use conflicts::{conflicts, Conflict};
+pub use content_kind::ContentKind;
+pub use entry_contents::EntriesWithContents;
+pub use entry_lines::{EntryLines, TextFallback};
+pub use entry_reader::EntryReader;
+pub use entry_table::EntryView;
 pub use error::Error;
-pub use gma_builder::GMABuilder;
-pub use gma_reader::{FileEntry, GMAFile};
+pub use extract::{
+    sanitize_path, ExtractOptions, ExtractPlan, ExtractReport, ExtractedFile, LinkKind, PathPolicy,
+    PlannedFile,
+};
+pub use gma_builder::{DuplicatePolicy, GMABuilder, DEFAULT_IGNORE};
+pub use gma_reader::{FileEntry, FileEntryHeader, GMAFile};
+pub use interner::Interner;
+pub use load_options::{LoadOptions, OpenOptions};
+pub use metadata::{ArchiveMetadata, EntryMetadata};
+pub use name_index::NameIndex;
+pub use normalize::{normalize, NormalizeOptions, NormalizeReport};
+pub use probe::{probe, ProbeInfo};
+pub use raw_records::{RawEntryRecord, RawEntryRecords};
+#[cfg(any(unix, windows))]
+pub use read_at::open_concurrent;
+pub use read_at::{load_concurrent, load_read_at, ReadAt, ReadAtReader};
+pub use recover::{recover, LossReason, LostEntry, RecoveredEntry, Recovery};
 pub use result::Result;
+pub use shared::{load_shared, SharedCursor};
+pub use snapshot::ArchiveSnapshot;
+pub use source::{AddonSource, SourceReader};
+pub use stats::{ArchiveStats, CategoryStats, ContentCategory};
 use std::convert::TryFrom;
-
-use gma_reader::GMAFileReader;
+pub use steamid::SteamID;
+pub use tee::{TeeWriter, WriteSeek};
+pub use text::{EntryText, TextEncoding};
+pub use trace::{ParseTrace, TraceField};
+pub use tree::DirectoryNode;
+pub use validate::{
+    ValidationIssue, ValidationReport, MAX_DESCRIPTION_LENGTH, MAX_FILENAME_LENGTH, MAX_NAME_LENGTH,
+};
+pub use version::GmaVersion;
+pub use view::{load_view, GMAView};
+pub use whitelist::{is_whitelisted, WHITELIST};
 
 use std::io::BufReader;
 use std::{
@@ -23,9 +156,9 @@ use std::{
 };
 
 const IDENT: [u8; 4] = [b'G', b'M', b'A', b'D'];
-const VALID_VERSIONS: [u8; 3] = [1, 2, 3];
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AddonType {
     Gamemode,
     Map,
@@ -61,6 +194,7 @@ impl TryFrom<&str> for AddonType {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AddonTag {
     Fun,
     Roleplay,
@@ -98,17 +232,75 @@ pub fn open<P>(path: P) -> Result<GMAFile<BufReader<std::fs::File>>>
 where
     P: AsRef<Path>,
 {
-    let file = std::fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    load(reader)
+    LoadOptions::new().open(path)
 }
 
-/// Loads a gma file from a reader
+/// Loads a gma file from a reader.
+///
+/// `&mut R` is a reader too, so callers that need their reader back can lend it instead of
+/// giving it away and reuse it once the archive is dropped. The archive seeks the reader as it
+/// likes, its position afterwards is unspecified
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// use std::io::{Seek, SeekFrom};
+///
+/// let mut reader = std::io::Cursor::new(dummy_buffer);
+/// {
+///     let archive = gma::load(&mut reader).unwrap();
+///     println!("{}", archive.name());
+/// }
+/// reader.seek(SeekFrom::Start(0)).unwrap();
+/// ```
 pub fn load<ReaderType>(r: ReaderType) -> Result<GMAFile<ReaderType>>
 where
     ReaderType: BufRead + Seek,
 {
-    GMAFileReader::new(r)?.read_gma()
+    LoadOptions::new().load(r)
+}
+
+/// Loads a gma file from a reader that can't seek, see [`LoadOptions::load_compressed`]
+pub fn load_compressed<R: std::io::Read>(reader: R) -> Result<GMAFile<Cursor<Vec<u8>>>> {
+    LoadOptions::new().load_compressed(reader)
+}
+
+/// Detects whether the file at `path` is a plain or a compressed archive by reading only its
+/// first bytes, much cheaper than [`open`] for compressed archives.
+///
+/// Returns an error if the file is neither a plain archive nor one compressed with a supported format
+/// ```no_run
+/// if gma::probe_compression("addon.gma").unwrap() != gma::Compression::None {
+///     println!("compressed");
+/// }
+/// ```
+pub fn probe_compression<P: AsRef<Path>>(path: P) -> Result<Compression> {
+    let mut reader =
+        BufReader::with_capacity(Config::global().buffer_size, std::fs::File::open(path)?);
+    compression::probe(&mut reader)
+}
+
+/// The size of the archive at `path` once decompressed, read from the header of compressed
+/// archives without decompressing them, so callers can decide whether to load an archive in
+/// memory, spill it to disk or reject it.
+///
+/// Plain archives return their file size. Returns None for archives whose header doesn't record
+/// the size, like lzma streams written without it or brotli archives. The size in the header is
+/// not verified until the archive is decompressed, see [`LoadOptions::max_decompressed_size`]
+/// ```no_run
+/// match gma::probe_decompressed_size("addon.gma").unwrap() {
+///     Some(size) if size > 512 * 1024 * 1024 => println!("too large to load in memory"),
+///     _ => println!("fine"),
+/// }
+/// ```
+pub fn probe_decompressed_size<P: AsRef<Path>>(path: P) -> Result<Option<u64>> {
+    let file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::with_capacity(Config::global().buffer_size, file);
+    match compression::probe(&mut reader)? {
+        Compression::None => Ok(Some(len)),
+        Compression::Lzma => compression::lzma_unpacked_size(&mut reader),
+        #[cfg(feature = "brotli")]
+        Compression::Brotli => Ok(None),
+    }
 }
 
 /// Loads a gma file from memory
@@ -0,0 +1,247 @@
+use crate::{
+    compression, gma_reader::GMAFileReader, Compression, Config, FileEntryHeader, GMAFile,
+    Interner, Result, IDENT,
+};
+use std::{
+    io::{BufRead, BufReader, Cursor, Read, Seek},
+    path::Path,
+};
+
+/// Options used when loading an archive.
+///
+/// [`crate::open`], [`crate::load`] and [`crate::load_from_memory`] use the default options.
+/// The defaults are the ones set in [`crate::Config::global`]
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let archive = gma::LoadOptions::new()
+///     .max_string_length(4096)
+///     .load_from_memory(dummy_buffer)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub(crate) max_string_length: usize,
+    pub(crate) trace: bool,
+    // (max entry size, capacity in bytes)
+    pub(crate) entry_cache: Option<(u64, usize)>,
+    pub(crate) interner: Option<Interner>,
+    pub(crate) ignore_case: bool,
+    pub(crate) compact: bool,
+    pub(crate) strict_file_numbers: bool,
+    pub(crate) lenient: bool,
+    pub(crate) verify_crc: bool,
+    pub(crate) max_decompressed_size: Option<u64>,
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) buffer_capacity: usize,
+    pub(crate) spill_threshold: Option<u64>,
+    pub(crate) entry_filter: Option<fn(&FileEntryHeader) -> bool>,
+}
+
+/// The same type as [`LoadOptions`], named like [`std::fs::OpenOptions`]
+/// ```no_run
+/// let archive = gma::OpenOptions::new()
+///     .verify_crc(true)
+///     .max_entries(10_000)
+///     .open("addon.gma")
+///     .unwrap();
+/// ```
+pub type OpenOptions = LoadOptions;
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadOptions {
+    /// Creates the default options, from [`crate::Config::global`]
+    pub fn new() -> Self {
+        let config = Config::global();
+        Self {
+            max_string_length: config.max_string_length,
+            trace: false,
+            entry_cache: None,
+            interner: None,
+            ignore_case: config.case_insensitive_lookups,
+            compact: false,
+            strict_file_numbers: config.strict_file_numbers,
+            lenient: config.lenient,
+            verify_crc: config.verify_crc,
+            max_decompressed_size: config.max_decompressed_size,
+            max_entries: config.max_entries,
+            buffer_capacity: config.buffer_size,
+            spill_threshold: None,
+            entry_filter: None,
+        }
+    }
+
+    /// Sets the maximum length in bytes of the strings in the header, like the name or description.
+    /// Longer strings fail with [`crate::Error::StringTooLong`]. Default : 1 MiB
+    pub fn max_string_length(&mut self, max_len: usize) -> &mut Self {
+        self.max_string_length = max_len;
+        self
+    }
+
+    /// Records every header and file table field that is read into a [`crate::ParseTrace`].
+    /// The trace is available from [`GMAFile::parse_trace`] or, if loading fails, from
+    /// [`crate::Error::trace`]. Default : false
+    pub fn trace(&mut self, trace: bool) -> &mut Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Keeps the contents of entries of at most `max_entry_size` bytes in memory after they are
+    /// read, so reading them again doesn't touch the underlying reader. The least recently used
+    /// entries are dropped once the cached contents exceed `capacity` bytes.
+    ///
+    /// Entries that are cached are read whole even if the [`GMAFile::read_entry`] callback only
+    /// reads part of them. Default : disabled
+    pub fn entry_cache(&mut self, max_entry_size: u64, capacity: usize) -> &mut Self {
+        self.entry_cache = Some((max_entry_size, capacity));
+        self
+    }
+
+    /// Stores entry filenames in `interner`, so archives loaded with the same interner share a
+    /// single copy of each distinct filename. Default : every archive owns its filenames
+    pub fn intern_names(&mut self, interner: &Interner) -> &mut Self {
+        self.interner = Some(interner.clone());
+        self
+    }
+
+    /// Makes [`GMAFile::get`] and indexing by filename ignore case, the way the game resolves paths.
+    /// Default : false
+    pub fn case_insensitive_lookups(&mut self, ignore_case: bool) -> &mut Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Stores the file table as parallel arrays with all the filenames in a single buffer, which
+    /// takes a fraction of the memory for archives with many entries. Filenames are not interned.
+    ///
+    /// The table stays compact as long as entries are found with [`GMAFile::entry_views`] and
+    /// [`GMAFile::get_view`] and read through [`crate::EntryView::to_entry`]. Extracting,
+    /// validating and the transforms work from the views too. Every accessor that returns a
+    /// `&FileEntry`, like [`GMAFile::entries`] or [`GMAFile::get`], builds the regular entries
+    /// the first time it is used and keeps them for the life of the archive. Default : false
+    pub fn compact(&mut self, compact: bool) -> &mut Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Fails with [`crate::Error::NonMonotonicFileNumber`] if the file numbers in the file table
+    /// are not increasing. The game and gmad only stop at a 0 and ignore the value otherwise,
+    /// see [`crate::FileEntry::file_number`]. Default : false
+    pub fn strict_file_numbers(&mut self, strict: bool) -> &mut Self {
+        self.strict_file_numbers = strict;
+        self
+    }
+
+    /// Accepts archives that would otherwise fail to load, for tools that inspect damaged or
+    /// unusual files: unknown format versions are accepted, strings that are not valid
+    /// utf8 have the invalid bytes replaced and archives whose last entries are truncated are
+    /// loaded, reading those entries then returns fewer bytes than their size.
+    /// See [`crate::recover`] for archives that are too damaged to load. Default : false
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Makes [`GMAFile::read_entry`], and everything built on it, check the contents against the
+    /// entry's crc32 like [`GMAFile::read_entry_verified`]. Default : false
+    pub fn verify_crc(&mut self, verify: bool) -> &mut Self {
+        self.verify_crc = verify;
+        self
+    }
+
+    /// Fails with [`crate::Error::DecompressedTooLarge`] if a compressed archive decompresses to
+    /// more than `max_size` bytes, protecting against decompression bombs. Default : no limit
+    pub fn max_decompressed_size(&mut self, max_size: u64) -> &mut Self {
+        self.max_decompressed_size = Some(max_size);
+        self
+    }
+
+    /// Fails with [`crate::Error::TooManyEntries`] if the file table has more than `max_entries`
+    /// entries. Default : no limit
+    pub fn max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// The capacity in bytes of the buffered reader used by [`LoadOptions::open`], a capacity of
+    /// 0 is raised to 1 byte. Default : [`crate::Config::buffer_size`]
+    pub fn buffer_capacity(&mut self, bytes: usize) -> &mut Self {
+        self.buffer_capacity = bytes.max(1);
+        self
+    }
+
+    /// Decompresses compressed archives larger than `threshold` bytes into a temporary file
+    /// instead of memory, entries are then read from that file. The file is removed when the
+    /// archive is dropped. Only applies to [`LoadOptions::open`] and [`LoadOptions::load`],
+    /// the other functions return archives that are kept in memory. Default : disabled
+    pub fn spill_to_disk(&mut self, threshold: u64) -> &mut Self {
+        self.spill_threshold = Some(threshold);
+        self
+    }
+
+    /// Only keeps the entries of the file table for which `filter` returns true, the others are
+    /// dropped while the table is parsed so they never take any memory. The archive behaves as if
+    /// it only had the kept entries, [`crate::FileEntry::index`] counts those only.
+    ///
+    /// Dropped entries still count for [`LoadOptions::max_entries`]. Default : every entry is kept
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::LoadOptions::new()
+    ///     .entry_filter(|entry| !entry.filename().ends_with(".exe"))
+    ///     .load_from_memory(dummy_buffer)
+    ///     .unwrap();
+    /// ```
+    pub fn entry_filter(&mut self, filter: fn(&FileEntryHeader) -> bool) -> &mut Self {
+        self.entry_filter = Some(filter);
+        self
+    }
+
+    /// Opens a file from disk with the given path and tries to read it as a gma archive
+    pub fn open<P>(&self, path: P) -> Result<GMAFile<BufReader<std::fs::File>>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = std::fs::File::open(path)?;
+        self.load(BufReader::with_capacity(self.buffer_capacity, file))
+    }
+
+    /// Loads a gma file from a reader, which can be a `&mut R` that is reused afterwards, see
+    /// [`crate::load`]
+    pub fn load<ReaderType>(&self, r: ReaderType) -> Result<GMAFile<ReaderType>>
+    where
+        ReaderType: BufRead + Seek,
+    {
+        GMAFileReader::new(r, self.clone())?.read_gma()
+    }
+
+    /// Loads a gma file from a reader that can't seek, like a pipe or a download stream.
+    ///
+    /// The input is read to the end and buffered in memory. Compressed archives are decompressed
+    /// and the compressed bytes are dropped, uncompressed archives are also accepted
+    pub fn load_compressed<R: Read>(&self, mut reader: R) -> Result<GMAFile<Cursor<Vec<u8>>>> {
+        let mut input = Vec::new();
+        reader.read_to_end(&mut input)?;
+        if input.starts_with(&IDENT) {
+            return GMAFileReader::with_compression(
+                Cursor::new(input),
+                Compression::None,
+                self.clone(),
+            )?
+            .read_gma();
+        }
+        let (format, data) = compression::decompress_limited(
+            &mut Cursor::new(input),
+            self.max_decompressed_size.unwrap_or(u64::MAX),
+        )?;
+        GMAFileReader::with_compression(Cursor::new(data), format, self.clone())?.read_gma()
+    }
+
+    /// Loads a gma file from memory
+    pub fn load_from_memory<'a>(&self, data: &'a [u8]) -> Result<GMAFile<Cursor<&'a [u8]>>> {
+        self.load(Cursor::new(data))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{AddonTag, AddonType, EntryView, FileEntry, GMAFile, GmaVersion, SteamID};
+use std::io::{BufRead, Seek};
+
+/// A snapshot of an archive's header and file table, see [`GMAFile::metadata`].
+///
+/// With the `serde` feature enabled it implements `Serialize` so it can be dumped to json, toml, etc
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArchiveMetadata {
+    pub version: GmaVersion,
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    pub author_steamid: SteamID,
+    pub timestamp: u64,
+    pub addon_type: Option<AddonType>,
+    pub addon_tags: Vec<AddonTag>,
+    pub compressed: bool,
+    pub entries: Vec<EntryMetadata>,
+}
+
+/// A file table entry inside [`ArchiveMetadata`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EntryMetadata {
+    pub filename: String,
+    pub size: u64,
+    pub crc: u32,
+    pub offset: u64,
+}
+
+impl From<&FileEntry> for EntryMetadata {
+    fn from(entry: &FileEntry) -> Self {
+        Self {
+            filename: entry.filename().to_owned(),
+            size: entry.size(),
+            crc: entry.crc(),
+            offset: entry.offset(),
+        }
+    }
+}
+
+impl From<EntryView<'_>> for EntryMetadata {
+    fn from(entry: EntryView<'_>) -> Self {
+        Self {
+            filename: entry.filename().to_owned(),
+            size: entry.size(),
+            crc: entry.crc(),
+            offset: entry.offset(),
+        }
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Copies the header fields and the file table into an [`ArchiveMetadata`]
+    pub fn metadata(&self) -> ArchiveMetadata {
+        ArchiveMetadata {
+            version: self.version(),
+            name: self.name().to_owned(),
+            description: self.description().to_owned(),
+            author: self.author().to_owned(),
+            author_steamid: self.author_steamid(),
+            timestamp: self.timestamp(),
+            addon_type: self.addon_type(),
+            addon_tags: self.addon_tags().to_vec(),
+            compressed: self.compressed(),
+            entries: self.entry_views().map(EntryMetadata::from).collect(),
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// 64 bit FNV-1a, simple enough to guarantee it never changes
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    // length prefixed so that ("ab", "c") and ("a", "bc") hash differently
+    fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+}
+
+fn addon_type_str(addon_type: AddonType) -> &'static str {
+    match addon_type {
+        AddonType::Gamemode => "gamemode",
+        AddonType::Map => "map",
+        AddonType::Weapon => "weapon",
+        AddonType::Vehicle => "vehicle",
+        AddonType::NPC => "npc",
+        AddonType::Entity => "entity",
+        AddonType::Tool => "tool",
+        AddonType::Effects => "effects",
+        AddonType::Model => "model",
+        AddonType::ServerContent => "servercontent",
+    }
+}
+
+fn addon_tag_str(tag: AddonTag) -> &'static str {
+    match tag {
+        AddonTag::Fun => "fun",
+        AddonTag::Roleplay => "roleplay",
+        AddonTag::Scenic => "scenic",
+        AddonTag::Movie => "movie",
+        AddonTag::Realism => "realism",
+        AddonTag::Cartoon => "cartoon",
+        AddonTag::Water => "water",
+        AddonTag::Comic => "comic",
+        AddonTag::Build => "build",
+    }
+}
+
+impl ArchiveMetadata {
+    /// A 64 bit hash of the archive's contents, suitable as a key for download caches.
+    ///
+    /// The hash covers the name, description, author, steamid, addon type, tags and the
+    /// filename, size and crc of every entry in archive order. The timestamp, the format version
+    /// and the compression are not included, so rebuilding the same addon gives the same hash.
+    ///
+    /// The algorithm (FNV-1a over a length prefixed encoding of those fields) is part of the
+    /// public api, the same metadata hashes to the same value in every version of this crate.
+    /// It is not a cryptographic hash and should not be trusted to detect tampering
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write_str(&self.name);
+        hasher.write_str(&self.description);
+        hasher.write_str(&self.author);
+        hasher.write_u64(self.author_steamid.as_u64());
+        hasher.write_str(self.addon_type.map_or("", addon_type_str));
+        hasher.write_u64(self.addon_tags.len() as u64);
+        for tag in self.addon_tags.iter() {
+            hasher.write_str(addon_tag_str(*tag));
+        }
+        hasher.write_u64(self.entries.len() as u64);
+        for entry in self.entries.iter() {
+            hasher.write_str(&entry.filename);
+            hasher.write_u64(entry.size);
+            hasher.write_u64(u64::from(entry.crc));
+        }
+        hasher.0
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// [`ArchiveMetadata::stable_hash`] as a 16 character lowercase hex string
+    pub fn cache_key(&self) -> String {
+        format!("{:016x}", self.metadata().stable_hash())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{FileEntry, GMAFile};
+use std::io::{BufRead, Seek};
+
+/// Case-insensitive lookup structure over entry names, see [`GMAFile::build_name_index`]
+#[derive(Debug, Clone)]
+pub struct NameIndex<'a> {
+    //lowercase names, sorted, with ties kept in archive order
+    names: Vec<(String, &'a FileEntry)>,
+}
+
+impl<'a> NameIndex<'a> {
+    /// The first entry, in archive order, whose filename matches `name` ignoring case
+    pub fn get(&self, name: &str) -> Option<&'a FileEntry> {
+        self.get_all(name).next()
+    }
+    /// Every entry whose filename matches `name` ignoring case, in archive order
+    pub fn get_all(&self, name: &str) -> impl Iterator<Item = &'a FileEntry> + '_ {
+        let name = name.to_lowercase();
+        let start = self
+            .names
+            .partition_point(|(n, _)| n.as_str() < name.as_str());
+        self.names[start..]
+            .iter()
+            .take_while(move |(n, _)| *n == name)
+            .map(|(_, entry)| *entry)
+    }
+    /// True if there is an entry whose filename matches `name` ignoring case
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+    /// Every entry whose filename starts with `prefix` ignoring case, sorted by lowercase filename.
+    /// Ex : `materials/` lists everything under the materials directory
+    pub fn with_prefix(&self, prefix: &str) -> impl Iterator<Item = &'a FileEntry> + '_ {
+        let prefix = prefix.to_lowercase();
+        let start = self
+            .names
+            .partition_point(|(n, _)| n.as_str() < prefix.as_str());
+        self.names[start..]
+            .iter()
+            .take_while(move |(n, _)| n.starts_with(prefix.as_str()))
+            .map(|(_, entry)| *entry)
+    }
+    /// The number of indexed entries
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    /// True if the archive has no entries
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    /// An estimate in bytes of the memory held by this index, not counting the archive
+    pub fn approx_memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.names.capacity() * std::mem::size_of::<(String, &FileEntry)>()
+            + self.names.iter().map(|(n, _)| n.capacity()).sum::<usize>()
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Builds a [`NameIndex`] for fast case-insensitive exact and prefix lookups.
+    ///
+    /// Names are lowercased with unicode rules so the index is worth building when many
+    /// lookups are done on the same archive
+    pub fn build_name_index(&self) -> NameIndex<'_> {
+        let mut names: Vec<(String, &FileEntry)> = self
+            .entries()
+            .map(|entry| (entry.filename().to_lowercase(), entry))
+            .collect();
+        //sort is stable so entries with the same name stay in archive order
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        NameIndex { names }
+    }
+}
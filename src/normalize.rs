@@ -0,0 +1,101 @@
+use crate::{transform::header_builder, Result};
+use std::io::{BufRead, Seek, Write};
+
+/// Options for [`normalize`]. Every step is enabled by default
+#[derive(Debug, Clone)]
+pub struct NormalizeOptions {
+    lowercase_paths: bool,
+    sort_entries: bool,
+    strip_disallowed: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NormalizeOptions {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self {
+            lowercase_paths: true,
+            sort_entries: true,
+            strip_disallowed: true,
+        }
+    }
+
+    /// Converts every entry path to lowercase. Default : true
+    pub fn lowercase_paths(&mut self, lowercase: bool) -> &mut Self {
+        self.lowercase_paths = lowercase;
+        self
+    }
+
+    /// Sorts the entries by path. Default : true
+    pub fn sort_entries(&mut self, sort: bool) -> &mut Self {
+        self.sort_entries = sort;
+        self
+    }
+
+    /// Removes the entries that are not in garry's mod whitelist. Default : true
+    pub fn strip_disallowed(&mut self, strip: bool) -> &mut Self {
+        self.strip_disallowed = strip;
+        self
+    }
+}
+
+/// What [`normalize`] changed
+#[derive(Debug, Default)]
+pub struct NormalizeReport {
+    stripped: Vec<String>,
+}
+
+impl NormalizeReport {
+    /// The entries that were removed because they are not whitelisted
+    pub fn stripped(&self) -> &[String] {
+        &self.stripped
+    }
+}
+
+/// Reads the archive from `input` and writes a normalized, uncompressed copy of it to `output`.
+///
+/// The header fields are preserved, the metadata json is rewritten in the same format used by
+/// [`crate::GMABuilder`] and every crc is recomputed from the entry contents.
+/// Entry contents are buffered in memory while the new archive is built.
+pub fn normalize<R, W>(input: R, output: W, options: &NormalizeOptions) -> Result<NormalizeReport>
+where
+    R: BufRead + Seek,
+    W: Write + Seek,
+{
+    let archive = crate::load(input)?;
+    let mut report = NormalizeReport::default();
+
+    let mut files = Vec::new();
+    for entry in archive.entries() {
+        let filename = match options.lowercase_paths {
+            true => entry.filename().to_lowercase(),
+            false => entry.filename().to_owned(),
+        };
+        if options.strip_disallowed && !crate::is_whitelisted(&filename) {
+            report.stripped.push(entry.filename().to_owned());
+            continue;
+        }
+        let contents = archive.try_read_entry(entry, |_, reader| {
+            let mut contents = Vec::with_capacity(archive.contents_capacity(entry));
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })?;
+        files.push((filename, contents));
+    }
+    if options.sort_entries {
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    let mut builder = header_builder(&archive);
+    for (filename, contents) in files {
+        builder.file_from_bytes(filename, contents);
+    }
+    builder.write_to(output)?;
+
+    Ok(report)
+}
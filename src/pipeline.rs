@@ -0,0 +1,339 @@
+//! Packing an addon folder into an archive that is ready to be published, in a single call
+use crate::{
+    disk, normalize, source::folder_builder, BuildReport, Compression, ContentKind, Error, GMAFile,
+    NonAsciiName, NormalizeOptions, Result, ValidationReport,
+};
+use std::{
+    fmt::Display,
+    io::{BufRead, Cursor, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+/// The compression of the written archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionChoice {
+    /// Always use this format
+    Format(Compression),
+    /// Compress with every supported format, including none, and keep the smallest output
+    Smallest,
+}
+
+/// Everything [`build_and_validate`] needs to know
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    source: PathBuf,
+    output: PathBuf,
+    name: Option<String>,
+    strip_disallowed: bool,
+    size_budget: Option<u64>,
+    compression: CompressionChoice,
+    deny_lints: bool,
+}
+
+impl PipelineConfig {
+    /// Packs the addon folder at `source` into an archive written to `output`.
+    ///
+    /// The folder is packed like [`crate::AddonSource::Folder`], reading `addon.json` if present
+    pub fn new<S: Into<PathBuf>, O: Into<PathBuf>>(source: S, output: O) -> Self {
+        Self {
+            source: source.into(),
+            output: output.into(),
+            name: None,
+            strip_disallowed: false,
+            size_budget: None,
+            compression: CompressionChoice::Format(Compression::None),
+            deny_lints: false,
+        }
+    }
+
+    /// Replaces the title read from `addon.json`. Default : the title in `addon.json`
+    pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Removes the files that are not in garry's mod whitelist instead of rejecting the archive.
+    /// Default : false
+    pub fn strip_disallowed(&mut self, strip: bool) -> &mut Self {
+        self.strip_disallowed = strip;
+        self
+    }
+
+    /// Rejects archives larger than `bytes` after compression. Default : no limit
+    pub fn size_budget(&mut self, bytes: u64) -> &mut Self {
+        self.size_budget = Some(bytes);
+        self
+    }
+
+    /// The compression of the written archive. Default : no compression
+    pub fn compression(&mut self, compression: CompressionChoice) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Rejects archives with any [`Lint`] instead of only reporting them in
+    /// [`Published::lints`]. Default : false
+    pub fn deny_lints(&mut self, deny: bool) -> &mut Self {
+        self.deny_lints = deny;
+        self
+    }
+}
+
+/// A likely mistake found by the lint step of [`build_and_validate`]. Unlike the problems found
+/// by [`crate::GMAFile::validate`] the game still loads the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// The filename has non ascii characters, which some servers and clients mishandle
+    NonAsciiFilename(NonAsciiName),
+    /// The file is empty
+    EmptyFile { filename: String },
+    /// The contents are `kind` but the extension says otherwise, like a png saved as `.vtf`.
+    /// Lua files are expected to be source, the game doesn't run precompiled lua
+    MismatchedContent { filename: String, kind: ContentKind },
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonAsciiFilename(name) => write!(
+                f,
+                "'{}' is not ascii, consider '{}'",
+                name.filename(),
+                name.suggestion()
+            ),
+            Self::EmptyFile { filename } => write!(f, "'{}' is empty", filename),
+            Self::MismatchedContent { filename, kind } => write!(
+                f,
+                "'{}' contains {:?} data, which doesn't match its extension",
+                filename, kind
+            ),
+        }
+    }
+}
+
+/// An archive written by [`build_and_validate`]
+#[derive(Debug, Clone)]
+pub struct Published {
+    path: PathBuf,
+    size: u64,
+    compression: Compression,
+    build_report: BuildReport,
+    stripped: Vec<String>,
+    lints: Vec<Lint>,
+}
+
+impl Published {
+    /// Where the archive was written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// The size in bytes of the written archive
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The compression of the written archive
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+    /// The files packed from the folder and the ones left out by the `ignore` list
+    pub fn build_report(&self) -> &BuildReport {
+        &self.build_report
+    }
+    /// The files removed because they are not whitelisted, see [`PipelineConfig::strip_disallowed`]
+    pub fn stripped(&self) -> &[String] {
+        &self.stripped
+    }
+    /// The likely mistakes found in the archive, see [`PipelineConfig::deny_lints`]
+    pub fn lints(&self) -> &[Lint] {
+        &self.lints
+    }
+}
+
+/// Why [`build_and_validate`] didn't write the archive
+#[derive(Debug, Clone)]
+pub enum Rejection {
+    /// [`crate::GMAFile::validate`] found problems with the packed archive
+    Invalid(ValidationReport),
+    /// The lint step found likely mistakes and [`PipelineConfig::deny_lints`] is set
+    Lints(Vec<Lint>),
+    /// The archive is `size` bytes, more than the `budget`
+    OverBudget { size: u64, budget: u64 },
+}
+
+/// The result of [`build_and_validate`]
+#[derive(Debug)]
+pub enum PipelineOutcome {
+    /// Every check passed and the archive was written
+    Published(Published),
+    /// A check failed, nothing was written
+    Rejected(Rejection),
+    /// An error stopped the pipeline, nothing was written
+    Failed(Error),
+}
+
+impl PipelineOutcome {
+    /// True if the archive was written
+    pub fn is_published(&self) -> bool {
+        matches!(self, Self::Published(_))
+    }
+}
+
+/// Packs an addon folder and writes the archive only if it is ready to be published.
+///
+/// The steps are, in order:
+/// 1. pack the folder, see [`PipelineConfig::new`]
+/// 2. remove the files that are not whitelisted, if [`PipelineConfig::strip_disallowed`] is set
+/// 3. check the archive with [`crate::GMAFile::validate`]
+/// 4. lint it, looking for the likely mistakes listed in [`Lint`]
+/// 5. compress it, see [`PipelineConfig::compression`]
+/// 6. check the [`PipelineConfig::size_budget`]
+/// 7. write it with a temporary file that is renamed to the output path once complete, like
+///    [`crate::GMABuilder::write_to_path`]
+///
+/// The archive is kept in memory until it is written
+/// ```no_run
+/// use gma::pipeline::{build_and_validate, CompressionChoice, PipelineConfig, PipelineOutcome};
+///
+/// let mut config = PipelineConfig::new("my_addon", "my_addon.gma");
+/// config
+///     .size_budget(100 * 1024 * 1024)
+///     .compression(CompressionChoice::Smallest);
+/// match build_and_validate(&config) {
+///     PipelineOutcome::Published(published) => println!("{} bytes", published.size()),
+///     PipelineOutcome::Rejected(rejection) => println!("rejected : {:?}", rejection),
+///     PipelineOutcome::Failed(error) => println!("error : {}", error),
+/// }
+/// ```
+pub fn build_and_validate(config: &PipelineConfig) -> PipelineOutcome {
+    match run(config) {
+        Ok(outcome) => outcome,
+        Err(error) => PipelineOutcome::Failed(error),
+    }
+}
+
+fn run(config: &PipelineConfig) -> Result<PipelineOutcome> {
+    let mut builder = folder_builder(&config.source, false)?;
+    if let Some(name) = &config.name {
+        builder.name(name.as_str());
+    }
+    let mut archive = Vec::new();
+    let build_report = builder
+        .write_to_with_report(Cursor::new(&mut archive))
+        .map_err(Error::uninterrupted)?;
+
+    let mut stripped = Vec::new();
+    if config.strip_disallowed {
+        let mut options = NormalizeOptions::new();
+        options.lowercase_paths(false).sort_entries(false);
+        let mut normalized = Vec::new();
+        let report = normalize(
+            Cursor::new(&archive),
+            Cursor::new(&mut normalized),
+            &options,
+        )?;
+        stripped = report.stripped().to_vec();
+        archive = normalized;
+    }
+
+    let lints = {
+        let loaded = crate::load_from_memory(&archive)?;
+        let report = loaded.validate()?;
+        if !report.is_valid() {
+            return Ok(PipelineOutcome::Rejected(Rejection::Invalid(report)));
+        }
+        lint(&loaded)?
+    };
+    if config.deny_lints && !lints.is_empty() {
+        return Ok(PipelineOutcome::Rejected(Rejection::Lints(lints)));
+    }
+
+    let (compression, output) = compress(archive, config.compression)?;
+    let size = output.len() as u64;
+    if let Some(budget) = config.size_budget.filter(|budget| size > *budget) {
+        return Ok(PipelineOutcome::Rejected(Rejection::OverBudget {
+            size,
+            budget,
+        }));
+    }
+
+    disk::write_atomically(
+        &config.output,
+        size,
+        |writer| Ok(writer.write_all(&output)?),
+    )?;
+    Ok(PipelineOutcome::Published(Published {
+        path: config.output.clone(),
+        size,
+        compression,
+        build_report,
+        stripped,
+        lints,
+    }))
+}
+
+fn lint<ReaderType>(archive: &GMAFile<ReaderType>) -> Result<Vec<Lint>>
+where
+    ReaderType: BufRead + Seek,
+{
+    let mut lints = Vec::new();
+    for entry in archive.entries() {
+        if let Some(name) = NonAsciiName::check(entry.filename()) {
+            lints.push(Lint::NonAsciiFilename(name));
+        }
+        if entry.size() == 0 {
+            lints.push(Lint::EmptyFile {
+                filename: entry.filename().to_owned(),
+            });
+            continue;
+        }
+        let extension = entry.extension().map(str::to_ascii_lowercase);
+        let expected = match extension.as_deref() {
+            Some("vtf") => ContentKind::Vtf,
+            Some("mdl") => ContentKind::Mdl,
+            Some("vvd") => ContentKind::Vvd,
+            Some("bsp") => ContentKind::Bsp,
+            Some("wav") => ContentKind::Wav,
+            Some("mp3") => ContentKind::Mp3,
+            Some("ogg") => ContentKind::Ogg,
+            Some("png") => ContentKind::Png,
+            Some("jpg") | Some("jpeg") => ContentKind::Jpeg,
+            Some("lua") => ContentKind::Unknown,
+            _ => continue,
+        };
+        let kind = archive.sniff(entry)?;
+        if kind != expected {
+            lints.push(Lint::MismatchedContent {
+                filename: entry.filename().to_owned(),
+                kind,
+            });
+        }
+    }
+    Ok(lints)
+}
+
+fn compress(archive: Vec<u8>, choice: CompressionChoice) -> Result<(Compression, Vec<u8>)> {
+    let formats: &[Compression] = match choice {
+        CompressionChoice::Format(Compression::None) => return Ok((Compression::None, archive)),
+        CompressionChoice::Format(format) => return Ok((format, compress_with(format, &archive)?)),
+        CompressionChoice::Smallest => &[
+            Compression::Lzma,
+            #[cfg(feature = "brotli")]
+            Compression::Brotli,
+        ],
+    };
+    let mut smallest: Option<(Compression, Vec<u8>)> = None;
+    for format in formats {
+        let output = compress_with(*format, &archive)?;
+        let best = smallest.as_ref().map_or(archive.len(), |(_, s)| s.len());
+        if output.len() < best {
+            smallest = Some((*format, output));
+        }
+    }
+    Ok(smallest.unwrap_or((Compression::None, archive)))
+}
+
+fn compress_with(format: Compression, archive: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    crate::compression::compress(format, archive, &mut output)?;
+    Ok(output)
+}
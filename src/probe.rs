@@ -0,0 +1,113 @@
+use crate::{compression, Compression, Error, GmaVersion, Result, IDENT};
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+// how much of the (decompressed) archive is looked at, enough for the header up to the name
+// of almost every archive
+const PROBE_SIZE: u64 = 512;
+
+/// What [`probe`] found out about a stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeInfo {
+    compression: Option<Compression>,
+    version: Option<GmaVersion>,
+    name: Option<String>,
+}
+
+impl ProbeInfo {
+    /// True if the stream starts like a gma archive, plain or compressed
+    pub fn is_gma(&self) -> bool {
+        self.compression.is_some()
+    }
+    /// The compression of the archive, None if the stream is not an archive
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+    /// The version byte of the archive, which might not be a known version.
+    /// None if the stream is not an archive or ends before it
+    pub fn version(&self) -> Option<GmaVersion> {
+        self.version
+    }
+    /// The name in the header, None if it could not be read within the probed bytes or is not
+    /// valid utf8
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Classifies a stream by looking only at its first bytes: whether it is a gma archive, its
+/// compression, version and name. Compressed archives are only decompressed up to the name.
+///
+/// Streams that are not archives are not an error, [`ProbeInfo::is_gma`] returns false for them,
+/// errors only come from the reader. The reader is left where it started
+/// ```no_run
+/// let mut file = std::io::BufReader::new(std::fs::File::open("upload.bin").unwrap());
+/// let info = gma::probe(&mut file).unwrap();
+/// if !info.is_gma() {
+///     println!("rejected");
+/// } else if let Some(name) = info.name() {
+///     println!("received '{}'", name);
+/// }
+/// ```
+pub fn probe<R: BufRead + Seek>(mut reader: R) -> Result<ProbeInfo> {
+    let start = reader.stream_position()?;
+    let result = probe_header(&mut reader);
+    reader.seek(SeekFrom::Start(start))?;
+    let (compression, header) = match result? {
+        Some(found) => found,
+        None => {
+            return Ok(ProbeInfo {
+                compression: None,
+                version: None,
+                name: None,
+            })
+        }
+    };
+    let version = header.get(IDENT.len()).map(|&v| GmaVersion::from(v));
+    Ok(ProbeInfo {
+        compression: Some(compression),
+        version,
+        name: version.and_then(|version| read_name(&header, version)),
+    })
+}
+
+// The first bytes of the archive, decompressed, or None if the stream is not an archive
+fn probe_header<R: BufRead + Seek>(reader: &mut R) -> Result<Option<(Compression, Vec<u8>)>> {
+    let compression = match compression::probe(reader) {
+        Ok(compression) => compression,
+        Err(Error::CompressionError(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut header = Vec::new();
+    match compression {
+        Compression::None => {
+            (&mut *reader).take(PROBE_SIZE).read_to_end(&mut header)?;
+        }
+        Compression::Lzma => {
+            let options = lzma_rs::decompress::Options {
+                unpacked_size: lzma_rs::decompress::UnpackedSize::ReadHeaderButUseProvided(Some(
+                    PROBE_SIZE,
+                )),
+                ..Default::default()
+            };
+            // small archives end before PROBE_SIZE, what was decompressed until then is enough
+            let _ = lzma_rs::lzma_decompress_with_options(reader, &mut header, &options);
+        }
+        #[cfg(feature = "brotli")]
+        Compression::Brotli => {
+            let decompressor = brotli::Decompressor::new(&mut *reader, 4096);
+            let _ = decompressor.take(PROBE_SIZE).read_to_end(&mut header);
+        }
+    }
+    Ok(Some((compression, header)))
+}
+
+fn read_name(header: &[u8], version: GmaVersion) -> Option<String> {
+    // ident, version, steamid and timestamp
+    let mut rest = header.get(IDENT.len() + 1 + 8 + 8..)?;
+    if version.has_required_content() {
+        let end = rest.iter().position(|&b| b == 0)?;
+        rest = &rest[end + 1..];
+    }
+    let end = rest.iter().position(|&b| b == 0)?;
+    String::from_utf8(rest[..end].to_vec()).ok()
+}
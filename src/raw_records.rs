@@ -0,0 +1,105 @@
+use crate::{GMAFile, Result};
+use std::{
+    convert::TryInto,
+    io::{BufRead, Seek},
+};
+
+/// A record of the file table exactly as stored, see [`GMAFile::raw_entry_records`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEntryRecord {
+    offset: u64,
+    raw: Vec<u8>,
+    file_number: u32,
+    filename_len: usize,
+    size: u64,
+    crc: u32,
+}
+
+impl RawEntryRecord {
+    /// The offset where the record starts.
+    ///
+    /// For compressed archives this is an offset into the decompressed data
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// The bytes of the whole record, from the file number to the crc
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+    /// The file number as stored. gmad numbers the files from 1 but the game only checks it isn't 0
+    pub fn file_number(&self) -> u32 {
+        self.file_number
+    }
+    /// The bytes of the filename without the nul terminator, which might not be valid utf8
+    pub fn filename_bytes(&self) -> &[u8] {
+        &self.raw[4..4 + self.filename_len]
+    }
+    /// The size as stored
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// The crc32 as stored
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+}
+
+/// Iterator over the records of a file table, see [`GMAFile::raw_entry_records`]
+#[derive(Debug, Clone)]
+pub struct RawEntryRecords {
+    table: Vec<u8>,
+    table_offset: u64,
+    position: usize,
+}
+
+impl Iterator for RawEntryRecords {
+    type Item = RawEntryRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.position;
+        let rest = self.table.get(start..)?;
+        let file_number = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?);
+        if file_number == 0 {
+            return None;
+        }
+        let filename_len = rest.get(4..)?.iter().position(|b| *b == 0)?;
+        let fields = 4 + filename_len + 1;
+        let size = u64::from_le_bytes(rest.get(fields..fields + 8)?.try_into().ok()?);
+        let crc = u32::from_le_bytes(rest.get(fields + 8..fields + 12)?.try_into().ok()?);
+        let len = fields + 12;
+        self.position += len;
+        Some(RawEntryRecord {
+            offset: self.table_offset + start as u64,
+            raw: rest[..len].to_vec(),
+            file_number,
+            filename_len,
+            size,
+            crc,
+        })
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// The records of the file table exactly as they are stored, in file table order, for tools
+    /// that look for tampering or recover damaged archives. Unlike [`GMAFile::entries`] this keeps
+    /// the file numbers and the raw filename bytes.
+    ///
+    /// The table is read again from the reader, see [`GMAFile::raw_entry_table`]
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// for record in archive.raw_entry_records().unwrap() {
+    ///     println!("{} {:?}", record.file_number(), record.filename_bytes());
+    /// }
+    /// ```
+    pub fn raw_entry_records(&self) -> Result<RawEntryRecords> {
+        Ok(RawEntryRecords {
+            table: self.raw_entry_table()?,
+            table_offset: self.entry_table_range().start,
+            position: 0,
+        })
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    checksum::Crc32Reader, entry_reader::offset_position, Config, Error, FileEntry, GMAFile,
+    LoadOptions, Result,
+};
+use std::{
+    fmt::Debug,
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    sync::Arc,
+};
+
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A random access source of bytes, like a file or an http server supporting range requests.
+///
+/// Archives can be loaded from any `ReadAt` with [`load_read_at`], only the header and the file
+/// table are read when loading and the contents of an entry are only fetched when it is read
+pub trait ReadAt {
+    /// Reads bytes starting at `offset` into `buf` and returns how many were read,
+    /// 0 means `offset` is at or past the end
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+    /// The total size of the source in bytes
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.len() as u64 {
+            return Ok(0);
+        }
+        let available = &self[offset as usize..];
+        let n = buf.len().min(available.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        Ok(n)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_slice().read_at(offset, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+#[cfg(windows)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for std::sync::Arc<T> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+}
+
+/// Buffered [`Read`], [`BufRead`] and [`Seek`] adapter over a [`ReadAt`].
+///
+/// Every time the buffer runs out a single `read_at` call of up to the buffer's capacity is made,
+/// so with remote sources the capacity is roughly the size of each request
+#[derive(Debug)]
+pub struct ReadAtReader<T> {
+    source: T,
+    position: u64,
+    buffer: Vec<u8>,
+    // range of `buffer` that holds the bytes at `position` onwards
+    buffer_start: usize,
+    buffer_end: usize,
+}
+
+impl<T: ReadAt> ReadAtReader<T> {
+    /// Creates a reader with a buffer of 64 KiB
+    pub fn new(source: T) -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_SIZE, source)
+    }
+
+    /// Creates a reader with a buffer of `capacity` bytes
+    pub fn with_capacity(capacity: usize, source: T) -> Self {
+        Self {
+            source,
+            position: 0,
+            buffer: vec![0; capacity.max(1)],
+            buffer_start: 0,
+            buffer_end: 0,
+        }
+    }
+
+    /// The underlying source
+    pub fn get_ref(&self) -> &T {
+        &self.source
+    }
+
+    /// Consumes the reader and returns the underlying source
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+}
+
+impl<T: ReadAt> Read for ReadAtReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // large reads skip the buffer
+        if self.buffer_start == self.buffer_end && buf.len() >= self.buffer.len() {
+            let n = self.source.read_at(self.position, buf)?;
+            self.position += n as u64;
+            return Ok(n);
+        }
+        let available = self.fill_buf()?;
+        let n = buf.len().min(available.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<T: ReadAt> BufRead for ReadAtReader<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.buffer_start == self.buffer_end {
+            self.buffer_end = self.source.read_at(self.position, &mut self.buffer)?;
+            self.buffer_start = 0;
+        }
+        Ok(&self.buffer[self.buffer_start..self.buffer_end])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.buffer_end - self.buffer_start);
+        self.buffer_start += amt;
+        self.position += amt as u64;
+    }
+}
+
+impl<T: ReadAt> Seek for ReadAtReader<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => offset_position(self.source.size()?, offset),
+            SeekFrom::Current(offset) => offset_position(self.position, offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        // keep the buffer if the new position is inside it
+        let buffered_start = self.position - self.buffer_start as u64;
+        let buffered_end = self.position + (self.buffer_end - self.buffer_start) as u64;
+        if position >= buffered_start && position <= buffered_end {
+            self.buffer_start = (position - buffered_start) as usize;
+        } else {
+            self.buffer_start = 0;
+            self.buffer_end = 0;
+        }
+        self.position = position;
+        Ok(position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+/// Loads a gma file from a random access source, see [`ReadAt`]
+pub fn load_read_at<T: ReadAt>(source: T) -> Result<GMAFile<ReadAtReader<T>>> {
+    LoadOptions::new().load_read_at(source)
+}
+
+impl LoadOptions {
+    /// Loads a gma file from a random access source, see [`ReadAt`].
+    ///
+    /// Compressed archives have to be decompressed as a whole so they are read entirely when loaded
+    pub fn load_read_at<T: ReadAt>(&self, source: T) -> Result<GMAFile<ReadAtReader<T>>> {
+        self.load(ReadAtReader::new(source))
+    }
+}
+
+/// Loads a gma file from a random access source that can be read from many threads at once,
+/// see [`GMAFile::read_entry_concurrent`]
+pub fn load_concurrent<T>(source: Arc<T>) -> Result<GMAFile<ReadAtReader<Arc<T>>>>
+where
+    T: ReadAt + Send + Sync + 'static,
+{
+    LoadOptions::new().load_concurrent(source)
+}
+
+/// Opens a file from disk that can be read from many threads at once, using positioned reads
+/// (`pread` on unix and `seek_read` on windows). See [`GMAFile::read_entry_concurrent`]
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// let archive = Arc::new(gma::open_concurrent("addon.gma").unwrap());
+/// let workers: Vec<_> = (0..4)
+///     .map(|worker| {
+///         let archive = archive.clone();
+///         std::thread::spawn(move || {
+///             for entry in archive.entries().skip(worker).step_by(4) {
+///                 archive.read_entry_concurrent(entry, |_, reader| {
+///                     std::io::copy(reader, &mut std::io::sink())
+///                 }).unwrap().unwrap();
+///             }
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// ```
+#[cfg(any(unix, windows))]
+pub fn open_concurrent<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<GMAFile<ReadAtReader<Arc<std::fs::File>>>> {
+    LoadOptions::new().open_concurrent(path)
+}
+
+impl LoadOptions {
+    /// Loads a gma file from a random access source with these options, see [`load_concurrent`]
+    pub fn load_concurrent<T>(&self, source: Arc<T>) -> Result<GMAFile<ReadAtReader<Arc<T>>>>
+    where
+        T: ReadAt + Send + Sync + 'static,
+    {
+        let mut archive = self.load_read_at(source.clone())?;
+        // compressed archives are read from the decompressed copy behind the reader
+        if archive.compression() == crate::Compression::None {
+            archive.set_concurrent(ConcurrentSource(source));
+        }
+        Ok(archive)
+    }
+
+    /// Opens a file from disk with these options, see [`open_concurrent`]
+    #[cfg(any(unix, windows))]
+    pub fn open_concurrent<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<GMAFile<ReadAtReader<Arc<std::fs::File>>>> {
+        self.load_concurrent(Arc::new(std::fs::File::open(path)?))
+    }
+}
+
+// The source of an archive loaded with load_concurrent, shared with its reader
+pub(crate) struct ConcurrentSource(Arc<dyn ReadAt + Send + Sync>);
+
+impl Debug for ConcurrentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ConcurrentSource")
+    }
+}
+
+impl<T: ReadAt> GMAFile<ReadAtReader<Arc<T>>> {
+    /// Same as [`GMAFile::read_entry`] but reads straight from the source with positioned reads
+    /// instead of going through the archive's reader, so many threads can read entries of the
+    /// same archive at once without waiting for each other.
+    ///
+    /// Only archives loaded with [`load_concurrent`] or [`open_concurrent`] that are not
+    /// compressed read concurrently, the others fall back to [`GMAFile::read_entry`]. The entry
+    /// cache is not used, [`LoadOptions::verify_crc`] is honored
+    pub fn read_entry_concurrent<F, R>(&self, entry: &FileEntry, func: F) -> Result<R>
+    where
+        F: FnOnce(&FileEntry, &mut dyn Read) -> R,
+    {
+        let source = match self.concurrent() {
+            Some(ConcurrentSource(source)) => source,
+            None => return self.read_entry(entry, func),
+        };
+        let capacity = entry.size().min(Config::global().buffer_size as u64) as usize;
+        let mut reader = ReadAtReader::with_capacity(capacity, &**source);
+        reader.seek(SeekFrom::Start(self.entry_start(entry)?))?;
+        let mut reader = reader.take(entry.size());
+        if !self.verifies_crc() {
+            return Ok(func(entry, &mut reader));
+        }
+        let mut crc_reader = Crc32Reader::new(reader);
+        let result = func(entry, &mut crc_reader);
+        let found = crc_reader.finish()?;
+        if found != entry.crc() {
+            return Err(Error::CrcMismatch {
+                filename: entry.filename().to_owned(),
+                expected: entry.crc(),
+                found,
+            });
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{
+    addon_metadata::AddonMetadata, checksum::Crc32Hasher, compression, AddonTag, AddonType,
+    BuildReport, Compression, Error, GMABuilder, GmaVersion, Result, IDENT,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::{Cursor, Read, Seek, Write},
+};
+
+/// An entry whose contents were read intact, see [`recover`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredEntry {
+    filename: String,
+    contents: Vec<u8>,
+}
+
+impl RecoveredEntry {
+    /// The filename, with invalid utf8 replaced
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// The contents, which match the crc32 in the file table
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+}
+
+/// Why the contents of an entry could not be recovered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LossReason {
+    /// The archive ends before the end of the contents
+    Truncated,
+    /// The contents don't match the crc32 in the file table
+    CrcMismatch { expected: u32, found: u32 },
+    /// The file table ends before its terminator, so where the contents start is unknown
+    DamagedFileTable,
+}
+
+/// An entry listed in the file table whose contents were lost, see [`recover`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LostEntry {
+    filename: String,
+    reason: LossReason,
+}
+
+impl LostEntry {
+    /// The filename, with invalid utf8 replaced
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// Why the contents were lost
+    pub fn reason(&self) -> &LossReason {
+        &self.reason
+    }
+}
+
+/// Everything [`recover`] could read from a damaged archive.
+///
+/// The header fields are None if the archive ends or is unreadable before them
+#[derive(Debug, Clone, Default)]
+pub struct Recovery {
+    compression: Option<Compression>,
+    version: Option<GmaVersion>,
+    steamid: Option<u64>,
+    timestamp: Option<u64>,
+    name: Option<String>,
+    description: Option<String>,
+    addon_type: Option<AddonType>,
+    addon_tags: Vec<AddonTag>,
+    author: Option<String>,
+    addon_version: Option<u32>,
+    entries: Vec<RecoveredEntry>,
+    lost: Vec<LostEntry>,
+    complete: bool,
+}
+
+impl Recovery {
+    /// The compression of the archive, None if it isn't
+    pub fn compression(&self) -> Compression {
+        self.compression.unwrap_or(Compression::None)
+    }
+    /// The format version
+    pub fn version(&self) -> Option<GmaVersion> {
+        self.version
+    }
+    /// The author's steamid
+    pub fn author_steamid(&self) -> Option<u64> {
+        self.steamid
+    }
+    /// The timestamp of when the archive was created
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+    /// The addon's name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    /// The addon's description
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// The addon's type, if the description is the json gmad writes
+    pub fn addon_type(&self) -> Option<AddonType> {
+        self.addon_type
+    }
+    /// The addon's tags, if the description is the json gmad writes
+    pub fn addon_tags(&self) -> &[AddonTag] {
+        &self.addon_tags
+    }
+    /// The author's name
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    /// The addon version
+    pub fn addon_version(&self) -> Option<u32> {
+        self.addon_version
+    }
+    /// The entries whose contents are intact, in file table order
+    pub fn entries(&self) -> &[RecoveredEntry] {
+        &self.entries
+    }
+    /// The entries listed in the file table whose contents were lost, in file table order
+    pub fn lost(&self) -> &[LostEntry] {
+        &self.lost
+    }
+    /// True if the header and the file table were read and no entry was lost.
+    ///
+    /// Entries whose file table record is unreadable are not listed anywhere, so the archive
+    /// could have had more of them when this is false
+    pub fn is_complete(&self) -> bool {
+        self.complete && self.lost.is_empty()
+    }
+
+    /// A builder with the recovered header fields and entries. The fields that were lost keep
+    /// the builder's defaults, the name is left empty
+    pub fn to_builder(&self) -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name(self.name.clone().unwrap_or_default())
+            .description(self.description.clone().unwrap_or_default());
+        if let Some(version) = self.version.filter(GmaVersion::is_known) {
+            builder.version(version);
+        }
+        if let Some(steamid) = self.steamid {
+            builder.steamid(steamid);
+        }
+        if let Some(timestamp) = self.timestamp {
+            builder.timestamp(timestamp);
+        }
+        if let Some(author) = &self.author {
+            builder.author(author.as_str());
+        }
+        if let Some(addon_version) = self.addon_version {
+            builder.addon_version(addon_version);
+        }
+        if let Some(addon_type) = self.addon_type {
+            builder.addon_type(addon_type);
+        }
+        for tag in self.addon_tags.iter() {
+            builder.addon_tag(*tag);
+        }
+        for entry in self.entries.iter() {
+            builder.file_from_bytes(entry.filename.as_str(), entry.contents.clone());
+        }
+        builder
+    }
+
+    /// Writes a clean, uncompressed archive with the recovered header fields and entries,
+    /// see [`Recovery::to_builder`]
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<BuildReport> {
+        self.to_builder()
+            .write_to_with_report(writer)
+            .map_err(Error::uninterrupted)
+    }
+}
+
+/// Salvages what it can from a damaged archive: the header fields before the damage and the
+/// entries whose contents are intact. Unlike [`crate::load`] this only fails if the data is not
+/// an archive at all, the entire input is read into memory.
+///
+/// The recovered entries can be written to a new archive with [`Recovery::write_to`]
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let damaged = &dummy_buffer[..dummy_buffer.len() - 10];
+/// let recovery = gma::recover(damaged).unwrap();
+/// for lost in recovery.lost() {
+///     println!("lost {} : {:?}", lost.filename(), lost.reason());
+/// }
+/// let mut clean = std::io::Cursor::new(Vec::new());
+/// recovery.write_to(&mut clean).unwrap();
+/// ```
+pub fn recover<R: Read>(mut reader: R) -> Result<Recovery> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let mut recovery = Recovery::default();
+    if !data.starts_with(&IDENT) {
+        let (compression, decompressed) =
+            compression::decompress(&mut Cursor::new(&data)).map_err(|_| Error::InvalidIdent)?;
+        if !decompressed.starts_with(&IDENT) {
+            return Err(Error::InvalidIdent);
+        }
+        recovery.compression = Some(compression);
+        data = decompressed;
+    }
+
+    let mut parser = Parser {
+        data: &data,
+        position: IDENT.len(),
+    };
+    if parser.read_header(&mut recovery).is_none() {
+        return Ok(recovery);
+    }
+    let (records, table_complete) = parser.read_file_table();
+    if !table_complete {
+        recovery.lost = records
+            .into_iter()
+            .map(|record| LostEntry {
+                filename: record.filename,
+                reason: LossReason::DamagedFileTable,
+            })
+            .collect();
+        return Ok(recovery);
+    }
+
+    let mut offset = parser.position as u64;
+    for record in records {
+        let start = offset;
+        offset = offset.saturating_add(record.size);
+        let contents = match data
+            .get(start as usize..)
+            .and_then(|rest| rest.get(..usize::try_from(record.size).ok()?))
+        {
+            Some(contents) => contents,
+            None => {
+                recovery.lost.push(LostEntry {
+                    filename: record.filename,
+                    reason: LossReason::Truncated,
+                });
+                continue;
+            }
+        };
+        let mut hasher = Crc32Hasher::new();
+        hasher.update(contents);
+        let found = hasher.finalize();
+        if found != record.crc {
+            recovery.lost.push(LostEntry {
+                filename: record.filename,
+                reason: LossReason::CrcMismatch {
+                    expected: record.crc,
+                    found,
+                },
+            });
+            continue;
+        }
+        recovery.entries.push(RecoveredEntry {
+            filename: record.filename,
+            contents: contents.to_vec(),
+        });
+    }
+    recovery.complete = true;
+    Ok(recovery)
+}
+
+struct Record {
+    filename: String,
+    size: u64,
+    crc: u32,
+}
+
+// Reads the archive without failing, every read returns None once the data is unreadable
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..)?.get(..len)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)?.try_into().ok().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes(8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+
+    // invalid utf8 is replaced instead of failing
+    fn string(&mut self) -> Option<String> {
+        let rest = self.data.get(self.position..)?;
+        let len = rest.iter().position(|b| *b == 0)?;
+        self.position += len + 1;
+        Some(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+
+    fn read_header(&mut self, recovery: &mut Recovery) -> Option<()> {
+        let version = GmaVersion::from(self.u8()?);
+        recovery.version = Some(version);
+        recovery.steamid = Some(self.u64()?);
+        recovery.timestamp = Some(self.u64()?);
+        if version.has_required_content() {
+            while !self.string()?.is_empty() {}
+        }
+        recovery.name = Some(self.string()?);
+        let metadata = self.string()?;
+        match AddonMetadata::from_json(&metadata) {
+            Some(metadata) => {
+                recovery.description = Some(metadata.get_description().to_owned());
+                recovery.addon_type = metadata.get_type();
+                let (t1, t2) = metadata.get_tags();
+                recovery.addon_tags = t1.into_iter().chain(t2).collect();
+            }
+            None => recovery.description = Some(metadata),
+        }
+        recovery.author = Some(self.string()?);
+        recovery.addon_version = Some(self.u32()?);
+        Some(())
+    }
+
+    // The records read and whether the terminator was found
+    fn read_file_table(&mut self) -> (Vec<Record>, bool) {
+        let mut records = Vec::new();
+        loop {
+            match self.u32() {
+                Some(0) => return (records, true),
+                Some(_) => {}
+                None => return (records, false),
+            }
+            let record = (|| {
+                Some(Record {
+                    filename: self.string()?,
+                    size: self.u64()?,
+                    crc: self.u32()?,
+                })
+            })();
+            match record {
+                Some(record) => records.push(record),
+                None => return (records, false),
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{FileEntry, GMAFile, Result};
+use std::io::{BufRead, Seek};
+
+// splitmix64, small and good enough to pick entries, the sequence must never change for a seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in 0..bound, bound must not be 0
+    fn below(&mut self, bound: usize) -> usize {
+        let bound = bound as u64;
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next();
+            if value < zone {
+                return (value % bound) as usize;
+            }
+        }
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// A random subset of `n` entries, or every entry if there are fewer, in file table order.
+    ///
+    /// The same `seed` always picks the same entries for the same archive, so an audit can be
+    /// repeated. See [`GMAFile::verify_sample`] to check their crc32
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// for entry in archive.sample_entries(10, 42) {
+    ///     println!("{}", entry.filename());
+    /// }
+    /// ```
+    pub fn sample_entries(&self, n: usize, seed: u64) -> Vec<&FileEntry> {
+        let entries: Vec<&FileEntry> = self.entries().collect();
+        let n = n.min(entries.len());
+        let mut indices: Vec<usize> = (0..entries.len()).collect();
+        let mut rng = SplitMix64(seed);
+        // partial fisher-yates, the first n indices end up as the sample
+        for i in 0..n {
+            let j = i + rng.below(indices.len() - i);
+            indices.swap(i, j);
+        }
+        indices.truncate(n);
+        indices.sort_unstable();
+        indices.into_iter().map(|i| entries[i]).collect()
+    }
+
+    /// Reads the entries picked by [`GMAFile::sample_entries`] and checks their crc32, to spot
+    /// check large archives without reading all of them.
+    ///
+    /// Fails with [`crate::Error::CrcMismatch`] for the first entry that doesn't match
+    pub fn verify_sample(&self, n: usize, seed: u64) -> Result<()> {
+        for entry in self.sample_entries(n, seed) {
+            self.read_entry_verified(entry, |_, reader| {
+                std::io::copy(reader, &mut std::io::sink())
+            })??;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitMix64;
+
+    #[test]
+    fn below_stays_in_bounds() {
+        let mut rng = SplitMix64(7);
+        for bound in 1..100 {
+            assert!(rng.below(bound) < bound);
+        }
+        // the sequence is part of the api, samples must not change between versions
+        assert_eq!(SplitMix64(0).next(), 0xe220_a839_7b1d_cdaf);
+    }
+}
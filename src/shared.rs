@@ -0,0 +1,91 @@
+use crate::{
+    compression, gma_reader::GMAFileReader, Compression, FileEntry, GMAFile, LoadOptions, Result,
+    IDENT,
+};
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, Cursor, Read, Seek, SeekFrom},
+    sync::Arc,
+};
+
+/// The reader of archives loaded with [`load_shared`], a cursor over the shared buffer
+#[derive(Debug, Clone)]
+pub struct SharedCursor(Cursor<Arc<[u8]>>);
+
+impl Read for SharedCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for SharedCursor {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl Seek for SharedCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+/// Loads an archive from a buffer that can be shared with other archives and threads.
+///
+/// The returned archive is `Send + Sync` and [`GMAFile::entry_bytes`] borrows the contents of
+/// an entry from the buffer without copying or locking, so one archive in memory can serve many
+/// concurrent requests. Compressed archives are decompressed into a new buffer
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// use std::sync::Arc;
+///
+/// let archive = Arc::new(gma::load_shared(Arc::from(dummy_buffer)).unwrap());
+/// let worker = {
+///     let archive = archive.clone();
+///     std::thread::spawn(move || {
+///         let entry = archive.entries().next().unwrap();
+///         archive.entry_bytes(entry).unwrap().len()
+///     })
+/// };
+/// assert_eq!(worker.join().unwrap() as u64, archive.entries().next().unwrap().size());
+/// ```
+pub fn load_shared(buffer: Arc<[u8]>) -> Result<GMAFile<SharedCursor>> {
+    LoadOptions::new().load_shared(buffer)
+}
+
+impl LoadOptions {
+    /// Loads an archive from a shared buffer with these options, see [`load_shared`]
+    pub fn load_shared(&self, buffer: Arc<[u8]>) -> Result<GMAFile<SharedCursor>> {
+        let (format, buffer) = match buffer.starts_with(&IDENT) {
+            true => (Compression::None, buffer),
+            false => {
+                let (format, data) = compression::decompress_limited(
+                    &mut Cursor::new(&buffer[..]),
+                    self.max_decompressed_size.unwrap_or(u64::MAX),
+                )?;
+                (format, Arc::from(data))
+            }
+        };
+        let reader = SharedCursor(Cursor::new(buffer.clone()));
+        let mut archive =
+            GMAFileReader::with_compression(reader, format, self.clone())?.read_gma()?;
+        archive.set_shared(buffer);
+        Ok(archive)
+    }
+}
+
+impl GMAFile<SharedCursor> {
+    /// The contents of `entry`, borrowed from the shared buffer. Unlike [`GMAFile::read_entry`]
+    /// this doesn't use the reader, so it can be called from many threads at once.
+    ///
+    /// Returns None if `entry` ends past the end of the buffer, like entries of another archive
+    pub fn entry_bytes(&self, entry: &FileEntry) -> Option<&[u8]> {
+        let buffer = self.shared()?;
+        let start = usize::try_from(entry.absolute_offset()).ok()?;
+        let len = usize::try_from(entry.size()).ok()?;
+        buffer.get(start..start.checked_add(len)?)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{metadata::StableHasher, ArchiveMetadata, EntryMetadata, GMAFile, Result};
+use std::{
+    io::{BufRead, Seek},
+    sync::Arc,
+};
+
+/// An immutable copy of an archive's header and file table, see [`GMAFile::snapshot`].
+///
+/// Cloning is cheap, every clone shares the same data, and a snapshot doesn't hold on to the
+/// reader so it stays valid no matter what happens to the file it was taken from
+#[derive(Debug, Clone)]
+pub struct ArchiveSnapshot {
+    metadata: Arc<ArchiveMetadata>,
+    generation: u64,
+}
+
+impl ArchiveSnapshot {
+    /// The header fields and the file table at the time of the snapshot
+    pub fn metadata(&self) -> &ArchiveMetadata {
+        &self.metadata
+    }
+    /// The entries, in archive order
+    pub fn entries(&self) -> &[EntryMetadata] {
+        &self.metadata.entries
+    }
+    /// The first entry with the given filename, compared exactly
+    pub fn get(&self, filename: &str) -> Option<&EntryMetadata> {
+        self.entries().iter().find(|e| e.filename == filename)
+    }
+    /// Identifies the bytes the snapshot was taken from, a hash of the raw header, the raw file
+    /// table and the length of the stream.
+    ///
+    /// Two snapshots with a different generation were taken from different versions of the
+    /// archive. The value is only meant to be compared with other generations, it is not stable
+    /// across versions of this crate
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Checks whether `archive` still reads the same bytes as when the snapshot was taken.
+    ///
+    /// Offsets in [`ArchiveSnapshot::entries`] can only be trusted to read contents from
+    /// `archive` while this returns true, a false means the file was rewritten underneath and
+    /// should be loaded again.
+    /// This reads the header and the file table
+    pub fn is_current<ReaderType>(&self, archive: &GMAFile<ReaderType>) -> Result<bool>
+    where
+        ReaderType: BufRead + Seek,
+    {
+        Ok(archive.generation()? == self.generation)
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Takes an [`ArchiveSnapshot`] of the header and the file table, tagged with the generation
+    /// of the bytes currently behind the reader.
+    ///
+    /// Tools that serve an archive while another process rewrites it can hand out the snapshot
+    /// instead of the archive, readers keep a consistent view of the entries and
+    /// [`ArchiveSnapshot::is_current`] tells when it has gone stale.
+    /// This reads the header and the file table
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// let snapshot = archive.snapshot().unwrap();
+    /// let shared = snapshot.clone();
+    /// std::thread::spawn(move || println!("{} entries", shared.entries().len()));
+    /// assert!(snapshot.is_current(&archive).unwrap());
+    /// ```
+    pub fn snapshot(&self) -> Result<ArchiveSnapshot> {
+        Ok(ArchiveSnapshot {
+            generation: self.generation()?,
+            metadata: Arc::new(self.metadata()),
+        })
+    }
+
+    fn generation(&self) -> Result<u64> {
+        let mut hasher = StableHasher::new();
+        hasher.write(&self.raw_header()?);
+        hasher.write(&self.raw_entry_table()?);
+        hasher.write_u64(self.stream_len()?);
+        Ok(hasher.finish())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    addon_json::AddonJson, whitelist, wildcard, Config, Error, GMABuilder, GMAFile, LoadOptions,
+    Result,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Where an addon comes from.
+///
+/// [`AddonSource::load`] turns any of them into a [`GMAFile`] with the same type
+pub enum AddonSource {
+    /// A .gma file on disk
+    LocalFile(PathBuf),
+    /// The bytes of a .gma file
+    Memory(Vec<u8>),
+    /// An addon folder, packed the same way gmad does it.
+    ///
+    /// The title, description, type, tags and ignore list are read from `addon.json`, which like
+    /// for gmad must exist and have a known type, otherwise loading fails with
+    /// [`Error::InvalidAddonType`]. Files outside the whitelist are left out, like gmad does with
+    /// `-warninvalid`
+    Folder(PathBuf),
+    /// Any other reader, like a network stream. The contents are read into memory
+    Reader(Box<dyn Read>),
+    /// An item published on the workshop, given as its id or the url of its page, like
+    /// `https://steamcommunity.com/sharedfiles/filedetails/?id=104691717`.
+    ///
+    /// The crate has no http client, `fetch` is called with the workshop id and returns the .gma
+    /// file, like a download stream from the Web API. The contents are read into memory.
+    /// Fails with [`Error::InvalidWorkshopItem`] if `item` is not an id or an item's url
+    Remote {
+        item: String,
+        fetch: Box<dyn FnOnce(u64) -> std::io::Result<Box<dyn Read>>>,
+    },
+}
+
+impl AddonSource {
+    /// Loads the addon with the default [`LoadOptions`]
+    pub fn load(self) -> Result<GMAFile<SourceReader>> {
+        self.load_with(&LoadOptions::new())
+    }
+
+    /// Loads the addon with the given options
+    pub fn load_with(self, options: &LoadOptions) -> Result<GMAFile<SourceReader>> {
+        let reader = match self {
+            Self::LocalFile(path) => SourceReader::File(BufReader::with_capacity(
+                Config::global().buffer_size,
+                File::open(path)?,
+            )),
+            Self::Memory(bytes) => SourceReader::Memory(Cursor::new(bytes)),
+            Self::Folder(path) => SourceReader::Memory(Cursor::new(pack_folder(&path)?)),
+            Self::Reader(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                SourceReader::Memory(Cursor::new(bytes))
+            }
+            Self::Remote { item, fetch } => {
+                let id = workshop_id(&item).ok_or(Error::InvalidWorkshopItem(item))?;
+                let mut bytes = Vec::new();
+                fetch(id)?.read_to_end(&mut bytes)?;
+                SourceReader::Memory(Cursor::new(bytes))
+            }
+        };
+        options.load(reader)
+    }
+}
+
+// The id in a workshop id or in the url of an item's page
+fn workshop_id(item: &str) -> Option<u64> {
+    let item = item.trim();
+    if let Ok(id) = item.parse() {
+        return Some(id);
+    }
+    let (_, query) = item.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("id="))
+        .and_then(|id| id.parse().ok())
+}
+
+fn pack_folder(root: &Path) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    folder_builder(root, true)?.write_to(Cursor::new(&mut buffer))?;
+    Ok(buffer)
+}
+
+/// A builder with the metadata and files of an addon folder, the same way gmad packs it.
+/// Files outside the whitelist are only added if `whitelisted_only` is false
+pub(crate) fn folder_builder(root: &Path, whitelisted_only: bool) -> Result<GMABuilder> {
+    let addon_json = AddonJson::from_path(&root.join("addon.json"))?;
+    let addon_type = addon_json
+        .addon_type()
+        .ok_or_else(|| Error::InvalidAddonType(addon_json.addon_type.clone()))?;
+
+    let mut builder = GMABuilder::new();
+    builder
+        .name(addon_json.title.as_str())
+        .description(addon_json.description.as_str())
+        .addon_type(addon_type);
+    for tag in addon_json.tags() {
+        builder.addon_tag(tag);
+    }
+
+    let filter_root = root.to_path_buf();
+    let ignore = addon_json.ignore;
+    builder.path_filter(move |path| {
+        let relative = path
+            .strip_prefix(&filter_root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        relative != "addon.json"
+            && (!whitelisted_only || whitelist::is_whitelisted(&relative))
+            && !ignore.iter().any(|p| wildcard::matches(p, &relative))
+    });
+    builder.files_from_glob(root, &["*"])?;
+    Ok(builder)
+}
+
+/// The reader used by archives loaded from an [`AddonSource`]
+#[derive(Debug)]
+pub enum SourceReader {
+    File(BufReader<File>),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl Read for SourceReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(r) => r.read(buf),
+            Self::Memory(r) => r.read(buf),
+        }
+    }
+}
+
+impl BufRead for SourceReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            Self::File(r) => r.fill_buf(),
+            Self::Memory(r) => r.fill_buf(),
+        }
+    }
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::File(r) => r.consume(amt),
+            Self::Memory(r) => r.consume(amt),
+        }
+    }
+}
+
+impl Seek for SourceReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Self::File(r) => r.seek(pos),
+            Self::Memory(r) => r.seek(pos),
+        }
+    }
+}
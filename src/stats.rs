@@ -0,0 +1,140 @@
+use crate::{FileEntry, GMAFile};
+use std::io::{BufRead, Seek};
+
+const LARGEST_ENTRIES: usize = 10;
+
+/// The categories used by [`GMAFile::stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentCategory {
+    Lua,
+    Models,
+    Materials,
+    Sounds,
+    Maps,
+    Other,
+}
+
+impl ContentCategory {
+    /// Every category, in the order used by [`ArchiveStats::categories`]
+    pub const ALL: [ContentCategory; 6] = [
+        ContentCategory::Lua,
+        ContentCategory::Models,
+        ContentCategory::Materials,
+        ContentCategory::Sounds,
+        ContentCategory::Maps,
+        ContentCategory::Other,
+    ];
+
+    /// The category of an entry, based on its extension and top level directory.
+    /// Content inside `gamemodes/<name>/content/` is categorized the same way as top level content
+    pub fn of(entry: &FileEntry) -> Self {
+        if entry
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lua"))
+        {
+            return ContentCategory::Lua;
+        }
+        let components: Vec<&str> = entry.components().collect();
+        let directory = match components.as_slice() {
+            [gamemodes, _, content, directory, ..]
+                if gamemodes.eq_ignore_ascii_case("gamemodes")
+                    && content.eq_ignore_ascii_case("content") =>
+            {
+                directory
+            }
+            [directory, _, ..] => directory,
+            _ => return ContentCategory::Other,
+        };
+        match directory.to_lowercase().as_str() {
+            "models" => ContentCategory::Models,
+            "materials" => ContentCategory::Materials,
+            "sound" => ContentCategory::Sounds,
+            "maps" => ContentCategory::Maps,
+            _ => ContentCategory::Other,
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&c| c == self).unwrap_or(0)
+    }
+}
+
+/// Number of entries and their combined size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryStats {
+    count: usize,
+    total_size: u64,
+}
+
+impl CategoryStats {
+    /// The number of entries
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// The combined size of the entries
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+}
+
+/// Summary returned by [`GMAFile::stats`]
+#[derive(Debug, Clone)]
+pub struct ArchiveStats<'a> {
+    categories: [CategoryStats; 6],
+    largest: Vec<&'a FileEntry>,
+    total: CategoryStats,
+}
+
+impl<'a> ArchiveStats<'a> {
+    /// The stats of a single category
+    pub fn category(&self, category: ContentCategory) -> CategoryStats {
+        self.categories[category.index()]
+    }
+    /// The stats of every category, in the same order as [`ContentCategory::ALL`]
+    pub fn categories(&self) -> impl Iterator<Item = (ContentCategory, CategoryStats)> + '_ {
+        ContentCategory::ALL
+            .iter()
+            .copied()
+            .zip(self.categories.iter().copied())
+    }
+    /// The largest entries, biggest first. At most 10 are kept
+    pub fn largest_entries(&self) -> &[&'a FileEntry] {
+        &self.largest
+    }
+    /// The number of entries in the archive
+    pub fn entry_count(&self) -> usize {
+        self.total.count
+    }
+    /// The combined uncompressed size of every entry
+    pub fn total_size(&self) -> u64 {
+        self.total.total_size
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Counts and sizes of the entries per [`ContentCategory`], the largest entries and the total size
+    pub fn stats(&self) -> ArchiveStats<'_> {
+        let mut stats = ArchiveStats {
+            categories: [CategoryStats::default(); 6],
+            largest: Vec::new(),
+            total: CategoryStats::default(),
+        };
+        for entry in self.entries() {
+            for category_stats in [
+                &mut stats.categories[ContentCategory::of(entry).index()],
+                &mut stats.total,
+            ] {
+                category_stats.count += 1;
+                category_stats.total_size += entry.size();
+            }
+        }
+        let mut largest: Vec<&FileEntry> = self.entries().collect();
+        largest.sort_by_key(|entry| std::cmp::Reverse(entry.size()));
+        largest.truncate(LARGEST_ENTRIES);
+        stats.largest = largest;
+        stats
+    }
+}
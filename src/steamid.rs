@@ -0,0 +1,126 @@
+use crate::Error;
+use std::{fmt::Display, str::FromStr};
+
+// universe public, account type individual, desktop instance
+const INDIVIDUAL_BASE: u64 = 0x0110_0001_0000_0000;
+
+/// A 64 bit steam id, with conversions to the other common text formats.
+///
+/// ```
+/// let steamid: gma::SteamID = "STEAM_0:1:11301".parse().unwrap();
+/// assert_eq!(steamid.as_u64(), 76561197960288331);
+/// assert_eq!(steamid.to_steam3(), "[U:1:22603]");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SteamID(u64);
+
+impl SteamID {
+    /// Creates a steam id from its 64 bit representation
+    pub fn new(steamid64: u64) -> Self {
+        Self(steamid64)
+    }
+    /// Creates the steam id of an individual account from its 32 bit account id
+    pub fn from_account_id(account_id: u32) -> Self {
+        Self(INDIVIDUAL_BASE + account_id as u64)
+    }
+    /// The 64 bit representation. Ex : 76561197960288331
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+    /// The lower 32 bits, the account id
+    pub fn account_id(&self) -> u32 {
+        self.0 as u32
+    }
+    /// The legacy text format. Ex : STEAM_0:1:11301
+    pub fn to_steam2(&self) -> String {
+        let account_id = self.account_id();
+        format!("STEAM_0:{}:{}", account_id & 1, account_id >> 1)
+    }
+    /// The modern text format. Ex : [U:1:22603]
+    pub fn to_steam3(&self) -> String {
+        format!("[U:1:{}]", self.account_id())
+    }
+}
+
+impl From<u64> for SteamID {
+    fn from(steamid64: u64) -> Self {
+        Self(steamid64)
+    }
+}
+
+impl From<SteamID> for u64 {
+    fn from(steamid: SteamID) -> Self {
+        steamid.0
+    }
+}
+
+impl PartialEq<u64> for SteamID {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Displays the 64 bit representation
+impl Display for SteamID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Parses a steam id in the SteamID64, `STEAM_X:Y:Z` or `[U:1:Z]` format
+impl FromStr for SteamID {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidSteamID(s.to_owned());
+        let s = s.trim();
+        if let Some(rest) = s.strip_prefix("STEAM_") {
+            let parts: Vec<&str> = rest.split(':').collect();
+            return match parts.as_slice() {
+                [_universe, y, z] => {
+                    let y: u32 = y.parse().map_err(|_| invalid())?;
+                    let z: u32 = z.parse().map_err(|_| invalid())?;
+                    if y > 1 || z > u32::MAX >> 1 {
+                        return Err(invalid());
+                    }
+                    Ok(Self::from_account_id(z << 1 | y))
+                }
+                _ => Err(invalid()),
+            };
+        }
+        if let Some(rest) = s.strip_prefix("[U:1:").and_then(|r| r.strip_suffix(']')) {
+            return rest
+                .parse()
+                .map(Self::from_account_id)
+                .map_err(|_| invalid());
+        }
+        s.parse().map(Self).map_err(|_| invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let steamid = SteamID::new(76561197960288331);
+        assert_eq!(steamid.account_id(), 22603);
+        assert_eq!(steamid.to_steam2(), "STEAM_0:1:11301");
+        assert_eq!(steamid.to_steam3(), "[U:1:22603]");
+        assert_eq!(steamid.to_string(), "76561197960288331");
+    }
+
+    #[test]
+    fn parse() {
+        let expected = SteamID::new(76561197960288331);
+        assert_eq!("76561197960288331".parse::<SteamID>().unwrap(), expected);
+        assert_eq!("STEAM_0:1:11301".parse::<SteamID>().unwrap(), expected);
+        assert_eq!("STEAM_1:1:11301".parse::<SteamID>().unwrap(), expected);
+        assert_eq!("[U:1:22603]".parse::<SteamID>().unwrap(), expected);
+        assert!("STEAM_0:2:11301".parse::<SteamID>().is_err());
+        assert!("[U:1:abc]".parse::<SteamID>().is_err());
+        assert!("steam".parse::<SteamID>().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{BuildReport, Error, GMABuilder, Result};
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// A writer that can also seek, implemented for every type that is both
+pub trait WriteSeek: Write + Seek {}
+
+impl<T: Write + Seek> WriteSeek for T {}
+
+/// Writes the same bytes to many writers, seeking them together.
+///
+/// Positions are the ones of the first writer, the others can start at a different position,
+/// seeks are moved by the difference so every writer ends up with the same bytes after where it
+/// started. Any error is returned as is and leaves the writers in an unknown state
+/// ```
+/// use std::io::{Cursor, Write};
+///
+/// let mut local = Cursor::new(Vec::new());
+/// let mut upload = Cursor::new(b"header".to_vec());
+/// upload.set_position(6);
+/// let mut tee = gma::TeeWriter::new(vec![&mut local, &mut upload]).unwrap();
+/// tee.write_all(b"contents").unwrap();
+/// drop(tee);
+/// assert_eq!(local.get_ref(), b"contents");
+/// assert_eq!(upload.get_ref(), b"headercontents");
+/// ```
+pub struct TeeWriter<'a> {
+    writers: Vec<&'a mut dyn WriteSeek>,
+    // the position of every writer when the tee was created
+    starts: Vec<u64>,
+}
+
+impl std::fmt::Debug for TeeWriter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TeeWriter")
+            .field("writers", &self.writers.len())
+            .field("starts", &self.starts)
+            .finish()
+    }
+}
+
+impl<'a> TeeWriter<'a> {
+    /// Creates a tee over `writers`, each one keeps writing from its current position
+    pub fn new(mut writers: Vec<&'a mut dyn WriteSeek>) -> io::Result<Self> {
+        let starts = writers
+            .iter_mut()
+            .map(|writer| writer.stream_position())
+            .collect::<io::Result<Vec<u64>>>()?;
+        Ok(Self { writers, starts })
+    }
+}
+
+impl Write for TeeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for writer in self.writers.iter_mut() {
+            writer.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for writer in self.writers.iter_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Seek for TeeWriter<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let first = match (self.writers.first_mut(), self.starts.first()) {
+            (Some(writer), Some(&start)) => (writer.seek(pos)?, start),
+            _ => return Ok(0),
+        };
+        let (position, first_start) = first;
+        let relative = position.checked_sub(first_start).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to before the position the writers started at",
+            )
+        })?;
+        for (writer, start) in self.writers.iter_mut().zip(&self.starts).skip(1) {
+            writer.seek(SeekFrom::Start(start + relative))?;
+        }
+        Ok(position)
+    }
+}
+
+impl GMABuilder {
+    /// Consumes the builder and writes the same archive to every writer in a single pass, like
+    /// a local file and an upload buffer, so the files are only read and hashed once.
+    /// See [`TeeWriter`]
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// let mut local = Cursor::new(Vec::new());
+    /// let mut upload = Cursor::new(Vec::new());
+    /// let mut builder = gma::GMABuilder::new();
+    /// builder.name("tee").file_from_bytes("lua/a.lua", b"print('a')".to_vec());
+    /// builder.write_to_multi(&mut [&mut local, &mut upload]).unwrap();
+    /// assert_eq!(local.get_ref(), upload.get_ref());
+    /// ```
+    pub fn write_to_multi(self, writers: &mut [&mut dyn WriteSeek]) -> Result<BuildReport> {
+        let writers = writers
+            .iter_mut()
+            .map(|writer| &mut **writer as &mut dyn WriteSeek)
+            .collect();
+        // a single token can't resume writers that failed at different positions
+        self.write_to_with_report(TeeWriter::new(writers)?)
+            .map_err(Error::uninterrupted)
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{FileEntry, GMAFile, Result};
+use std::io::{BufRead, Seek};
+
+/// The encoding [`GMAFile::read_entry_text`] decoded an entry with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// The windows ansi code page used by older windows editors, the fallback for entries that
+    /// are not valid utf8
+    Windows1252,
+}
+
+/// The contents of an entry decoded as text, see [`GMAFile::read_entry_text`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryText {
+    text: String,
+    encoding: TextEncoding,
+    lossy: bool,
+}
+
+impl EntryText {
+    /// The decoded text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Consumes this and returns the decoded text
+    pub fn into_text(self) -> String {
+        self.text
+    }
+    /// The encoding the contents were decoded with
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+    /// True if some bytes could not be decoded and were replaced with U+FFFD, so writing the
+    /// text back would not give the original contents
+    pub fn is_lossy(&self) -> bool {
+        self.lossy
+    }
+}
+
+// The chars of the bytes 0x80 to 0x9F, the rest of windows-1252 matches latin1.
+// None for the 5 bytes the code page leaves undefined
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+// Decodes windows-1252, returns true if undefined bytes were replaced
+pub(crate) fn decode_windows1252(bytes: &[u8]) -> (String, bool) {
+    let mut lossy = false;
+    let text = bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize].unwrap_or_else(|| {
+                lossy = true;
+                char::REPLACEMENT_CHARACTER
+            }),
+            b => b as char,
+        })
+        .collect();
+    (text, lossy)
+}
+
+// Decodes utf8, falling back to windows-1252
+pub(crate) fn decode_text(bytes: Vec<u8>) -> EntryText {
+    match String::from_utf8(bytes) {
+        Ok(text) => EntryText {
+            text,
+            encoding: TextEncoding::Utf8,
+            lossy: false,
+        },
+        Err(e) => {
+            let (text, lossy) = decode_windows1252(e.as_bytes());
+            EntryText {
+                text,
+                encoding: TextEncoding::Windows1252,
+                lossy,
+            }
+        }
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Reads the contents of an entry as text. Entries that are not valid utf8 are decoded as
+    /// windows-1252, which is what most older lua files that fail as utf8 were saved with.
+    ///
+    /// Windows-1252 leaves 5 byte values undefined, they are replaced with U+FFFD and the
+    /// result is flagged with [`EntryText::is_lossy`]
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// let entry = archive.entries().next().unwrap();
+    /// let text = archive.read_entry_text(entry).unwrap();
+    /// if text.encoding() != gma::TextEncoding::Utf8 {
+    ///     println!("{} is not utf8", entry.filename());
+    /// }
+    /// ```
+    pub fn read_entry_text(&self, entry: &FileEntry) -> Result<EntryText> {
+        let contents = self.try_read_entry(entry, |entry, reader| {
+            let mut contents = Vec::with_capacity(self.contents_capacity(entry));
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })?;
+        Ok(decode_text(contents))
+    }
+}
@@ -0,0 +1,53 @@
+/// A single field read while parsing an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceField {
+    pub(crate) name: String,
+    pub(crate) offset: u64,
+    pub(crate) raw: Vec<u8>,
+    pub(crate) value: Option<String>,
+}
+
+impl TraceField {
+    /// The name of the field. Ex : name, entries[3].filename
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The offset where the field starts.
+    ///
+    /// For compressed archives this is an offset into the decompressed data
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// The bytes that were consumed while reading the field
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+    /// The decoded value in its debug representation, None if reading the field failed
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// Every field read while parsing an archive, in the order they were read.
+///
+/// Only recorded when [`crate::LoadOptions::trace`] is enabled, see [`crate::GMAFile::parse_trace`]
+/// and [`crate::Error::trace`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTrace {
+    pub(crate) fields: Vec<TraceField>,
+}
+
+impl ParseTrace {
+    /// The recorded fields
+    pub fn fields(&self) -> &[TraceField] {
+        &self.fields
+    }
+    /// The first recorded field with the given name
+    pub fn field(&self, name: &str) -> Option<&TraceField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+    /// The last recorded field, when parsing fails this is usually the field that failed
+    pub fn last(&self) -> Option<&TraceField> {
+        self.fields.last()
+    }
+}
@@ -0,0 +1,511 @@
+//! Rewriting existing archives into new ones
+use crate::{GMABuilder, GMAFile, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{BufRead, Seek},
+    ops::Range,
+};
+
+/// Decides the new filename of each entry for [`rename_entries`].
+///
+/// Implemented for closures `FnMut(&str) -> Option<String>` and for maps from prefixes to their
+/// replacements. With maps the longest prefix matching the filename is replaced, a key can be a
+/// directory like `materials/old/` or a complete filename
+pub trait Renamer {
+    /// The new filename for `filename`, None keeps it unchanged
+    fn rename(&mut self, filename: &str) -> Option<String>;
+}
+
+impl<F> Renamer for F
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    fn rename(&mut self, filename: &str) -> Option<String> {
+        self(filename)
+    }
+}
+
+fn rename_prefix<'a, I>(prefixes: I, filename: &str) -> Option<String>
+where
+    I: Iterator<Item = (&'a String, &'a String)>,
+{
+    prefixes
+        .filter(|(prefix, _)| filename.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(prefix, replacement)| format!("{}{}", replacement, &filename[prefix.len()..]))
+}
+
+impl Renamer for HashMap<String, String> {
+    fn rename(&mut self, filename: &str) -> Option<String> {
+        rename_prefix(self.iter(), filename)
+    }
+}
+
+impl Renamer for BTreeMap<String, String> {
+    fn rename(&mut self, filename: &str) -> Option<String> {
+        rename_prefix(self.iter(), filename)
+    }
+}
+
+/// What to do with lua string literals that reference a renamed entry, see
+/// [`RenameOptions::lua_references`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LuaReferences {
+    /// Lua files are copied untouched
+    Ignore,
+    /// References are listed in the [`RenameReport`] but not changed
+    Report,
+    /// References are replaced with the new path and listed in the [`RenameReport`]
+    Rewrite,
+}
+
+/// Options for [`rename_entries_with`]
+#[derive(Debug, Clone)]
+pub struct RenameOptions {
+    lua_references: LuaReferences,
+}
+
+impl Default for RenameOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenameOptions {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self {
+            lua_references: LuaReferences::Ignore,
+        }
+    }
+
+    /// Scans the string literals of every lua file for paths of renamed entries.
+    ///
+    /// A literal matches, ignoring case, if it is the full old filename or the filename without
+    /// its first directory, like the `include("myaddon/init.lua")` path of `lua/myaddon/init.lua`.
+    /// Materials also match without their extension, like `Material("myaddon/icon")`.
+    /// Forms without a directory, like `"init.lua"`, are too likely to be something else and
+    /// never match. Default : [`LuaReferences::Ignore`]
+    pub fn lua_references(&mut self, references: LuaReferences) -> &mut Self {
+        self.lua_references = references;
+        self
+    }
+}
+
+/// A lua string literal referencing a renamed entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaReference {
+    file: String,
+    line: usize,
+    literal: String,
+    replacement: String,
+    rewritten: bool,
+}
+
+impl LuaReference {
+    /// The lua file containing the reference, with its new name
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// The line of the reference, starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// The contents of the string literal
+    pub fn literal(&self) -> &str {
+        &self.literal
+    }
+    /// The equivalent reference to the renamed entry
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+    /// True if the literal was replaced with [`LuaReference::replacement`]
+    pub fn rewritten(&self) -> bool {
+        self.rewritten
+    }
+}
+
+/// What [`rename_entries_with`] found
+#[derive(Debug, Clone, Default)]
+pub struct RenameReport {
+    lua_references: Vec<LuaReference>,
+}
+
+impl RenameReport {
+    /// The lua references to renamed entries, in archive order
+    pub fn lua_references(&self) -> &[LuaReference] {
+        &self.lua_references
+    }
+}
+
+/// A builder with the same header fields as `archive` and no files
+pub(crate) fn header_builder<ReaderType>(archive: &GMAFile<ReaderType>) -> GMABuilder
+where
+    ReaderType: BufRead + Seek,
+{
+    let mut builder = GMABuilder::new();
+    builder
+        .version(archive.version())
+        .steamid(archive.author_steamid())
+        .timestamp(archive.timestamp())
+        .name(archive.name())
+        .description(archive.description())
+        .author(archive.author())
+        .addon_version(archive.addon_version())
+        .raw_metadata(archive.metadata_json());
+    if let Some(addon_type) = archive.addon_type() {
+        builder.addon_type(addon_type);
+    }
+    for tag in archive.addon_tags() {
+        builder.addon_tag(*tag);
+    }
+    builder
+}
+
+/// Copies `archive` into a new builder, renaming the entries with `renamer`.
+///
+/// The header fields and the order of the entries are preserved, the contents are buffered in
+/// memory. More files can be added to the returned builder before writing it
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let archive = gma::load_from_memory(&dummy_buffer).unwrap();
+/// let mut renames = std::collections::HashMap::new();
+/// renames.insert("lua/".to_owned(), "lua/myaddon/".to_owned());
+/// let builder = gma::transform::rename_entries(&archive, renames).unwrap();
+/// ```
+pub fn rename_entries<ReaderType, R>(
+    archive: &GMAFile<ReaderType>,
+    renamer: R,
+) -> Result<GMABuilder>
+where
+    ReaderType: BufRead + Seek,
+    R: Renamer,
+{
+    rename_entries_with(archive, renamer, &RenameOptions::new()).map(|(builder, _)| builder)
+}
+
+/// Same as [`rename_entries`] but lua files can also be checked for references to the old paths,
+/// see [`RenameOptions::lua_references`]
+pub fn rename_entries_with<ReaderType, R>(
+    archive: &GMAFile<ReaderType>,
+    mut renamer: R,
+    options: &RenameOptions,
+) -> Result<(GMABuilder, RenameReport)>
+where
+    ReaderType: BufRead + Seek,
+    R: Renamer,
+{
+    let filenames: Vec<String> = archive
+        .entry_views()
+        .map(|entry| {
+            renamer
+                .rename(entry.filename())
+                .unwrap_or_else(|| entry.filename().to_owned())
+        })
+        .collect();
+    let references = match options.lua_references {
+        LuaReferences::Ignore => HashMap::new(),
+        _ => reference_forms(
+            archive
+                .entry_views()
+                .map(|e| e.filename())
+                .zip(filenames.iter()),
+        ),
+    };
+
+    let mut builder = header_builder(archive);
+    let mut report = RenameReport::default();
+    for (view, filename) in archive.entry_views().zip(filenames) {
+        let entry = view.to_entry();
+        let mut contents = archive.try_read_entry(&entry, |_, reader| {
+            let mut contents = Vec::with_capacity(archive.contents_capacity(&entry));
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })?;
+        let is_lua = entry
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lua"));
+        if is_lua && !references.is_empty() {
+            let rewrite = options.lua_references == LuaReferences::Rewrite;
+            let (found, rewritten) = scan_references(&filename, &contents, &references, rewrite);
+            report.lua_references.extend(found);
+            if let Some(rewritten) = rewritten {
+                contents = rewritten;
+            }
+        }
+        builder
+            .file_from_bytes(filename, contents)
+            .file_number(entry.file_number());
+    }
+    Ok((builder, report))
+}
+
+/// Decides which archive each entry goes to for [`split_by`].
+///
+/// Implemented for closures `FnMut(&str) -> String`, entries with the same group end up in the
+/// same archive. See [`LuaAndContent`] and [`ByDirectory`] for the common splits
+pub trait Classifier {
+    /// The group of the entry named `filename`
+    fn classify(&mut self, filename: &str) -> String;
+}
+
+impl<F> Classifier for F
+where
+    F: FnMut(&str) -> String,
+{
+    fn classify(&mut self, filename: &str) -> String {
+        self(filename)
+    }
+}
+
+/// Splits code from assets: entries under `lua/` go to the `lua` group and everything else to
+/// the `content` group, so servers can ship the small code addon separately from heavy content
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LuaAndContent;
+
+impl Classifier for LuaAndContent {
+    fn classify(&mut self, filename: &str) -> String {
+        let is_lua = filename
+            .get(..4)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("lua/"));
+        match is_lua {
+            true => "lua",
+            false => "content",
+        }
+        .to_owned()
+    }
+}
+
+/// Groups entries by their first `depth` directories, so `ByDirectory(1)` splits `models/`,
+/// `materials/` and `sound/` into their own archives. Entries with fewer directories are grouped
+/// by all the directories they have, files at the root go to the empty group
+#[derive(Debug, Clone, Copy)]
+pub struct ByDirectory(pub usize);
+
+impl Classifier for ByDirectory {
+    fn classify(&mut self, filename: &str) -> String {
+        let parent = filename.rsplit_once('/').map_or("", |(parent, _)| parent);
+        parent
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .take(self.0)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Partitions the entries of `archive` into one builder per group returned by `classifier`,
+/// sorted by group.
+///
+/// Every builder has the header fields of `archive`, including its name, and the entries of its
+/// group in their original order, with the contents buffered in memory
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// use gma::transform::{split_by, LuaAndContent};
+///
+/// let archive = gma::load_from_memory(&dummy_buffer).unwrap();
+/// for (group, mut builder) in split_by(&archive, LuaAndContent).unwrap() {
+///     builder.name(format!("{} ({})", archive.name(), group));
+///     let mut output = std::io::Cursor::new(Vec::new());
+///     builder.write_to(&mut output).unwrap();
+/// }
+/// ```
+pub fn split_by<ReaderType, C>(
+    archive: &GMAFile<ReaderType>,
+    mut classifier: C,
+) -> Result<Vec<(String, GMABuilder)>>
+where
+    ReaderType: BufRead + Seek,
+    C: Classifier,
+{
+    let mut groups: BTreeMap<String, GMABuilder> = BTreeMap::new();
+    for view in archive.entry_views() {
+        let entry = view.to_entry();
+        let contents = archive.try_read_entry(&entry, |_, reader| {
+            let mut contents = Vec::with_capacity(archive.contents_capacity(&entry));
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })?;
+        let group = classifier.classify(entry.filename());
+        groups
+            .entry(group)
+            .or_insert_with(|| header_builder(archive))
+            .file_from_bytes(entry.filename(), contents);
+    }
+    Ok(groups.into_iter().collect())
+}
+
+// Maps the lowercase forms a lua file could use to reference each renamed entry to the same
+// form of the new name. Forms without a '/' are left out, a literal like "gui" or "a.lua" is
+// more likely a word or some other file than a reference
+fn reference_forms<'a, I>(renames: I) -> HashMap<String, String>
+where
+    I: Iterator<Item = (&'a str, &'a String)>,
+{
+    let mut forms = HashMap::new();
+    let mut insert = |old: &str, new: &str| {
+        if old.contains('/') {
+            forms.insert(old.to_lowercase(), new.to_owned());
+        }
+    };
+    for (old, new) in renames.filter(|(old, new)| old != new) {
+        insert(old, new);
+        let (old_relative, new_relative) = match (old.split_once('/'), new.split_once('/')) {
+            (Some((_, old_relative)), Some((_, new_relative))) => (old_relative, new_relative),
+            _ => continue,
+        };
+        insert(old_relative, new_relative);
+        if old.to_lowercase().starts_with("materials/") {
+            if let (Some((old_stem, _)), Some((new_stem, _))) =
+                (old_relative.rsplit_once('.'), new_relative.rsplit_once('.'))
+            {
+                insert(old_stem, new_stem);
+            }
+        }
+    }
+    forms
+}
+
+// Finds the string literals of `source` that are in `references`, returns them and, if
+// `rewrite` is set and something was found, the source with the literals replaced
+fn scan_references(
+    file: &str,
+    source: &[u8],
+    references: &HashMap<String, String>,
+    rewrite: bool,
+) -> (Vec<LuaReference>, Option<Vec<u8>>) {
+    let mut found = Vec::new();
+    let mut output = Vec::new();
+    let mut copied = 0;
+    for (line, range) in string_literals(source) {
+        let literal = String::from_utf8_lossy(&source[range.clone()]);
+        let new = match references.get(&literal.to_lowercase()) {
+            Some(new) => new,
+            None => continue,
+        };
+        found.push(LuaReference {
+            file: file.to_owned(),
+            line,
+            literal: literal.into_owned(),
+            replacement: new.clone(),
+            rewritten: rewrite,
+        });
+        if rewrite {
+            output.extend_from_slice(&source[copied..range.start]);
+            output.extend_from_slice(new.as_bytes());
+            copied = range.end;
+        }
+    }
+    if !rewrite || found.is_empty() {
+        return (found, None);
+    }
+    output.extend_from_slice(&source[copied..]);
+    (found, Some(output))
+}
+
+// The line and contents range of every quoted string literal without escape sequences.
+// Comments and long bracket strings are skipped
+fn string_literals(source: &[u8]) -> Vec<(usize, Range<usize>)> {
+    let mut literals = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < source.len() {
+        match source[i] {
+            b'\n' => line += 1,
+            b'-' if source.get(i + 1) == Some(&b'-') => {
+                i += 2;
+                match long_bracket_level(source, i) {
+                    Some(level) => i = skip_long_bracket(source, i, level, &mut line),
+                    None => {
+                        while i < source.len() && source[i] != b'\n' {
+                            i += 1;
+                        }
+                    }
+                }
+                continue;
+            }
+            b'[' => {
+                if let Some(level) = long_bracket_level(source, i) {
+                    i = skip_long_bracket(source, i, level, &mut line);
+                    continue;
+                }
+            }
+            quote @ (b'"' | b'\'') => {
+                let start = i + 1;
+                let mut escaped = false;
+                i = start;
+                while i < source.len() && source[i] != quote && source[i] != b'\n' {
+                    if source[i] == b'\\' {
+                        escaped = true;
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if !escaped && i < source.len() && source[i] == quote {
+                    literals.push((line, start..i));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    literals
+}
+
+// The level of the long bracket opening at `i`, 2 for `[==[`
+fn long_bracket_level(source: &[u8], i: usize) -> Option<usize> {
+    if source.get(i) != Some(&b'[') {
+        return None;
+    }
+    let level = source[i + 1..].iter().take_while(|c| **c == b'=').count();
+    match source.get(i + 1 + level) {
+        Some(b'[') => Some(level),
+        _ => None,
+    }
+}
+
+// Returns the position after the long bracket that opens at `i`
+fn skip_long_bracket(source: &[u8], i: usize, level: usize, line: &mut usize) -> usize {
+    let mut i = i + level + 2;
+    while i < source.len() {
+        if source[i] == b'\n' {
+            *line += 1;
+        } else if source[i] == b']'
+            && source[i + 1..].iter().take_while(|c| **c == b'=').count() == level
+            && source.get(i + 1 + level) == Some(&b']')
+        {
+            return i + level + 2;
+        }
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::string_literals;
+
+    fn literals(source: &str) -> Vec<(usize, &str)> {
+        string_literals(source.as_bytes())
+            .into_iter()
+            .map(|(line, range)| (line, &source[range]))
+            .collect()
+    }
+
+    #[test]
+    fn finds_literals() {
+        let source = "include(\"a.lua\")\nlocal m = Material('b')\n";
+        assert_eq!(literals(source), [(1, "a.lua"), (2, "b")]);
+    }
+
+    #[test]
+    fn skips_comments_and_long_strings() {
+        let source = "-- \"comment\"\n--[[ \"block\"\n]] x = [==[ \"long\" ]==] .. \"real\"";
+        assert_eq!(literals(source), [(3, "real")]);
+    }
+
+    #[test]
+    fn skips_escaped_literals() {
+        assert_eq!(literals("print(\"a\\\"b\") print('c')"), [(1, "c")]);
+    }
+}
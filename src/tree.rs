@@ -0,0 +1,83 @@
+use crate::{FileEntry, GMAFile};
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Seek},
+};
+
+/// A directory in the tree returned by [`GMAFile::tree`]
+#[derive(Debug)]
+pub struct DirectoryNode<'a> {
+    name: &'a str,
+    directories: Vec<DirectoryNode<'a>>,
+    files: Vec<&'a FileEntry>,
+}
+
+impl<'a> DirectoryNode<'a> {
+    /// The name of this directory, empty for the root
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+    /// The subdirectories, sorted by name
+    pub fn directories(&self) -> &[DirectoryNode<'a>] {
+        &self.directories
+    }
+    /// The files directly inside this directory, sorted by name
+    pub fn files(&self) -> &[&'a FileEntry] {
+        &self.files
+    }
+    /// The size of every file inside this directory and its subdirectories
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size()).sum::<u64>()
+            + self.directories.iter().map(|d| d.total_size()).sum::<u64>()
+    }
+    /// The subdirectory at the given slash separated path, relative to this directory
+    pub fn directory(&self, path: &str) -> Option<&DirectoryNode<'a>> {
+        path.split('/')
+            .filter(|c| !c.is_empty())
+            .try_fold(self, |dir, name| {
+                dir.directories.iter().find(|d| d.name == name)
+            })
+    }
+}
+
+#[derive(Default)]
+struct PartialDirectory<'a> {
+    directories: BTreeMap<&'a str, PartialDirectory<'a>>,
+    files: Vec<&'a FileEntry>,
+}
+
+impl<'a> PartialDirectory<'a> {
+    fn finish(self, name: &'a str) -> DirectoryNode<'a> {
+        let mut files = self.files;
+        files.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+        DirectoryNode {
+            name,
+            directories: self
+                .directories
+                .into_iter()
+                .map(|(name, dir)| dir.finish(name))
+                .collect(),
+            files,
+        }
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Builds a directory tree from the entries' slash separated filenames
+    pub fn tree(&self) -> DirectoryNode<'_> {
+        let mut root = PartialDirectory::default();
+        for entry in self.entries() {
+            let mut dir = &mut root;
+            if let Some(parent) = entry.parent() {
+                for name in parent.split('/').filter(|c| !c.is_empty()) {
+                    dir = dir.directories.entry(name).or_default();
+                }
+            }
+            dir.files.push(entry);
+        }
+        root.finish("")
+    }
+}
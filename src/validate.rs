@@ -0,0 +1,242 @@
+use crate::{is_whitelisted, Error, GMAFile, Result};
+use std::{
+    fmt::Display,
+    io::{BufRead, Seek},
+};
+
+/// The longest title the workshop accepts
+pub const MAX_NAME_LENGTH: usize = 128;
+/// The longest description the workshop accepts
+pub const MAX_DESCRIPTION_LENGTH: usize = 8000;
+/// The longest filename the game can open on every platform
+pub const MAX_FILENAME_LENGTH: usize = 255;
+
+// characters windows doesn't allow in filenames
+const ILLEGAL_CHARACTERS: &[char] = &['\\', '<', '>', ':', '"', '|', '?', '*'];
+
+/// A problem found by [`GMAFile::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ValidationIssue {
+    /// The contents of the entry end at `end`, past the end of the data at `len`
+    OutOfBounds {
+        filename: String,
+        end: u64,
+        len: u64,
+    },
+    /// The contents of the entry don't match its crc32
+    CrcMismatch {
+        filename: String,
+        expected: u32,
+        found: u32,
+    },
+    /// The filename is empty, not a relative path or has characters that are not allowed
+    IllegalFilename {
+        filename: String,
+        reason: &'static str,
+    },
+    /// These entries have the same filename, compared case-insensitively like the game does
+    DuplicatePath(Vec<String>),
+    /// The game doesn't load files with this path, see [`crate::WHITELIST`]
+    NotWhitelisted { filename: String },
+    /// A string is longer than allowed. `field` is name, description or the filename
+    StringTooLong {
+        field: String,
+        len: usize,
+        max: usize,
+    },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds { filename, end, len } => write!(
+                f,
+                "'{}' ends at {} but the archive only has {} bytes",
+                filename, end, len
+            ),
+            Self::CrcMismatch {
+                filename,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the crc32 of '{}' does not match, expected {:x} but found {:x}",
+                filename, expected, found
+            ),
+            Self::IllegalFilename { filename, reason } => {
+                write!(f, "'{}' is not a valid filename, {}", filename, reason)
+            }
+            Self::DuplicatePath(filenames) => {
+                write!(f, "duplicate paths : {}", filenames.join(", "))
+            }
+            Self::NotWhitelisted { filename } => write!(f, "'{}' is not whitelisted", filename),
+            Self::StringTooLong { field, len, max } => write!(
+                f,
+                "{} is {} bytes long but at most {} are allowed",
+                field, len, max
+            ),
+        }
+    }
+}
+
+/// Every problem found by [`GMAFile::validate`], in the order they were found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// The problems found
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+    /// True if no problems were found
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// One issue per line
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for issue in self.issues.iter() {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Checks everything the game and the workshop could reject and lists every problem found,
+    /// instead of stopping at the first one. This reads the contents of every entry to check
+    /// their crc32.
+    ///
+    /// Only io errors while reading are returned as errors
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(dummy_buffer).unwrap();
+    /// let report = archive.validate().unwrap();
+    /// for issue in report.issues() {
+    ///     println!("{}", issue);
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<ValidationReport> {
+        let mut issues = Vec::new();
+        let mut check_len = |field: &str, len: usize, max: usize| {
+            if len > max {
+                issues.push(ValidationIssue::StringTooLong {
+                    field: field.to_owned(),
+                    len,
+                    max,
+                });
+            }
+        };
+        check_len("name", self.name().len(), MAX_NAME_LENGTH);
+        check_len(
+            "description",
+            self.description().len(),
+            MAX_DESCRIPTION_LENGTH,
+        );
+        for entry in self.entry_views() {
+            check_len(
+                entry.filename(),
+                entry.filename().len(),
+                MAX_FILENAME_LENGTH,
+            );
+        }
+
+        for entry in self.entry_views() {
+            let filename = entry.filename();
+            if let Some(reason) = illegal_filename(filename) {
+                issues.push(ValidationIssue::IllegalFilename {
+                    filename: filename.to_owned(),
+                    reason,
+                });
+            } else if !is_whitelisted(&filename.to_lowercase()) {
+                issues.push(ValidationIssue::NotWhitelisted {
+                    filename: filename.to_owned(),
+                });
+            }
+        }
+        for group in self.duplicate_views() {
+            issues.push(ValidationIssue::DuplicatePath(
+                group.iter().map(|e| e.filename().to_owned()).collect(),
+            ));
+        }
+
+        let len = self.stream_len()?;
+        for entry in self.entry_views() {
+            let end = entry.absolute_offset().saturating_add(entry.size());
+            if end > len {
+                issues.push(ValidationIssue::OutOfBounds {
+                    filename: entry.filename().to_owned(),
+                    end,
+                    len,
+                });
+                continue;
+            }
+            match self.read_entry_verified(&entry.to_entry(), |_, reader| {
+                std::io::copy(reader, &mut std::io::sink())
+            }) {
+                Ok(result) => {
+                    result?;
+                }
+                Err(Error::CrcMismatch {
+                    filename,
+                    expected,
+                    found,
+                }) => issues.push(ValidationIssue::CrcMismatch {
+                    filename,
+                    expected,
+                    found,
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(ValidationReport { issues })
+    }
+}
+
+// Why `filename` can't be used as a path inside an addon, if it can't
+fn illegal_filename(filename: &str) -> Option<&'static str> {
+    if filename.is_empty() {
+        return Some("it is empty");
+    }
+    if filename.chars().any(|c| c.is_control()) {
+        return Some("it contains control characters");
+    }
+    if filename.contains(ILLEGAL_CHARACTERS) {
+        return Some("it contains characters that are not allowed in paths");
+    }
+    // std's Path skips `.` and repeated slashes, so split by hand
+    if filename
+        .split('/')
+        .any(|component| matches!(component, "" | "." | ".."))
+    {
+        return Some("it is not a relative path to a file");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::illegal_filename;
+
+    #[test]
+    fn illegal_filenames() {
+        assert_eq!(illegal_filename("lua/autorun/a.lua"), None);
+        assert!(illegal_filename("").is_some());
+        assert!(illegal_filename("/lua/a.lua").is_some());
+        assert!(illegal_filename("lua/../a.lua").is_some());
+        assert!(illegal_filename("lua/./a.lua").is_some());
+        assert!(illegal_filename("lua\\a.lua").is_some());
+        assert!(illegal_filename("lua/a?.lua").is_some());
+        assert!(illegal_filename("lua/a\n.lua").is_some());
+        assert!(illegal_filename("lua/").is_some());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::Display;
+
+/// The version of the gma file format, stored right after the ident.
+///
+/// Versions 1, 2 and 3 are the ones the game can read, other values are kept as
+/// [`GmaVersion::Unknown`]. With the `serde` feature it serializes as the version number
+/// ```
+/// let version = gma::GmaVersion::from(2);
+/// assert_eq!(version, gma::GmaVersion::V2);
+/// assert!(version.has_required_content());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GmaVersion {
+    V1,
+    V2,
+    V3,
+    Unknown(u8),
+}
+
+impl GmaVersion {
+    /// The version byte. Ex : 3
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+            Self::V3 => 3,
+            Self::Unknown(version) => *version,
+        }
+    }
+    /// True for the versions this crate and the game can read
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+    /// True if the header has the list of required content after the timestamp, from version 2 onwards
+    pub fn has_required_content(&self) -> bool {
+        self.as_u8() > 1
+    }
+}
+
+/// Version 3, the one gmad currently writes
+impl Default for GmaVersion {
+    fn default() -> Self {
+        Self::V3
+    }
+}
+
+impl From<u8> for GmaVersion {
+    fn from(version: u8) -> Self {
+        match version {
+            1 => Self::V1,
+            2 => Self::V2,
+            3 => Self::V3,
+            _ => Self::Unknown(version),
+        }
+    }
+}
+
+impl From<GmaVersion> for u8 {
+    fn from(version: GmaVersion) -> Self {
+        version.as_u8()
+    }
+}
+
+impl PartialEq<u8> for GmaVersion {
+    fn eq(&self, other: &u8) -> bool {
+        self.as_u8() == *other
+    }
+}
+
+/// Displays the version number
+impl Display for GmaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_u8().fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GmaVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.as_u8())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        for version in 0..=u8::MAX {
+            assert_eq!(GmaVersion::from(version).as_u8(), version);
+        }
+        assert_eq!(GmaVersion::from(1), GmaVersion::V1);
+        assert_eq!(GmaVersion::from(4), GmaVersion::Unknown(4));
+        assert!(!GmaVersion::Unknown(0).is_known());
+        assert!(!GmaVersion::V1.has_required_content());
+        assert!(GmaVersion::V3.has_required_content());
+    }
+}
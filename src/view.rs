@@ -0,0 +1,70 @@
+use crate::{Error, FileEntry, GMAFile, LoadOptions, Result};
+use std::{convert::TryFrom, io::Cursor};
+
+/// An archive loaded from a buffer whose entry contents are borrowed straight from it, see
+/// [`load_view`]
+#[derive(Debug)]
+pub struct GMAView<'a> {
+    archive: GMAFile<Cursor<&'a [u8]>>,
+    data: &'a [u8],
+}
+
+impl<'a> GMAView<'a> {
+    /// The archive, for the header fields and the file table
+    pub fn archive(&self) -> &GMAFile<Cursor<&'a [u8]>> {
+        &self.archive
+    }
+    /// Drops the view and returns the archive
+    pub fn into_archive(self) -> GMAFile<Cursor<&'a [u8]>> {
+        self.archive
+    }
+    /// The buffer the archive was loaded from
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+    /// The contents of `entry`, a slice of the buffer that can outlive the view.
+    ///
+    /// The crc is not checked, even with [`LoadOptions::verify_crc`]. Returns None if `entry`
+    /// ends past the end of the buffer, like entries of another archive
+    pub fn entry_bytes(&self, entry: &FileEntry) -> Option<&'a [u8]> {
+        let start = usize::try_from(entry.absolute_offset()).ok()?;
+        let len = usize::try_from(entry.size()).ok()?;
+        self.data.get(start..start.checked_add(len)?)
+    }
+    /// Every entry with its contents, in archive order
+    pub fn entries_with_bytes(&self) -> impl Iterator<Item = (&FileEntry, &'a [u8])> + '_ {
+        self.archive
+            .entries()
+            .filter_map(move |entry| Some((entry, self.entry_bytes(entry)?)))
+    }
+}
+
+/// Loads an archive from memory without copying its contents, for scanners that already have
+/// the bytes in memory or mapped. [`GMAView::entry_bytes`] returns slices of `data` that don't
+/// go through a reader, so they don't lock and can be kept after the view is dropped.
+///
+/// Fails with [`Error::CompressedArchive`] if the archive is compressed, since there are no
+/// contents to borrow until it is decompressed, use [`crate::load_from_memory`] for those
+/// ```
+/// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+/// let view = gma::load_view(dummy_buffer).unwrap();
+/// for (entry, contents) in view.entries_with_bytes() {
+///     if contents.windows(9).any(|w| w == b"RunString") {
+///         println!("{}", entry.filename());
+///     }
+/// }
+/// ```
+pub fn load_view(data: &[u8]) -> Result<GMAView<'_>> {
+    LoadOptions::new().load_view(data)
+}
+
+impl LoadOptions {
+    /// Loads a borrowed view of an archive in memory with these options, see [`load_view`]
+    pub fn load_view<'a>(&self, data: &'a [u8]) -> Result<GMAView<'a>> {
+        let archive = self.load_from_memory(data)?;
+        if archive.compressed() {
+            return Err(Error::CompressedArchive);
+        }
+        Ok(GMAView { archive, data })
+    }
+}
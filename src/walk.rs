@@ -0,0 +1,57 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A file found while walking a directory
+pub struct WalkedFile {
+    /// The path on disk
+    pub path: PathBuf,
+    /// The path relative to the walked root, using '/' as the separator
+    pub relative: String,
+    /// The file size in bytes
+    pub size: u64,
+}
+
+/// Recursively lists every file under `root`, sorted by relative path.
+///
+/// Symbolic links are followed, except for links to a directory that contains them, which would
+/// never end
+pub fn walk_dir(root: &Path) -> io::Result<Vec<WalkedFile>> {
+    let mut files = Vec::new();
+    // every directory with the canonical paths of itself and the directories above it
+    let mut pending = vec![(
+        root.to_path_buf(),
+        String::new(),
+        vec![fs::canonicalize(root)?],
+    )];
+    while let Some((dir, prefix, ancestors)) = pending.pop() {
+        for dir_entry in fs::read_dir(&dir)? {
+            let dir_entry = dir_entry?;
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            let relative = match prefix.is_empty() {
+                true => name,
+                false => format!("{}/{}", prefix, name),
+            };
+            let path = dir_entry.path();
+            let metadata = fs::metadata(&path)?;
+            if metadata.is_dir() {
+                let canonical = fs::canonicalize(&path)?;
+                if ancestors.contains(&canonical) {
+                    continue;
+                }
+                let mut ancestors = ancestors.clone();
+                ancestors.push(canonical);
+                pending.push((path, relative, ancestors));
+            } else {
+                files.push(WalkedFile {
+                    path,
+                    relative,
+                    size: metadata.len(),
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(files)
+}
@@ -0,0 +1,60 @@
+/// Matches `text` against a gmad style wildcard `pattern`.
+///
+/// `*` matches any sequence of characters, including `/`, and `?` matches a single character.
+/// The comparison is case-insensitive since gmod itself does not care about case.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    //position of the last '*' in the pattern and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal() {
+        assert!(matches("lua/init.lua", "lua/init.lua"));
+        assert!(matches("lua/init.lua", "LUA/Init.lua"));
+        assert!(!matches("lua/init.lua", "lua/init.luac"));
+    }
+
+    #[test]
+    fn star() {
+        assert!(matches("lua/*.lua", "lua/autorun/client/init.lua"));
+        assert!(matches("*.psd", "materials/texture.psd"));
+        assert!(matches("*", ""));
+        assert!(!matches("lua/*.lua", "materials/init.lua"));
+        assert!(!matches("maps/*.bsp", "maps/map.bsp.bak"));
+    }
+
+    #[test]
+    fn question_mark() {
+        assert!(matches("icon??.png", "icon24.png"));
+        assert!(!matches("icon??.png", "icon2.png"));
+    }
+}
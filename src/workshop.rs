@@ -0,0 +1,113 @@
+//! Checks of local archives against what is published on the workshop
+use crate::{Error, GMAFile, Result};
+use std::io::{self, BufRead, Seek};
+
+/// What is known about the published version of a workshop item, from the Steam Web API's
+/// `GetPublishedFileDetails` (`file_size` and `time_updated`) and, optionally, from the tool
+/// that published it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkshopManifest {
+    size: u64,
+    time_updated: u64,
+    crc: Option<u32>,
+}
+
+impl WorkshopManifest {
+    /// A manifest for an item whose published file is `size` bytes and that was last updated at
+    /// `time_updated`, in seconds since the unix epoch.
+    ///
+    /// `size` is the `file_size` of the Web API, the size of the file as it is stored on the
+    /// workshop. Items published with gmpublish are lzma compressed so this is not the size of
+    /// the archive once it is decompressed
+    pub fn new(size: u64, time_updated: u64) -> Self {
+        Self {
+            size,
+            time_updated,
+            crc: None,
+        }
+    }
+
+    /// The crc32 of the published archive's contents, the one gmad appends to the archive.
+    /// The Web API doesn't provide it but publishing tools can record it. Default : unknown
+    pub fn crc(&mut self, crc: u32) -> &mut Self {
+        self.crc = Some(crc);
+        self
+    }
+}
+
+/// How a local archive compares to the published version, see [`verify_local`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocalStatus {
+    /// The archive is intact and matches the published crc, or size if the crc is unknown
+    UpToDate,
+    /// The archive is intact but differs from the published one and was built before the last
+    /// update, a newer version should be downloaded
+    Outdated,
+    /// The contents don't match the crc32 stored in the archive or in its entries, the archive
+    /// was damaged and should be downloaded again
+    Corrupted,
+    /// The archive is intact but differs from the published one and was built after the last
+    /// update, it was probably changed locally
+    Modified,
+}
+
+/// Compares a local copy of the workshop item described by `manifest` against it. This reads
+/// the whole archive.
+///
+/// The archive is first checked against its own checksums, [`GMAFile::archive_crc`] or, if there
+/// is none, the crc32 of every entry. An intact archive is then compared with the published crc
+/// if it is known, or else with the published size. [`GMAFile::timestamp`] tells outdated copies
+/// from modified ones.
+///
+/// The size compared is the one of the input as it was loaded, still compressed if the archive
+/// was compressed. A copy that was decompressed after downloading it doesn't match the size of a
+/// compressed item, a manifest with the crc is needed to tell if it is up to date
+/// ```no_run
+/// use gma::workshop::{verify_local, LocalStatus, WorkshopManifest};
+///
+/// // file_size and time_updated of the item from GetPublishedFileDetails
+/// let manifest = WorkshopManifest::new(10_485_760, 1_700_000_000);
+/// let archive = gma::open("2118049154.gma").unwrap();
+/// match verify_local(&manifest, &archive).unwrap() {
+///     LocalStatus::UpToDate => {}
+///     LocalStatus::Modified => println!("keeping local changes"),
+///     LocalStatus::Outdated | LocalStatus::Corrupted => println!("downloading again"),
+/// }
+/// ```
+pub fn verify_local<ReaderType>(
+    manifest: &WorkshopManifest,
+    archive: &GMAFile<ReaderType>,
+) -> Result<LocalStatus>
+where
+    ReaderType: BufRead + Seek,
+{
+    let crc = archive.contents_crc()?;
+    match archive.archive_crc() {
+        Some(expected) if expected != crc => return Ok(LocalStatus::Corrupted),
+        Some(_) => {}
+        None => {
+            for entry in archive.entry_views() {
+                let result = archive.read_entry_verified(&entry.to_entry(), |_, reader| {
+                    io::copy(reader, &mut io::sink())
+                });
+                match result {
+                    Err(Error::CrcMismatch { .. }) => return Ok(LocalStatus::Corrupted),
+                    result => {
+                        result??;
+                    }
+                }
+            }
+        }
+    }
+
+    // the crc doesn't depend on how the archive is stored, the size does
+    let matches = match manifest.crc {
+        Some(expected) => expected == crc,
+        None => archive.stored_size()? == manifest.size,
+    };
+    Ok(match matches {
+        true => LocalStatus::UpToDate,
+        false if archive.timestamp() < manifest.time_updated => LocalStatus::Outdated,
+        false => LocalStatus::Modified,
+    })
+}
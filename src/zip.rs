@@ -0,0 +1,227 @@
+use crate::{checksum::Crc32Reader, sanitize_path, Error, FileEntry, GMAFile, PathPolicy, Result};
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, Seek, Write},
+};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const VERSION: u16 = 20;
+// sizes and crc come after the contents in a data descriptor, filenames are utf8
+const FLAGS: u16 = 0x0008 | 0x0800;
+const METHOD_STORED: u16 = 0;
+// the size of every record without the filename
+const LOCAL_HEADER_LEN: u64 = 30;
+const DATA_DESCRIPTOR_LEN: u64 = 16;
+const CENTRAL_HEADER_LEN: u64 = 46;
+const END_OF_CENTRAL_DIRECTORY_LEN: u64 = 22;
+
+struct CentralEntry {
+    filename: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+// Counts the bytes written so the offsets of the local headers are known without Seek
+struct CountingWriter<W> {
+    writer: W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<ReaderType> GMAFile<ReaderType>
+where
+    ReaderType: BufRead + Seek,
+{
+    /// Writes the given entries as an uncompressed zip archive into `writer` and returns the
+    /// amount of bytes written.
+    ///
+    /// The zip is produced on the fly, `writer` does not need to be seekable so it can be an http
+    /// response body. The crc32 of each file is computed while streaming instead of trusting the
+    /// one stored in the archive. Zip64 is not supported, selections with more than 65535 files or
+    /// larger than 4 GiB fail with [`Error::ZipLimitExceeded`].
+    ///
+    /// Filenames go through [`crate::sanitize_path`] so the zip can't write outside the directory
+    /// it is extracted to, unsafe ones fail with [`Error::UnsafePath`]. Both checks happen before
+    /// anything is written
+    /// ```
+    /// # let dummy_buffer = &include_bytes!("../tests/addon.gma")[..];
+    /// let archive = gma::load_from_memory(&dummy_buffer).unwrap();
+    /// let lua = archive.entries().filter(|e| e.filename().starts_with("lua/"));
+    /// let mut zip = Vec::new();
+    /// archive.stream_subset_as_zip(lua, &mut zip).unwrap();
+    /// ```
+    pub fn stream_subset_as_zip<'e, I, W>(&self, entries: I, writer: W) -> Result<u64>
+    where
+        I: IntoIterator<Item = &'e FileEntry>,
+        W: Write,
+    {
+        let mut files = Vec::new();
+        let mut zip_size = END_OF_CENTRAL_DIRECTORY_LEN;
+        for entry in entries {
+            let name = zip_name(entry.filename())?;
+            u16::try_from(name.len()).map_err(|_| Error::ZipLimitExceeded)?;
+            zip_size = zip_size
+                .saturating_add(LOCAL_HEADER_LEN + DATA_DESCRIPTOR_LEN + CENTRAL_HEADER_LEN)
+                .saturating_add(2 * name.len() as u64)
+                .saturating_add(entry.size());
+            files.push((entry, name));
+        }
+        u16::try_from(files.len()).map_err(|_| Error::ZipLimitExceeded)?;
+        zip_u32(zip_size)?;
+
+        let (time, date) = dos_date_time(self.timestamp());
+        let mut writer = CountingWriter { writer, written: 0 };
+        let mut central = Vec::new();
+        for (entry, filename) in files {
+            let offset = zip_u32(writer.written)?;
+            let name = filename.as_bytes();
+            let name_len = name.len() as u16;
+
+            writer.write_all(&LOCAL_HEADER_SIGNATURE.to_le_bytes())?;
+            writer.write_all(&VERSION.to_le_bytes())?;
+            writer.write_all(&FLAGS.to_le_bytes())?;
+            writer.write_all(&METHOD_STORED.to_le_bytes())?;
+            writer.write_all(&time.to_le_bytes())?;
+            writer.write_all(&date.to_le_bytes())?;
+            // crc, compressed size and uncompressed size are in the data descriptor
+            writer.write_all(&[0; 12])?;
+            writer.write_all(&name_len.to_le_bytes())?;
+            writer.write_all(&0u16.to_le_bytes())?;
+            writer.write_all(name)?;
+
+            let (size, crc) = self.try_read_entry(entry, |_, reader| {
+                let mut crc_reader = Crc32Reader::new(reader);
+                let size = io::copy(&mut crc_reader, &mut writer)?;
+                Ok((size, crc_reader.finish()?))
+            })?;
+            let size = zip_u32(size)?;
+
+            writer.write_all(&DATA_DESCRIPTOR_SIGNATURE.to_le_bytes())?;
+            writer.write_all(&crc.to_le_bytes())?;
+            writer.write_all(&size.to_le_bytes())?;
+            writer.write_all(&size.to_le_bytes())?;
+
+            central.push(CentralEntry {
+                filename,
+                crc,
+                size,
+                offset,
+            });
+        }
+
+        let central_offset = zip_u32(writer.written)?;
+        let count = u16::try_from(central.len()).map_err(|_| Error::ZipLimitExceeded)?;
+        for entry in central.iter() {
+            writer.write_all(&CENTRAL_HEADER_SIGNATURE.to_le_bytes())?;
+            writer.write_all(&VERSION.to_le_bytes())?;
+            writer.write_all(&VERSION.to_le_bytes())?;
+            writer.write_all(&FLAGS.to_le_bytes())?;
+            writer.write_all(&METHOD_STORED.to_le_bytes())?;
+            writer.write_all(&time.to_le_bytes())?;
+            writer.write_all(&date.to_le_bytes())?;
+            writer.write_all(&entry.crc.to_le_bytes())?;
+            writer.write_all(&entry.size.to_le_bytes())?;
+            writer.write_all(&entry.size.to_le_bytes())?;
+            writer.write_all(&(entry.filename.len() as u16).to_le_bytes())?;
+            // extra field length, comment length, disk number and internal attributes
+            writer.write_all(&[0; 8])?;
+            // external attributes
+            writer.write_all(&0u32.to_le_bytes())?;
+            writer.write_all(&entry.offset.to_le_bytes())?;
+            writer.write_all(entry.filename.as_bytes())?;
+        }
+        let central_size = zip_u32(writer.written)? - central_offset;
+
+        writer.write_all(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes())?;
+        // this disk and the disk where the central directory starts
+        writer.write_all(&[0; 4])?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&central_size.to_le_bytes())?;
+        writer.write_all(&central_offset.to_le_bytes())?;
+        // comment length
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.flush()?;
+        Ok(writer.written)
+    }
+}
+
+// The filename stored in the zip, without the components that could escape the extraction
+// directory and always separated by '/'
+fn zip_name(filename: &str) -> Result<String> {
+    let path = sanitize_path(filename, PathPolicy::Reject)?;
+    let components: Vec<_> = path.iter().map(|c| c.to_string_lossy()).collect();
+    Ok(components.join("/"))
+}
+
+fn zip_u32(value: u64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| Error::ZipLimitExceeded)
+}
+
+// Converts seconds since UNIX epoch to the ms-dos (time, date) used by zip,
+// clamped to the range of dates it can represent
+fn dos_date_time(timestamp: u64) -> (u16, u16) {
+    let days = timestamp / 86400;
+    let seconds = timestamp % 86400;
+    let (year, month, day) = civil_from_days(days);
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    if year > 2107 {
+        return ((23 << 11) | (59 << 5) | 29, (127 << 9) | (12 << 5) | 31);
+    }
+    let time = ((seconds / 3600) << 11) | (((seconds % 3600) / 60) << 5) | ((seconds % 60) / 2);
+    let date = ((year - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+// Days since 1970-01-01 to (year, month, day), from Howard Hinnant's civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, dos_date_time};
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(18466), (2020, 7, 23));
+    }
+
+    #[test]
+    fn dos_dates() {
+        // 2020-07-23 14:36:55
+        assert_eq!(
+            dos_date_time(1595515015),
+            ((14 << 11) | (36 << 5) | 27, (40 << 9) | (7 << 5) | 23)
+        );
+        assert_eq!(dos_date_time(0), (0, (1 << 5) | 1));
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, SkipReason};
+    use std::{fs, io::Cursor};
+
+    #[test]
+    fn add_dir() {
+        let root = std::env::temp_dir().join(format!("gma-test-add-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("lua/autorun/client")).unwrap();
+        fs::create_dir_all(root.join("materials/.git")).unwrap();
+        fs::write(root.join("lua/autorun/init.lua"), b"print('init')").unwrap();
+        fs::write(root.join("lua/autorun/client/cl.lua"), b"print('cl')").unwrap();
+        fs::write(root.join("materials/a.vmt"), b"vmt").unwrap();
+        fs::write(root.join("materials/.git/HEAD"), b"ref").unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("dir")
+            .skip_hidden(true)
+            .add_dir(&root)
+            .unwrap();
+        let report = builder
+            .write_to_with_report(Cursor::new(&mut buffer))
+            .unwrap();
+        assert_eq!(report.skipped().len(), 1);
+        assert_eq!(report.skipped()[0].filename(), "materials/.git/HEAD");
+        assert_eq!(report.skipped()[0].reason(), &SkipReason::Hidden);
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "lua/autorun/client/cl.lua",
+                "lua/autorun/init.lua",
+                "materials/a.vmt"
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(GMABuilder::new().add_dir(&root).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loop() {
+        let root =
+            std::env::temp_dir().join(format!("gma-test-add-dir-loop-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("lua")).unwrap();
+        fs::write(root.join("lua/init.lua"), b"print('init')").unwrap();
+        std::os::unix::fs::symlink("..", root.join("lua/loop")).unwrap();
+
+        let mut builder = GMABuilder::new();
+        builder.name("loop").add_dir(&root).unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, vec!["lua/init.lua"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn files_are_opened_when_written() {
+        const FILES: usize = 256;
+        let open_files = || fs::read_dir("/proc/self/fd").unwrap().count();
+        let root =
+            std::env::temp_dir().join(format!("gma-test-add-dir-open-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("lua")).unwrap();
+        for i in 0..FILES {
+            fs::write(root.join(format!("lua/{}.lua", i)), b"print('hi')").unwrap();
+        }
+
+        let before = open_files();
+        let mut builder = GMABuilder::new();
+        builder.name("open").add_dir(&root).unwrap();
+        // other tests running at the same time can have a few files open
+        assert!(open_files() < before + FILES / 2);
+        let mut buffer: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        assert_eq!(gma::load_from_memory(&buffer).unwrap().entry_count(), FILES);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
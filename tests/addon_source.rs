@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod test {
+    use gma::{AddonSource, AddonTag, AddonType, Error};
+    use std::{fs, io::Read, path::PathBuf};
+
+    fn test_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join(name)
+    }
+
+    #[test]
+    fn local_file_and_memory() {
+        let genuine = include_bytes!("genuine.gma");
+        let sources = vec![
+            AddonSource::LocalFile(test_path("genuine.gma")),
+            AddonSource::Memory(genuine.to_vec()),
+            AddonSource::Reader(Box::new(&genuine[..])),
+        ];
+        for source in sources {
+            let archive = source.load().unwrap();
+            assert_eq!(archive.name(), "My Test Addon");
+            assert_eq!(archive.entries().count(), 1);
+        }
+    }
+
+    #[test]
+    fn folder() {
+        let archive = AddonSource::Folder(test_path("addon")).load().unwrap();
+        assert_eq!(archive.name(), "My Test Addon");
+        assert_eq!(archive.description(), "My Description");
+        assert_eq!(archive.addon_type(), Some(AddonType::Gamemode));
+        assert_eq!(archive.addon_tags(), &[AddonTag::Fun, AddonTag::Cartoon]);
+
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, vec!["lua/hello.lua"]);
+    }
+
+    #[test]
+    fn folder_follows_gmad() {
+        let root = std::env::temp_dir().join(format!("gma-source-folder-{}", std::process::id()));
+        fs::create_dir_all(root.join("lua")).unwrap();
+        fs::write(root.join("lua/a.lua"), b"print('a')").unwrap();
+        fs::write(root.join("notes.txt"), b"not whitelisted").unwrap();
+
+        fs::write(root.join("addon.json"), br#"{"title": "folder"}"#).unwrap();
+        match AddonSource::Folder(root.clone()).load() {
+            Err(Error::InvalidAddonType(t)) => assert_eq!(t, ""),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        fs::write(
+            root.join("addon.json"),
+            br#"{"title": "folder", "type": "tool"}"#,
+        )
+        .unwrap();
+        let archive = AddonSource::Folder(root.clone()).load().unwrap();
+        assert_eq!(archive.addon_type(), Some(AddonType::Tool));
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, vec!["lua/a.lua"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn remote() {
+        let items = [
+            "2118049154",
+            "https://steamcommunity.com/sharedfiles/filedetails/?id=2118049154",
+            "https://steamcommunity.com/sharedfiles/filedetails/?l=english&id=2118049154",
+        ];
+        for item in items.iter() {
+            let source = AddonSource::Remote {
+                item: item.to_string(),
+                fetch: Box::new(|id| {
+                    assert_eq!(id, 2118049154);
+                    let reader: Box<dyn Read> = Box::new(&include_bytes!("genuine.gma")[..]);
+                    Ok(reader)
+                }),
+            };
+            assert_eq!(source.load().unwrap().name(), "My Test Addon");
+        }
+
+        let source = AddonSource::Remote {
+            item: "https://steamcommunity.com/id/someone".to_owned(),
+            fetch: Box::new(|_| panic!("nothing to fetch")),
+        };
+        assert!(matches!(source.load(), Err(Error::InvalidWorkshopItem(_))));
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(AddonSource::LocalFile(test_path("missing.gma"))
+            .load()
+            .is_err());
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod test {
+    use crc::{Crc, CRC_32_ISO_HDLC};
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("crc")
+            .file_from_bytes("lua/a.lua", b"print(\"a\")".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn with_crc(mut buffer: Vec<u8>) -> Vec<u8> {
+        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&buffer);
+        buffer.extend_from_slice(&crc.to_le_bytes());
+        buffer
+    }
+
+    #[test]
+    fn genuine_archive_crc() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        assert!(archive.archive_crc().is_some());
+        archive.verify_archive_crc().unwrap();
+    }
+
+    #[test]
+    fn verify() {
+        let buffer = with_crc(build());
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(archive.archive_crc().is_some());
+        archive.verify_archive_crc().unwrap();
+
+        let mut corrupted = buffer.clone();
+        let last_content_byte = corrupted.len() - 5;
+        corrupted[last_content_byte] ^= 0xff;
+        let archive = gma::load_from_memory(&corrupted).unwrap();
+        assert!(matches!(
+            archive.verify_archive_crc(),
+            Err(gma::Error::ArchiveCrcMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn missing() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.archive_crc(), None);
+        assert!(matches!(
+            archive.verify_archive_crc(),
+            Err(gma::Error::MissingArchiveCrc)
+        ));
+    }
+
+    #[test]
+    fn kept_by_buffer_all() {
+        let buffer = with_crc(build());
+        let archive = gma::load_from_memory(&buffer)
+            .unwrap()
+            .buffer_all()
+            .unwrap();
+        archive.verify_archive_crc().unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::borrow::Cow;
+    use std::io::Cursor;
+
+    static STATIC_CONTENTS: &[u8] = b"print('borrowed')";
+
+    fn read(archive: &gma::GMAFile<Cursor<&[u8]>>, filename: &str) -> Vec<u8> {
+        archive
+            .read_entry(&archive[filename], |_, reader| {
+                let mut contents = Vec::new();
+                reader.read_to_end(&mut contents).unwrap();
+                contents
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn file_from_cow() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("cow")
+            .file_from_cow("lua/borrowed.lua", Cow::Borrowed(STATIC_CONTENTS))
+            .file_from_cow("lua/owned.lua", Cow::Owned(b"print('owned')".to_vec()));
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(read(&archive, "lua/borrowed.lua"), STATIC_CONTENTS);
+        assert_eq!(read(&archive, "lua/owned.lua"), b"print('owned')");
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn file_from_bytes_shared() {
+        let shared = bytes::Bytes::from_static(b"print('shared')");
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("shared")
+            .file_from_bytes_shared("lua/shared.lua", shared.clone());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(read(&archive, "lua/shared.lua"), shared.as_ref());
+    }
+}
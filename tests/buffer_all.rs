@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::{BufReader, Cursor};
+
+    fn build(compressed: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("buffered")
+            .compression(compressed)
+            .file_from_bytes("lua/a.lua", b"print(\"a\")".to_vec())
+            .file_from_bytes("materials/b.vmt", vec![b'b'; 300]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn contents(archive: &gma::GMAFile<Cursor<Vec<u8>>>) -> Vec<Vec<u8>> {
+        archive
+            .entries()
+            .map(|entry| {
+                archive
+                    .read_entry_verified(entry, |_, reader| {
+                        let mut c = Vec::new();
+                        reader.read_to_end(&mut c).unwrap();
+                        c
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outlives_the_original_reader() {
+        for compressed in [false, true].iter() {
+            let mut buffer = build(*compressed);
+            let archive = gma::load(BufReader::new(Cursor::new(&buffer[..])))
+                .unwrap()
+                .buffer_all()
+                .unwrap();
+            buffer.clear();
+            assert_eq!(archive.name(), "buffered");
+            assert_eq!(archive.compressed(), *compressed);
+            let contents = contents(&archive);
+            assert_eq!(contents[0], b"print(\"a\")");
+            assert_eq!(contents[1], vec![b'b'; 300]);
+        }
+    }
+
+    #[test]
+    fn offsets_are_kept() {
+        let buffer = build(false);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let header = archive.raw_header().unwrap();
+        let data_offset = archive.data_offset();
+        let archive = archive.buffer_all().unwrap();
+        assert_eq!(archive.raw_header().unwrap(), header);
+        assert_eq!(archive.data_offset(), data_offset);
+    }
+}
@@ -1,16 +1,17 @@
 #[cfg(test)]
 mod test {
-    use gma::{AddonTag, AddonType, GMABuilder};
+    use gma::{AddonTag, AddonType, GMABuilder, GmaVersion};
     use std::io::Cursor;
 
     #[test]
     fn build_parse_gma() {
-        const VERSION: u8 = 3;
+        const VERSION: GmaVersion = GmaVersion::V3;
         const STEAMID: u64 = 123456;
         const TIMESTAMP: u64 = 987654;
         const NAME: &str = "ADDON_NAME";
         const DESC: &str = "ADDON_DESC";
         const AUTHOR: &str = "AUTHOR_NAME";
+        const ADDON_VERSION: u32 = 7;
         const TYPE: AddonType = AddonType::Model;
         const TAG1: AddonTag = AddonTag::Build;
         const TAG2: AddonTag = AddonTag::Fun;
@@ -31,6 +32,7 @@ mod test {
             .addon_tag(TAG1)
             .addon_tag(TAG2)
             .author(AUTHOR)
+            .addon_version(ADDON_VERSION)
             .compression(true)
             .file_from_bytes(ENTRY_NAME, ENTRY_DATA.into());
 
@@ -47,6 +49,7 @@ mod test {
         assert!(archive.contains_tag(TAG2));
         //this fields isnt currently used and is hardcoded to this
         assert_eq!(archive.author(), AUTHOR);
+        assert_eq!(archive.addon_version(), ADDON_VERSION);
 
         let entry = archive
             .entries()
@@ -63,4 +66,33 @@ mod test {
             })
             .unwrap();
     }
+
+    #[test]
+    fn every_known_version() {
+        for version in [GmaVersion::V1, GmaVersion::V2, GmaVersion::V3].iter() {
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut builder = GMABuilder::new();
+            builder
+                .version(*version)
+                .name("versioned")
+                .file_from_bytes("file1", b"hello".to_vec());
+            builder.write_to(Cursor::new(&mut buffer)).unwrap();
+            let archive = gma::load_from_memory(&buffer).unwrap();
+            assert_eq!(archive.version(), *version);
+            assert_eq!(archive.name(), "versioned");
+            assert_eq!(archive.entries().next().unwrap().filename(), "file1");
+        }
+    }
+
+    #[test]
+    fn unknown_version() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.version(9).name("versioned");
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        assert!(matches!(
+            gma::load_from_memory(&buffer),
+            Err(gma::Error::InvalidVersion(9))
+        ));
+    }
 }
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod test {
+    use gma::Compression;
+
+    #[test]
+    fn capabilities() {
+        let capabilities = gma::capabilities();
+        assert_eq!(capabilities.version(), env!("CARGO_PKG_VERSION"));
+        assert!(capabilities
+            .compression_formats()
+            .contains(&Compression::Lzma));
+        assert_eq!(capabilities.has_feature("brotli"), cfg!(feature = "brotli"));
+        assert_eq!(
+            capabilities.has_feature("crc32fast"),
+            cfg!(feature = "crc32fast")
+        );
+        assert!(!capabilities.has_feature("mmap"));
+
+        let json = capabilities.to_json();
+        assert!(json.contains("\"compression_formats\":[\"none\",\"lzma\""));
+        for feature in capabilities.features() {
+            assert!(json.contains(&format!("\"{}\"", feature)));
+        }
+    }
+}
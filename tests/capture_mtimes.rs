@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::{
+        io::Cursor,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn capture_mtimes() {
+        let dir = std::env::temp_dir().join(format!("gma-test-mtimes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("init.lua");
+        std::fs::write(&path, b"print('disk')").unwrap();
+        let modified = UNIX_EPOCH + Duration::from_secs(1595515015);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let build = |capture: bool| {
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut builder = GMABuilder::new();
+            builder
+                .name("mtimes")
+                .capture_mtimes(capture)
+                .file_from_bytes("lua/memory.lua", b"print('memory')".to_vec())
+                .file_with_name(&path, "lua/init.lua")
+                .unwrap();
+            builder
+                .write_to_with_report(Cursor::new(&mut buffer))
+                .unwrap()
+        };
+
+        let report = build(false);
+        assert!(report.files().iter().all(|file| file.modified().is_none()));
+
+        let report = build(true);
+        assert_eq!(report.files()[0].modified(), None);
+        assert_eq!(report.files()[1].modified(), Some(modified));
+
+        let mut manifest = Vec::new();
+        report.write_manifest(&mut manifest).unwrap();
+        let manifest = String::from_utf8(manifest).unwrap();
+        assert!(manifest.contains("\"filename\":\"lua/init.lua\""));
+        assert!(manifest.contains("\"modified\":1595515015"));
+        assert!(manifest.contains("\"crc\":1499690957}"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
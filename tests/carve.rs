@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod test {
+    use crc::{Crc, CRC_32_ISO_HDLC};
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build(name: &str) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name(name)
+            .file_from_bytes("lua/a.lua", b"print(\"a\")".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn carve() {
+        let first = build("first");
+        let mut second = build("second");
+        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&second);
+        second.extend_from_slice(&crc.to_le_bytes());
+
+        let mut blob = b"cache header GMAD not an archive ".to_vec();
+        let first_offset = blob.len();
+        blob.extend_from_slice(&first);
+        blob.extend_from_slice(b"padding between archives");
+        let second_offset = blob.len();
+        blob.extend_from_slice(&second);
+        blob.extend_from_slice(b"trailer");
+
+        let carved: Vec<_> = gma::carve(&blob).collect();
+        assert_eq!(carved.len(), 2);
+        let (offset, archive) = &carved[0];
+        assert_eq!(*offset, first_offset as u64);
+        assert_eq!(archive.name(), "first");
+        assert_eq!(archive.archive_crc(), None);
+        let (offset, archive) = &carved[1];
+        assert_eq!(*offset, second_offset as u64);
+        assert_eq!(archive.name(), "second");
+        archive.verify_archive_crc().unwrap();
+        let entry = archive.entries().next().unwrap();
+        let contents = archive
+            .read_entry_verified(entry, |_, reader| {
+                let mut contents = Vec::new();
+                reader.read_to_end(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(contents, b"print(\"a\")");
+    }
+
+    #[test]
+    fn nothing_to_carve() {
+        assert_eq!(gma::carve(b"").count(), 0);
+        assert_eq!(gma::carve(b"GMAD").count(), 0);
+        assert_eq!(gma::carve(b"random bytes without any archive").count(), 0);
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test {
+    use gma::{EntryView, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn build(files: usize) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("compact");
+        for i in 0..files {
+            builder.file_from_bytes(format!("lua/autorun/file_{}.lua", i), vec![b'a'; i % 7]);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn same_entries_as_regular() {
+        let buffer = build(50);
+        let regular = gma::load_from_memory(&buffer).unwrap();
+        let compact = LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let regular_views: Vec<EntryView> = regular.entry_views().collect();
+        let compact_views: Vec<EntryView> = compact.entry_views().collect();
+        assert_eq!(regular_views, compact_views);
+        assert_eq!(compact.metadata(), regular.metadata());
+
+        let view = compact_views[10];
+        assert_eq!(view.filename(), "lua/autorun/file_10.lua");
+        assert_eq!(view.parent(), Some("lua/autorun"));
+        let contents = compact
+            .read_entry(&view.to_entry(), |_, reader| {
+                let mut c = Vec::new();
+                reader.read_to_end(&mut c).unwrap();
+                c
+            })
+            .unwrap();
+        assert_eq!(contents, vec![b'a'; 3]);
+    }
+
+    #[test]
+    fn regular_accessors_still_work() {
+        let buffer = build(10);
+        let archive = LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(archive.entries().count(), 10);
+        assert_eq!(archive["lua/autorun/file_4.lua"].size(), 4);
+        assert_eq!(archive.entries().nth(9).unwrap().index(), 9);
+    }
+
+    #[test]
+    fn uses_less_memory() {
+        let buffer = build(2000);
+        let regular = gma::load_from_memory(&buffer).unwrap();
+        let compact = LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert!(compact.approx_memory_usage() * 3 < regular.approx_memory_usage() * 2);
+    }
+
+    #[test]
+    fn views_keep_the_table_compact() {
+        let buffer = build(500);
+        let archive = LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let usage = archive.approx_memory_usage();
+
+        let view = archive.get_view("lua/autorun/file_12.lua").unwrap();
+        assert_eq!(view.index(), 12);
+        assert!(archive.get_view("lua/autorun/missing.lua").is_none());
+        assert!(archive.validate().unwrap().is_valid());
+        assert!(archive.non_ascii_entries().is_empty());
+        gma::analysis::lua_stats(&archive).unwrap();
+        gma::transform::rename_entries(&archive, |_: &str| None).unwrap();
+        let dir = std::env::temp_dir().join(format!("gma-test-compact-{}", std::process::id()));
+        archive
+            .extract_to(&dir, &gma::ExtractOptions::new())
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(archive.approx_memory_usage(), usage);
+
+        // the accessors returning a FileEntry still work, but build the entries
+        assert_eq!(archive["lua/autorun/file_12.lua"].index(), 12);
+        assert!(archive.approx_memory_usage() > usage);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, Error, GMABuilder};
+    use std::io::Cursor;
+
+    fn build_archive(format: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("compression")
+            .compression_format(format)
+            .file_from_bytes("lua/init.lua", b"print('compressed')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn lzma() {
+        let buffer = build_archive(Compression::Lzma);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(archive.compressed());
+        assert_eq!(archive.compression(), Compression::Lzma);
+        assert_eq!(archive.name(), "compression");
+    }
+
+    #[test]
+    fn uncompressed() {
+        let buffer = build_archive(Compression::None);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(!archive.compressed());
+        assert_eq!(archive.compression(), Compression::None);
+    }
+
+    #[test]
+    fn invalid_compressed_data() {
+        let result = gma::load_from_memory(b"not a gma file at all");
+        assert!(matches!(result, Err(Error::CompressionError(_))));
+    }
+
+    fn probe(name: &str, data: &[u8]) -> gma::Result<Compression> {
+        let path = std::env::temp_dir().join(format!(
+            "gma-test-probe-{}-{}.gma",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, data).unwrap();
+        let result = gma::probe_compression(&path);
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn probe_compression() {
+        let plain = build_archive(Compression::None);
+        assert_eq!(probe("plain", &plain).unwrap(), Compression::None);
+        let lzma = build_archive(Compression::Lzma);
+        assert_eq!(probe("lzma", &lzma).unwrap(), Compression::Lzma);
+        // only the start of the stream is needed
+        assert_eq!(probe("lzma-start", &lzma[..40]).unwrap(), Compression::Lzma);
+        assert!(matches!(
+            probe("invalid", b"not a gma file at all"),
+            Err(Error::CompressionError(_))
+        ));
+
+        let mut not_gma = Vec::new();
+        lzma_rs::lzma_compress(&mut &b"some other file"[..], &mut not_gma).unwrap();
+        assert!(probe("lzma-not-gma", &not_gma).is_err());
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn brotli() {
+        let buffer = build_archive(Compression::Brotli);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(archive.compressed());
+        assert_eq!(archive.compression(), Compression::Brotli);
+        let entry = &archive["lua/init.lua"];
+        let mut contents = Vec::new();
+        archive.read_entry_to_writer(entry, &mut contents).unwrap();
+        assert_eq!(contents, b"print('compressed')");
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn probe_brotli() {
+        let buffer = build_archive(Compression::Brotli);
+        assert_eq!(probe("brotli", &buffer).unwrap(), Compression::Brotli);
+    }
+
+    // a Read that is neither BufRead nor Seek, like a pipe
+    struct Pipe<'a>(&'a [u8]);
+
+    impl std::io::Read for Pipe<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn load_compressed_from_pipe() {
+        let buffer = build_archive(Compression::Lzma);
+        let archive = gma::load_compressed(Pipe(&buffer)).unwrap();
+        assert_eq!(archive.compression(), Compression::Lzma);
+        assert_eq!(archive.name(), "compression");
+        let contents = archive
+            .read_entry(&archive["lua/init.lua"], |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(contents, "print('compressed')");
+
+        let buffer = build_archive(Compression::None);
+        let archive = gma::load_compressed(Pipe(&buffer)).unwrap();
+        assert!(!archive.compressed());
+    }
+}
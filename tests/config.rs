@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod test {
+    use gma::{Config, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn build(filename: &str) -> std::result::Result<Vec<u8>, gma::Error> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("config")
+            .file_from_bytes(filename, b"a".to_vec());
+        builder.write_to(Cursor::new(&mut buffer))?;
+        Ok(buffer)
+    }
+
+    // the defaults are shared by the whole process, so everything is checked in a single test
+    #[test]
+    fn global_defaults() {
+        assert_eq!(Config::global(), Config::new());
+        let buffer = build("lua/A.lua").unwrap();
+        build("lua/é.lua").unwrap();
+
+        Config::update_global(|config| {
+            config
+                .max_string_length(2)
+                .case_insensitive_lookups(true)
+                .strict_ascii(true)
+                .buffer_size(64);
+        });
+        assert!(matches!(
+            gma::load_from_memory(&buffer),
+            Err(gma::Error::StringTooLong(2))
+        ));
+        assert!(matches!(
+            build("lua/é.lua"),
+            Err(gma::Error::NonAsciiFilenames(_))
+        ));
+
+        // per call options override the global defaults
+        let archive = LoadOptions::new()
+            .max_string_length(1024)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert!(archive.get("LUA/a.lua").is_some());
+        let mut builder = GMABuilder::new();
+        builder
+            .strict_ascii(false)
+            .name("config")
+            .file_from_bytes("lua/é.lua", b"a".to_vec());
+        builder.write_to(Cursor::new(Vec::new())).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("gma-test-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.gma"), &buffer).unwrap();
+        let mut options = LoadOptions::new();
+        options.max_string_length(1024);
+        assert_eq!(
+            options.open(dir.join("a.gma")).unwrap().entries().count(),
+            1
+        );
+
+        // the limits of later options, a buffer can't be empty
+        Config::set_global(Config::new());
+        Config::update_global(|config| {
+            config
+                .max_entries(0)
+                .verify_crc(true)
+                .strict_whitelist(true)
+                .buffer_size(0);
+        });
+        assert!(matches!(
+            gma::load_from_memory(&buffer),
+            Err(gma::Error::TooManyEntries(0))
+        ));
+        assert!(matches!(
+            build("lua/a.exe"),
+            Err(gma::Error::NotWhitelisted(_))
+        ));
+        let mut corrupted = buffer.clone();
+        let at = corrupted.len() - 1;
+        corrupted[at] = b'b';
+        let archive = LoadOptions::new()
+            .max_entries(1)
+            .load_from_memory(&corrupted)
+            .unwrap();
+        assert!(matches!(
+            archive.read_entry(&archive["lua/A.lua"], |_, _| ()),
+            Err(gma::Error::CrcMismatch { .. })
+        ));
+        let archive = LoadOptions::new()
+            .max_entries(1)
+            .buffer_capacity(0)
+            .open(dir.join("a.gma"))
+            .unwrap();
+        archive
+            .read_entry(&archive["lua/A.lua"], |_, _| ())
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        Config::set_global(Config::new());
+        assert!(gma::load_from_memory(&buffer)
+            .unwrap()
+            .get("LUA/a.lua")
+            .is_none());
+    }
+}
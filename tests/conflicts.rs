@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("conflicts");
+        for (name, contents) in files {
+            builder.file_from_bytes(*name, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn conflicts() {
+        let a = build_archive(&[
+            ("materials/shared.vmt", b"same"),
+            ("lua/autorun/init.lua", b"a"),
+            ("materials/only_a.vmt", b"a"),
+        ]);
+        let b = build_archive(&[
+            ("LUA/autorun/init.lua", b"b"),
+            ("materials/shared.vmt", b"same"),
+            ("materials/only_b.vmt", b"b"),
+        ]);
+        let a = gma::load_from_memory(&a).unwrap();
+        let b = gma::load_from_memory(&b).unwrap();
+
+        let conflicts = gma::conflicts(&a, &b);
+        let summary: Vec<(&str, &str, bool)> = conflicts
+            .iter()
+            .map(|c| (c.filename(), c.second().filename(), c.same_contents()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("materials/shared.vmt", "materials/shared.vmt", true),
+                ("lua/autorun/init.lua", "LUA/autorun/init.lua", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder};
+    use std::io::Cursor;
+
+    fn build_archive(format: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("decompressed_size")
+            .timestamp(0)
+            .compression_format(format)
+            .file_from_bytes("lua/init.lua", b"print('size')".repeat(100));
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn decompressed_size() {
+        let plain = build_archive(Compression::None);
+        let compressed = build_archive(Compression::Lzma);
+
+        let archive = gma::load_from_memory(&plain).unwrap();
+        assert_eq!(archive.decompressed_size(), None);
+        let archive = gma::load_from_memory(&compressed).unwrap();
+        assert_eq!(archive.decompressed_size(), Some(plain.len() as u64));
+
+        let dir = std::env::temp_dir().join(format!("gma-test-size-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain_path = dir.join("plain.gma");
+        let compressed_path = dir.join("compressed.gma");
+        std::fs::write(&plain_path, &plain).unwrap();
+        std::fs::write(&compressed_path, &compressed).unwrap();
+        assert_eq!(
+            gma::probe_decompressed_size(&plain_path).unwrap(),
+            Some(plain.len() as u64)
+        );
+        assert_eq!(
+            gma::probe_decompressed_size(&compressed_path).unwrap(),
+            Some(plain.len() as u64)
+        );
+
+        // lzma streams can leave the size out of the header
+        let mut unknown = compressed.clone();
+        unknown[5..13].copy_from_slice(&[0xff; 8]);
+        std::fs::write(&compressed_path, &unknown).unwrap();
+        assert_eq!(
+            gma::probe_decompressed_size(&compressed_path).unwrap(),
+            None
+        );
+        let archive = gma::load_from_memory(&unknown).unwrap();
+        assert_eq!(archive.decompressed_size(), Some(plain.len() as u64));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod test {
+    use gma::{BuildReport, BuildWarning, GMABuilder};
+    use std::io::Cursor;
+
+    fn build(description: &str, configure: impl FnOnce(&mut GMABuilder)) -> (BuildReport, String) {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("description").description(description);
+        configure(&mut builder);
+        let report = builder
+            .write_to_with_report(Cursor::new(&mut buffer))
+            .unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        (report, archive.description().to_owned())
+    }
+
+    #[test]
+    fn short_description() {
+        let (report, description) = build("short", |_| {});
+        assert!(report.warnings().is_empty());
+        assert_eq!(description, "short");
+    }
+
+    #[test]
+    fn detects_long_description() {
+        let long = "a".repeat(gma::MAX_DESCRIPTION_LENGTH + 1);
+        let (report, description) = build(&long, |_| {});
+        assert_eq!(
+            report.warnings(),
+            &[BuildWarning::LongDescription {
+                len: long.len(),
+                max: gma::MAX_DESCRIPTION_LENGTH,
+                truncated: false
+            }]
+        );
+        assert_eq!(description, long);
+    }
+
+    #[test]
+    fn truncates_long_description() {
+        let (report, description) = build("ééééé", |builder| {
+            builder.max_description_length(8).truncate_description(true);
+        });
+        assert_eq!(
+            report.warnings(),
+            &[BuildWarning::LongDescription {
+                len: 10,
+                max: 8,
+                truncated: true
+            }]
+        );
+        // 5 bytes are left for the text, which can only hold 2 whole characters
+        assert_eq!(description, "éé...");
+
+        let (_, description) = build("abcdef", |builder| {
+            builder.max_description_length(2).truncate_description(true);
+        });
+        assert_eq!(description, "ab");
+    }
+
+    #[test]
+    fn raw_metadata_ignores_description() {
+        let long = "a".repeat(gma::MAX_DESCRIPTION_LENGTH + 1);
+        let json = r#"{"description":"raw","type":"tool","tags":[]}"#;
+        let (report, description) = build(&long, |builder| {
+            builder.raw_metadata(json).truncate_description(true);
+        });
+        assert!(report.warnings().is_empty());
+        assert_eq!(description, "raw");
+    }
+}
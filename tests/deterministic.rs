@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder};
+    use std::io::Cursor;
+
+    fn build(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("deterministic").deterministic();
+        for (name, contents) in files {
+            builder.file_from_bytes(*name, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    // a single test since it changes the environment of the whole process
+    #[test]
+    fn deterministic() {
+        let a: (&str, &[u8]) = ("lua/a.lua", b"print('a')");
+        let b: (&str, &[u8]) = ("lua/b.lua", b"print('b')");
+        let m: (&str, &[u8]) = ("materials/m.vmt", b"vmt");
+
+        std::env::remove_var("SOURCE_DATE_EPOCH");
+        let buffer = build(&[m, b, a]);
+        assert_eq!(buffer, build(&[a, m, b]));
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.timestamp(), 0);
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, vec!["lua/a.lua", "lua/b.lua", "materials/m.vmt"]);
+
+        std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+        let buffer = build(&[b, a]);
+        std::env::remove_var("SOURCE_DATE_EPOCH");
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.timestamp(), 1_700_000_000);
+
+        // an explicit timestamp wins
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("deterministic")
+            .deterministic()
+            .timestamp(42)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        assert_eq!(gma::load_from_memory(&buffer).unwrap().timestamp(), 42);
+
+        // a malformed epoch is an error instead of a silent 0, unless it is overridden
+        std::env::set_var("SOURCE_DATE_EPOCH", "2023-11-14");
+        let builder = |timestamp: Option<u64>| {
+            let mut builder = GMABuilder::new();
+            builder
+                .name("deterministic")
+                .deterministic()
+                .file_from_bytes("lua/a.lua", b"print('a')".to_vec());
+            if let Some(timestamp) = timestamp {
+                builder.timestamp(timestamp);
+            }
+            builder
+        };
+        match builder(None).write_to(Cursor::new(Vec::new())) {
+            Err(Error::InvalidSourceDateEpoch(epoch)) => assert_eq!(epoch, "2023-11-14"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        builder(Some(42)).write_to(Cursor::new(Vec::new())).unwrap();
+        std::env::remove_var("SOURCE_DATE_EPOCH");
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(all(test, feature = "difftest"))]
+mod test {
+    use gma::difftest::{compare, generate_corpus, Gmad};
+    use gma::AddonSource;
+    use std::path::PathBuf;
+
+    fn work_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gma-difftest-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn corpus_is_deterministic() {
+        assert_eq!(generate_corpus(7, 5), generate_corpus(7, 5));
+        assert_ne!(generate_corpus(7, 5), generate_corpus(8, 5));
+    }
+
+    #[test]
+    fn folder_and_builder_agree() {
+        let dir = work_dir("folder");
+        for (index, addon) in generate_corpus(42, 8).iter().enumerate() {
+            let folder = dir.join(index.to_string());
+            addon.write_folder(&folder).unwrap();
+            let packed = AddonSource::Folder(folder).load().unwrap();
+            let built = addon.to_gma().unwrap();
+            let built = gma::load_from_memory(&built).unwrap();
+            assert_eq!(packed.name(), addon.title);
+            assert_eq!(packed.description(), built.description());
+            assert_eq!(packed.addon_type(), Some(addon.addon_type));
+            assert_eq!(packed.entries().count(), addon.files.len());
+            for (filename, contents) in addon.files.iter() {
+                let read = packed
+                    .read_entry(&packed[filename.as_str()], |_, reader| {
+                        let mut c = Vec::new();
+                        reader.read_to_end(&mut c).unwrap();
+                        c
+                    })
+                    .unwrap();
+                assert_eq!(&read, contents);
+            }
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn matches_gmad() {
+        let gmad = match Gmad::find() {
+            Some(gmad) => gmad,
+            None => {
+                eprintln!("gmad not found in PATH, skipping");
+                return;
+            }
+        };
+        let dir = work_dir("gmad");
+        let mut report = Vec::new();
+        for addon in generate_corpus(1, 20) {
+            for divergence in compare(&gmad, &addon, &dir).unwrap() {
+                report.push(format!("{} : {}", addon.title, divergence));
+            }
+        }
+        let _ = std::fs::remove_dir_all(dir);
+        assert!(report.is_empty(), "{}", report.join("\n"));
+    }
+}
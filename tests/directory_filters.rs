@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, SkipReason};
+    use std::{fs, io::Cursor, path::PathBuf};
+
+    fn create_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("gma-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("lua/autorun")).unwrap();
+        fs::write(root.join(".git/config"), b"[core]").unwrap();
+        fs::write(root.join("lua/autorun/init.lua"), b"print('init')").unwrap();
+        fs::write(root.join("lua/autorun/.swp"), b"junk").unwrap();
+        fs::write(root.join("lua/autorun/big.lua"), vec![b'-'; 1024]).unwrap();
+        fs::write(root.join("lua/autorun/secret.lua"), b"secret").unwrap();
+        root
+    }
+
+    #[test]
+    fn directory_filters() {
+        let root = create_dir("filters");
+        let mut builder = GMABuilder::new();
+        builder
+            .name("filters")
+            .skip_hidden(true)
+            .max_file_size(512)
+            .path_filter(|path| !path.ends_with("secret.lua"))
+            .files_from_glob(&root, &["*"])
+            .unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let report = builder
+            .write_to_with_report(Cursor::new(&mut buffer))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let written: Vec<&str> = report.files().iter().map(|f| f.filename()).collect();
+        assert_eq!(written, vec!["lua/autorun/init.lua"]);
+        assert_eq!(report.files()[0].size(), 13);
+
+        let skipped: Vec<(&str, &SkipReason)> = report
+            .skipped()
+            .iter()
+            .map(|s| (s.filename(), s.reason()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (".git/config", &SkipReason::Hidden),
+                ("lua/autorun/.swp", &SkipReason::Hidden),
+                ("lua/autorun/big.lua", &SkipReason::TooLarge(1024)),
+                ("lua/autorun/secret.lua", &SkipReason::Filtered),
+            ]
+        );
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entries().count(), 1);
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let root = create_dir("ignore");
+        fs::create_dir_all(root.join("materials")).unwrap();
+        fs::write(root.join("addon.json"), b"{}").unwrap();
+        fs::write(root.join("materials/source.PSD"), b"psd").unwrap();
+        fs::write(root.join("materials/a.vmt"), b"vmt").unwrap();
+        fs::write(
+            root.join(".gmaignore"),
+            b"# local files\n\n*.swp\nlua/autorun/secret.lua\n",
+        )
+        .unwrap();
+
+        let mut builder = GMABuilder::new();
+        builder
+            .name("ignore")
+            .ignore_defaults()
+            .ignore_file(root.join(".gmaignore"))
+            .unwrap()
+            .ignore(".gmaignore")
+            .ignore("*/big.*")
+            .add_dir(&root)
+            .unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        let report = builder
+            .write_to_with_report(Cursor::new(&mut buffer))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let written: Vec<&str> = report.files().iter().map(|f| f.filename()).collect();
+        assert_eq!(written, vec!["lua/autorun/init.lua", "materials/a.vmt"]);
+        let skipped: Vec<(&str, &SkipReason)> = report
+            .skipped()
+            .iter()
+            .map(|s| (s.filename(), s.reason()))
+            .collect();
+        let ignored = |pattern: &str| SkipReason::Ignored(pattern.to_owned());
+        assert_eq!(
+            skipped,
+            vec![
+                (".git/config", &ignored(".git/*")),
+                (".gmaignore", &ignored(".gmaignore")),
+                ("addon.json", &ignored("addon.json")),
+                ("lua/autorun/.swp", &ignored("*.swp")),
+                ("lua/autorun/big.lua", &ignored("*/big.*")),
+                ("lua/autorun/secret.lua", &ignored("lua/autorun/secret.lua")),
+                ("materials/source.PSD", &ignored("*.psd")),
+            ]
+        );
+    }
+
+    #[test]
+    fn directory_filters_disabled() {
+        let root = create_dir("no-filters");
+        let mut builder = GMABuilder::new();
+        builder
+            .name("no filters")
+            .files_from_glob(&root, &["*"])
+            .unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let report = builder
+            .write_to_with_report(Cursor::new(&mut buffer))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(report.files().len(), 5);
+        assert!(report.skipped().is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn duplicate_entries() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("duplicates")
+            .file_from_bytes("lua/autorun/init.lua", b"a".to_vec())
+            .file_from_bytes("materials/a.vmt", b"b".to_vec())
+            .file_from_bytes("LUA/Autorun/init.lua", b"c".to_vec())
+            .file_from_bytes("lua/autorun/init.lua", b"d".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let duplicates = archive.duplicate_entries();
+        assert_eq!(duplicates.len(), 1);
+        let names: Vec<&str> = duplicates[0].iter().map(|e| e.filename()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "lua/autorun/init.lua",
+                "LUA/Autorun/init.lua",
+                "lua/autorun/init.lua"
+            ]
+        );
+    }
+
+    #[test]
+    fn no_duplicate_entries() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        assert!(archive.duplicate_entries().is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test {
+    use gma::{DuplicatePolicy, Error, GMABuilder};
+    use std::io::Cursor;
+
+    fn builder(policy: DuplicatePolicy) -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("duplicates")
+            .duplicates(policy)
+            .file_from_bytes("lua/a.lua", b"print('first')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec())
+            .file_from_bytes("LUA/A.lua", b"print('second')".to_vec());
+        builder
+    }
+
+    fn entries(builder: GMABuilder) -> Vec<(String, String)> {
+        let mut buffer: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        archive
+            .entries()
+            .map(|entry| {
+                let contents = archive
+                    .read_entry(entry, |_, reader| {
+                        let mut contents = String::new();
+                        reader.read_to_string(&mut contents).map(|_| contents)
+                    })
+                    .unwrap()
+                    .unwrap();
+                (entry.filename().to_owned(), contents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keep_duplicates() {
+        let names: Vec<String> = entries(builder(DuplicatePolicy::Keep))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["lua/a.lua", "lua/b.lua", "LUA/A.lua"]);
+    }
+
+    #[test]
+    fn reject_duplicates() {
+        let mut buffer: Vec<u8> = Vec::new();
+        match builder(DuplicatePolicy::Reject).write_to(Cursor::new(&mut buffer)) {
+            Err(Error::DuplicateFilenames(filenames)) => assert_eq!(filenames, vec!["lua/a.lua"]),
+            other => panic!("expected DuplicateFilenames, got {:?}", other.err()),
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn replace_duplicates() {
+        assert_eq!(
+            entries(builder(DuplicatePolicy::Replace)),
+            vec![
+                ("lua/b.lua".to_owned(), "print('b')".to_owned()),
+                ("LUA/A.lua".to_owned(), "print('second')".to_owned()),
+            ]
+        );
+    }
+}
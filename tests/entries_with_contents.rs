@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("contents")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/empty.lua", Vec::new())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn entries_with_contents() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let iter = archive.entries_with_contents();
+        assert_eq!(iter.len(), 3);
+        let items: Vec<(String, Vec<u8>)> = iter
+            .map(|item| {
+                let (entry, contents) = item.unwrap();
+                // other reads still work while iterating
+                assert_eq!(archive.read_entry(entry, |_, _| ()).ok(), Some(()));
+                (entry.filename().to_owned(), contents)
+            })
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("lua/a.lua".to_owned(), b"print('a')".to_vec()),
+                ("lua/empty.lua".to_owned(), Vec::new()),
+                ("lua/b.lua".to_owned(), b"print('b')".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn entries_with_contents_continues_after_errors() {
+        let mut buffer = build_archive();
+        let at = buffer.windows(10).position(|w| w == b"print('a')").unwrap();
+        buffer[at] = b'P';
+        let archive = LoadOptions::new()
+            .verify_crc(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let results: Vec<_> = archive.entries_with_contents().collect();
+        assert!(matches!(results[0], Err(Error::CrcMismatch { .. })));
+        assert_eq!(results[2].as_ref().unwrap().1, b"print('b')");
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::cell::Cell;
+    use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
+    use std::rc::Rc;
+
+    // Cursor that counts the reads made through it
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: Rc<Cell<usize>>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.read(buf)
+        }
+    }
+
+    impl BufRead for CountingReader {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.fill_buf()
+        }
+        fn consume(&mut self, amt: usize) {
+            self.inner.consume(amt)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn read_string(archive: &gma::GMAFile<CountingReader>, filename: &str) -> String {
+        archive
+            .read_entry(&archive[filename], |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents).unwrap();
+                contents
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn entry_cache() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("cache")
+            .file_from_bytes("small.lua", b"small".to_vec())
+            .file_from_bytes("large.lua", vec![b'l'; 1000]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let reads = Rc::new(Cell::new(0));
+        let archive = gma::LoadOptions::new()
+            .entry_cache(100, 1024)
+            .load(CountingReader {
+                inner: Cursor::new(buffer),
+                reads: reads.clone(),
+            })
+            .unwrap();
+
+        reads.set(0);
+        assert_eq!(read_string(&archive, "small.lua"), "small");
+        assert!(reads.get() > 0);
+        reads.set(0);
+        assert_eq!(read_string(&archive, "small.lua"), "small");
+        assert_eq!(reads.get(), 0);
+
+        // too large to be cached
+        assert_eq!(read_string(&archive, "large.lua").len(), 1000);
+        reads.set(0);
+        assert_eq!(read_string(&archive, "large.lua").len(), 1000);
+        assert!(reads.get() > 0);
+    }
+
+    #[test]
+    fn cached_archive_is_send() {
+        // the cached contents must not make the archive !Send
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("cache")
+            .file_from_bytes("small.lua", b"small".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::LoadOptions::new()
+            .entry_cache(100, 1024)
+            .load(Cursor::new(buffer))
+            .unwrap();
+        let contents = std::thread::spawn(move || {
+            archive
+                .read_entry(&archive["small.lua"], |_, reader| {
+                    let mut contents = String::new();
+                    reader.read_to_string(&mut contents).unwrap();
+                    contents
+                })
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(contents, "small");
+    }
+}
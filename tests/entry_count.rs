@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build(files: usize) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("count");
+        for i in 0..files {
+            builder.file_from_bytes(format!("lua/{}.lua", i), vec![b'a'; i]);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn exact_size() {
+        let buffer = build(5);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entry_count(), 5);
+        assert!(!archive.is_empty());
+        let mut entries = archive.entries();
+        assert_eq!(entries.len(), 5);
+        entries.next();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(archive.entry_views().len(), 5);
+
+        let reversed: Vec<usize> = archive.entries().rev().map(|e| e.index()).collect();
+        assert_eq!(reversed, [4, 3, 2, 1, 0]);
+        let last = archive.entry_views().next_back().unwrap();
+        assert_eq!(last.filename(), "lua/4.lua");
+    }
+
+    #[test]
+    fn compact() {
+        let buffer = build(5);
+        let archive = gma::LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(archive.entry_count(), 5);
+        assert_eq!(archive.entry_views().next_back().unwrap().index(), 4);
+    }
+
+    #[test]
+    fn empty() {
+        let buffer = build(0);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(archive.is_empty());
+        assert_eq!(archive.entries().len(), 0);
+    }
+}
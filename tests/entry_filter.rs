@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("filter")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("bin/tool.exe", b"MZ".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn no_executables(entry: &gma::FileEntryHeader) -> bool {
+        !entry.filename().ends_with(".exe")
+    }
+
+    #[test]
+    fn drops_rejected_entries() {
+        let buffer = build_archive();
+        for compact in [false, true] {
+            let archive = LoadOptions::new()
+                .compact(compact)
+                .entry_filter(no_executables)
+                .load_from_memory(&buffer)
+                .unwrap();
+            assert_eq!(archive.entry_count(), 2);
+            assert!(archive.get("bin/tool.exe").is_none());
+
+            // the kept entries still point at their contents
+            let entry = archive.get("lua/b.lua").unwrap();
+            assert_eq!(entry.index(), 1);
+            let contents = archive
+                .read_entry(entry, |_, reader| {
+                    let mut contents = String::new();
+                    reader.read_to_string(&mut contents).map(|_| contents)
+                })
+                .unwrap()
+                .unwrap();
+            assert_eq!(contents, "print('b')");
+        }
+    }
+
+    #[test]
+    fn sees_table_fields() {
+        let buffer = build_archive();
+        let archive = LoadOptions::new()
+            .entry_filter(|entry| entry.table_index() == 2 && entry.size() == 10)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, ["lua/b.lua"]);
+    }
+
+    #[test]
+    fn keeps_archive_bounds() {
+        let buffer = include_bytes!("genuine.gma");
+        for compact in [false, true] {
+            // dropping entries must not move the end of the archive
+            let archive = LoadOptions::new()
+                .compact(compact)
+                .entry_filter(|_| false)
+                .load_from_memory(buffer)
+                .unwrap();
+            assert_eq!(archive.entry_count(), 0);
+            archive.verify_archive_crc().unwrap();
+            let buffered = archive.buffer_all().unwrap();
+            buffered.verify_archive_crc().unwrap();
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("filters")
+            .file_from_bytes("lua/autorun/init.LUA", Vec::new())
+            .file_from_bytes("Materials/a.vmt", Vec::new())
+            .file_from_bytes("materials/sub/b.vtf", Vec::new())
+            .file_from_bytes("materials_extra/c.vmt", Vec::new())
+            .file_from_bytes("readme", Vec::new());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn names<'a>(entries: impl Iterator<Item = &'a gma::FileEntry>) -> Vec<&'a str> {
+        entries.map(|e| e.filename()).collect()
+    }
+
+    #[test]
+    fn entries_with_extension() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(
+            names(archive.entries_with_extension(&["lua", ".vmt"])),
+            [
+                "lua/autorun/init.LUA",
+                "Materials/a.vmt",
+                "materials_extra/c.vmt"
+            ]
+        );
+        assert_eq!(archive.entries_with_extension(&[]).count(), 0);
+    }
+
+    #[test]
+    fn entries_under() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let expected = ["Materials/a.vmt", "materials/sub/b.vtf"];
+        assert_eq!(names(archive.entries_under("materials/")), expected);
+        assert_eq!(names(archive.entries_under("MATERIALS")), expected);
+        assert_eq!(
+            names(archive.entries_under("materials/sub")),
+            ["materials/sub/b.vtf"]
+        );
+        assert_eq!(archive.entries_under("").count(), 5);
+    }
+
+    #[test]
+    fn entries_under_non_ascii() {
+        let mut buffer = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("filters")
+            .file_from_bytes("Äpfel/a.vmt", Vec::new())
+            .file_from_bytes("materials/İ/b.vmt", Vec::new());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(names(archive.entries_under("Äpfel")), ["Äpfel/a.vmt"]);
+        assert_eq!(archive.entries_under("äpfel").count(), 0);
+        assert_eq!(
+            names(archive.entries_under("MATERIALS/İ")),
+            ["materials/İ/b.vmt"]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder, TextFallback};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("lines")
+            .file_from_bytes("lua/a.lua", b"local a = 1\r\nprint(a)\n\nreturn a".to_vec())
+            .file_from_bytes("lua/b.lua", b"-- caf\xe9\nprint('b')\n".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn splits_lines() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.get("lua/a.lua").unwrap();
+        let lines: Vec<String> = archive
+            .entry_lines(entry)
+            .unwrap()
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, ["local a = 1", "print(a)", "", "return a"]);
+
+        // stops at the end of the entry even without a trailing newline
+        let entry = archive.get("lua/b.lua").unwrap();
+        assert_eq!(archive.entry_lines(entry).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn fallbacks() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.get("lua/b.lua").unwrap();
+
+        let first = |fallback| archive.entry_lines_with(entry, fallback).unwrap().next();
+        assert_eq!(
+            first(TextFallback::Lossy).unwrap().unwrap(),
+            "-- caf\u{fffd}"
+        );
+        assert_eq!(first(TextFallback::Latin1).unwrap().unwrap(), "-- café");
+        assert!(matches!(
+            first(TextFallback::Strict).unwrap(),
+            Err(Error::UTF8Error(_))
+        ));
+
+        let mut lines = archive
+            .entry_lines_with(entry, TextFallback::Strict)
+            .unwrap();
+        assert!(lines.next().unwrap().is_err());
+        assert_eq!(lines.next().unwrap().unwrap(), "print('b')");
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn locks_reader() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.get("lua/a.lua").unwrap();
+        let lines = archive.entry_lines(entry).unwrap();
+        assert!(matches!(
+            archive.read_entry(entry, |_, _| ()),
+            Err(Error::ReaderInUse)
+        ));
+        drop(lines);
+        archive.read_entry(entry, |_, _| ()).unwrap();
+    }
+}
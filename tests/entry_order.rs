@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    const NAMES: [&str; 4] = ["lua/b.lua", "materials/a.vmt", "lua/a.lua", "lua/b.lua"];
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("order");
+        for (i, name) in NAMES.iter().enumerate() {
+            builder.file_from_bytes(*name, vec![i as u8]);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn entries_on_disk_order() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let mut previous_offset = None;
+        for (i, entry) in archive.entries().enumerate() {
+            assert_eq!(entry.index(), i);
+            assert_eq!(entry.filename(), NAMES[i]);
+            assert!(previous_offset < Some(entry.offset()));
+            previous_offset = Some(entry.offset());
+        }
+    }
+
+    #[test]
+    fn entries_sorted_by_name() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let sorted: Vec<(&str, usize)> = archive
+            .entries_sorted_by_name()
+            .into_iter()
+            .map(|e| (e.filename(), e.index()))
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![
+                ("lua/a.lua", 2),
+                ("lua/b.lua", 0),
+                ("lua/b.lua", 3),
+                ("materials/a.vmt", 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    fn build(compression: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("seek")
+            .compression(compression)
+            .file_from_bytes("before.bin", b"before".to_vec())
+            .file_from_bytes("maps/test.bsp", b"0123456789".to_vec())
+            .file_from_bytes("after.bin", b"after".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn read_n(reader: &mut impl Read, n: usize) -> Vec<u8> {
+        let mut buf = Vec::new();
+        reader.take(n as u64).read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn seek_within_entry() {
+        for compression in [false, true] {
+            let buffer = build(compression);
+            let archive = gma::load_from_memory(&buffer).unwrap();
+            archive
+                .read_entry_seekable(&archive["maps/test.bsp"], |_, reader| {
+                    assert_eq!(reader.len(), 10);
+                    assert_eq!(reader.seek(SeekFrom::Start(4)).unwrap(), 4);
+                    assert_eq!(read_n(reader, 2), b"45");
+                    assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 2);
+                    assert_eq!(read_n(reader, 1), b"2");
+                    assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 8);
+                    // reads stop at the end of the entry
+                    assert_eq!(read_n(reader, 100), b"89");
+                    assert_eq!(reader.seek(SeekFrom::End(5)).unwrap(), 15);
+                    assert_eq!(read_n(reader, 100), b"");
+                    assert!(reader.seek(SeekFrom::Current(-100)).is_err());
+                    reader.seek(SeekFrom::Start(0)).unwrap();
+                    let mut all = Vec::new();
+                    reader.read_to_end(&mut all).unwrap();
+                    assert_eq!(all, b"0123456789");
+                })
+                .unwrap();
+        }
+    }
+}
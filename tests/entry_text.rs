@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, TextEncoding, TextFallback};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("text")
+            .file_from_bytes("lua/utf8.lua", "-- café".as_bytes().to_vec())
+            .file_from_bytes("lua/ansi.lua", b"-- caf\xe9 \x93quoted\x94 \x80".to_vec())
+            .file_from_bytes("lua/lossy.lua", b"-- \x81".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn read_entry_text() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let text = archive
+            .read_entry_text(archive.get("lua/utf8.lua").unwrap())
+            .unwrap();
+        assert_eq!(text.encoding(), TextEncoding::Utf8);
+        assert!(!text.is_lossy());
+        assert_eq!(text.text(), "-- café");
+
+        let text = archive
+            .read_entry_text(archive.get("lua/ansi.lua").unwrap())
+            .unwrap();
+        assert_eq!(text.encoding(), TextEncoding::Windows1252);
+        assert!(!text.is_lossy());
+        assert_eq!(text.into_text(), "-- café \u{201C}quoted\u{201D} €");
+
+        let text = archive
+            .read_entry_text(archive.get("lua/lossy.lua").unwrap())
+            .unwrap();
+        assert_eq!(text.encoding(), TextEncoding::Windows1252);
+        assert!(text.is_lossy());
+        assert_eq!(text.text(), "-- \u{fffd}");
+    }
+
+    #[test]
+    fn windows1252_lines() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.get("lua/ansi.lua").unwrap();
+        let line = archive
+            .entry_lines_with(entry, TextFallback::Windows1252)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "-- café \u{201C}quoted\u{201D} €");
+    }
+}
@@ -0,0 +1,362 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, ExtractOptions, GMABuilder, LinkKind, PathPolicy};
+    use std::{
+        fs,
+        io::Cursor,
+        path::PathBuf,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gma-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn build_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("extract");
+        for (name, contents) in files {
+            builder.file_from_bytes(*name, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn extract_to() {
+        let dir = temp_dir("extract");
+        let buffer = build_archive(&[
+            ("lua/autorun/init.lua", b"print('init')"),
+            ("materials/a.vmt", b"vmt"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive.extract_to(&dir, &ExtractOptions::new()).unwrap();
+
+        assert_eq!(report.files().len(), 2);
+        assert_eq!(
+            fs::read(dir.join("lua/autorun/init.lua")).unwrap(),
+            b"print('init')"
+        );
+        assert_eq!(fs::read(dir.join("materials/a.vmt")).unwrap(), b"vmt");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_unsafe_path() {
+        let dir = temp_dir("extract-unsafe");
+        let buffer = build_archive(&[("lua/init.lua", b"ok"), ("../evil.lua", b"evil")]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let result = archive.extract_to(&dir, &ExtractOptions::new());
+        assert!(matches!(result, Err(Error::UnsafePath(name)) if name == "../evil.lua"));
+        //nothing was written
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn extract_strip_unsafe_paths() {
+        let dir = temp_dir("extract-strip");
+        let buffer = build_archive(&[
+            ("../../lua/escaped.lua", b"escaped"),
+            ("/etc/absolute.txt", b"absolute"),
+            ("C:\\windows\\drive.txt", b"drive"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let result = archive.extract_to(&dir, &ExtractOptions::new());
+        assert!(matches!(result, Err(Error::UnsafePath(_))));
+
+        let mut options = ExtractOptions::new();
+        options.path_policy(PathPolicy::Strip);
+        archive.extract_to(&dir, &options).unwrap();
+        assert_eq!(fs::read(dir.join("lua/escaped.lua")).unwrap(), b"escaped");
+        assert_eq!(fs::read(dir.join("etc/absolute.txt")).unwrap(), b"absolute");
+        assert_eq!(fs::read(dir.join("windows/drive.txt")).unwrap(), b"drive");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_selected() {
+        let dir = temp_dir("extract-selected");
+        let buffer = build_archive(&[
+            ("lua/autorun/init.lua", b"init"),
+            ("lua/readme.txt", b"readme"),
+            ("maps/gm_test.bsp", b"bsp"),
+            ("materials/a.vmt", b"vmt"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let mut options = ExtractOptions::new();
+        options.include("lua/*").include("MAPS/*").exclude("*.txt");
+        let report = archive.extract_to(&dir, &options).unwrap();
+
+        let names: Vec<&str> = report.files().iter().map(|f| f.filename()).collect();
+        assert_eq!(names, ["lua/autorun/init.lua", "maps/gm_test.bsp"]);
+        assert!(dir.join("maps/gm_test.bsp").exists());
+        assert!(!dir.join("lua/readme.txt").exists());
+        assert!(!dir.join("materials").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_mapped() {
+        let dir = temp_dir("extract-mapped");
+        let buffer = build_archive(&[
+            ("materials/a/one.vmt", b"one"),
+            ("materials/b/two.vmt", b"two"),
+            ("lua/init.lua", b"init"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive
+            .extract_mapped(&dir, &ExtractOptions::new(), |entry| {
+                match entry.extension() {
+                    Some("vmt") => Some(format!("flat/{}", entry.file_name())),
+                    _ => None,
+                }
+            })
+            .unwrap();
+        assert_eq!(report.files().len(), 2);
+        assert_eq!(report.files()[1].filename(), "materials/b/two.vmt");
+        assert_eq!(report.files()[1].path(), dir.join("flat/two.vmt"));
+        assert_eq!(fs::read(dir.join("flat/one.vmt")).unwrap(), b"one");
+        assert!(!dir.join("lua").exists());
+
+        // mapped paths are checked too
+        let result = archive.extract_mapped(&dir, &ExtractOptions::new(), |_| {
+            Some("../escaped".to_owned())
+        });
+        assert!(matches!(result, Err(Error::UnsafePath(name)) if name == "../escaped"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_plan() {
+        let dir = temp_dir("extract-plan");
+        let buffer = build_archive(&[
+            ("lua/init.lua", b"init"),
+            ("materials/a.vmt", b"vmt"),
+            ("lua/init.lua", b"again"),
+        ]);
+        fs::create_dir_all(dir.join("materials")).unwrap();
+        fs::write(dir.join("materials/a.vmt"), b"old").unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let plan = archive.extract_plan(&dir, &ExtractOptions::new()).unwrap();
+        assert_eq!(plan.files().len(), 3);
+        assert_eq!(plan.total_size(), 12);
+        assert_eq!(plan.files()[1].path(), dir.join("materials/a.vmt"));
+        let collisions: Vec<(&str, bool, bool)> = plan
+            .collisions()
+            .map(|f| (f.filename(), f.exists(), f.duplicated()))
+            .collect();
+        assert_eq!(
+            collisions,
+            [
+                ("materials/a.vmt", true, false),
+                ("lua/init.lua", false, true)
+            ]
+        );
+        // nothing was written
+        assert!(!dir.join("lua").exists());
+        assert_eq!(fs::read(dir.join("materials/a.vmt")).unwrap(), b"old");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sanitize_path() {
+        for unsafe_name in ["..", "a/../b", "/a", "\\a", "C:/a", "a\\..\\b", "", "./"] {
+            assert!(
+                gma::sanitize_path(unsafe_name, PathPolicy::Reject).is_err(),
+                "{}",
+                unsafe_name
+            );
+        }
+        assert_eq!(
+            gma::sanitize_path("lua/./a.lua", PathPolicy::Reject).unwrap(),
+            PathBuf::from("lua/a.lua")
+        );
+        assert_eq!(
+            gma::sanitize_path("lua\\b.lua", PathPolicy::Reject).unwrap(),
+            PathBuf::from("lua/b.lua")
+        );
+        assert!(gma::sanitize_path("../..", PathPolicy::Strip).is_err());
+    }
+
+    #[test]
+    fn extract_dedup_store() {
+        let root = temp_dir("extract-dedup");
+        let store = root.join("store");
+        let first = build_archive(&[("materials/shared.vtf", b"shared"), ("a.txt", b"a")]);
+        let second = build_archive(&[("materials/shared.vtf", b"shared"), ("b.txt", b"b")]);
+
+        let mut options = ExtractOptions::new();
+        options.dedup_store(&store, LinkKind::Hardlink);
+
+        let report = gma::load_from_memory(&first)
+            .unwrap()
+            .extract_to(root.join("first"), &options)
+            .unwrap();
+        assert!(report.files().iter().all(|f| !f.deduplicated()));
+
+        let report = gma::load_from_memory(&second)
+            .unwrap()
+            .extract_to(root.join("second"), &options)
+            .unwrap();
+        let deduplicated: Vec<bool> = report.files().iter().map(|f| f.deduplicated()).collect();
+        assert_eq!(deduplicated, vec![true, false]);
+
+        assert_eq!(fs::read_dir(&store).unwrap().count(), 3);
+        assert_eq!(
+            fs::read(root.join("second/materials/shared.vtf")).unwrap(),
+            b"shared"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let first = fs::metadata(root.join("first/materials/shared.vtf")).unwrap();
+            let second = fs::metadata(root.join("second/materials/shared.vtf")).unwrap();
+            assert_eq!(first.ino(), second.ino());
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_dedup_symlink() {
+        let root = temp_dir("extract-symlink");
+        let buffer = build_archive(&[("sound/a.wav", b"wav")]);
+        let mut options = ExtractOptions::new();
+        options.dedup_store(root.join("store"), LinkKind::Symlink);
+        gma::load_from_memory(&buffer)
+            .unwrap()
+            .extract_to(root.join("out"), &options)
+            .unwrap();
+
+        let path = root.join("out/sound/a.wav");
+        assert!(fs::symlink_metadata(&path)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read(&path).unwrap(), b"wav");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn extract_over_dedup_links_keeps_store() {
+        for kind in [LinkKind::Hardlink, LinkKind::Symlink] {
+            let root = temp_dir(&format!("extract-relink-{:?}", kind));
+            let store = root.join("store");
+            let first = build_archive(&[("lua/a.lua", b"first")]);
+            let second = build_archive(&[("lua/a.lua", b"second")]);
+
+            let mut options = ExtractOptions::new();
+            options.dedup_store(&store, kind);
+            gma::load_from_memory(&first)
+                .unwrap()
+                .extract_to(root.join("out"), &options)
+                .unwrap();
+            let blobs: Vec<_> = fs::read_dir(&store)
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .filter(|p| p.is_file())
+                .collect();
+
+            gma::load_from_memory(&second)
+                .unwrap()
+                .extract_to(root.join("out"), &ExtractOptions::new())
+                .unwrap();
+
+            assert_eq!(fs::read(root.join("out/lua/a.lua")).unwrap(), b"second");
+            assert!(blobs.iter().any(|blob| fs::read(blob).unwrap() == b"first"));
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[test]
+    fn extract_preserve_timestamps() {
+        let dir = temp_dir("extract-timestamps");
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("extract")
+            .timestamp(1_500_000_000)
+            .file_from_bytes("lua/autorun/init.lua", b"print('init')".to_vec())
+            .file_from_bytes("materials/a.vmt", b"vmt".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        fs::create_dir_all(dir.join("materials")).unwrap();
+        let existing = fs::metadata(dir.join("materials"))
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        let mut options = ExtractOptions::new();
+        options.preserve_timestamps(true);
+        archive.extract_to(&dir, &options).unwrap();
+        let modified = |path: &str| fs::metadata(dir.join(path)).unwrap().modified().unwrap();
+        let expected = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        assert_eq!(modified("lua/autorun/init.lua"), expected);
+        assert_eq!(modified("materials/a.vmt"), expected);
+        assert_eq!(modified("lua/autorun"), expected);
+        assert_eq!(modified("lua"), expected);
+        // directories that already existed are left alone
+        assert_ne!(modified("materials"), expected);
+        assert!(modified("materials") >= existing);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_prefetch() {
+        let dir = temp_dir("extract-prefetch");
+        let names: Vec<String> = (0..50).map(|i| format!("lua/files/f{}.lua", i)).collect();
+        let big = vec![3u8; 5 * 1024 * 1024];
+        let mut files: Vec<(&str, &[u8])> =
+            names.iter().map(|n| (n.as_str(), n.as_bytes())).collect();
+        files.insert(10, ("maps/big.bsp", &big));
+        let buffer = build_archive(&files);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let mut options = ExtractOptions::new();
+        options.prefetch(true);
+        let report = archive.extract_to(&dir, &options).unwrap();
+        assert_eq!(report.files().len(), 51);
+        assert_eq!(report.files()[10].filename(), "maps/big.bsp");
+        for name in &names {
+            assert_eq!(fs::read(dir.join(name)).unwrap(), name.as_bytes());
+        }
+        assert_eq!(fs::read(dir.join("maps/big.bsp")).unwrap(), big);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // write errors from the background thread are returned, with the entry that failed
+        fs::create_dir_all(dir.join("lua/files/f20.lua")).unwrap();
+        match archive.extract_to(&dir, &options) {
+            Err(Error::InEntry { name, source }) => {
+                assert_eq!(name, "lua/files/f20.lua");
+                assert!(matches!(*source, Error::IOError(_)));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_prefetch_mapped_last_kept() {
+        let dir = temp_dir("extract-prefetch-mapped");
+        let names: Vec<String> = (0..8).map(|i| format!("lua/f{}.lua", i)).collect();
+        let big = vec![3u8; 5 * 1024 * 1024];
+        let mut files: Vec<(&str, &[u8])> =
+            names.iter().map(|n| (n.as_str(), n.as_bytes())).collect();
+        files.push(("maps/big.bsp", &big));
+        let buffer = build_archive(&files);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        // the large entry is not prefetched but still has to be written after the queued ones
+        let mut options = ExtractOptions::new();
+        options.prefetch(true);
+        archive
+            .extract_mapped(&dir, &options, |_| Some("out".to_owned()))
+            .unwrap();
+        assert_eq!(fs::read(dir.join("out")).unwrap(), big);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn file_entry_paths() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("paths")
+            .file_from_bytes("lua/autorun/cl_myscript.lua", b"".to_vec())
+            .file_from_bytes("addon.json", b"".to_vec())
+            .file_from_bytes("materials/.hidden", b"".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entries: Vec<_> = archive.entries().collect();
+
+        assert_eq!(entries[0].file_name(), "cl_myscript.lua");
+        assert_eq!(entries[0].extension(), Some("lua"));
+        assert_eq!(entries[0].parent(), Some("lua/autorun"));
+        assert_eq!(
+            entries[0].components().collect::<Vec<_>>(),
+            vec!["lua", "autorun", "cl_myscript.lua"]
+        );
+
+        assert_eq!(entries[1].file_name(), "addon.json");
+        assert_eq!(entries[1].extension(), Some("json"));
+        assert_eq!(entries[1].parent(), None);
+
+        assert_eq!(entries[2].file_name(), ".hidden");
+        assert_eq!(entries[2].extension(), None);
+        assert_eq!(entries[2].parent(), Some("materials"));
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test {
+    use gma::{transform, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn build(numbers: &[u32]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("numbers");
+        for (i, number) in numbers.iter().enumerate() {
+            builder
+                .file_from_bytes(format!("lua/{}.lua", i), vec![b'a'; i])
+                .file_number(*number);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn numbers(buffer: &[u8]) -> Vec<u32> {
+        let archive = gma::load_from_memory(buffer).unwrap();
+        archive.entries().map(|e| e.file_number()).collect()
+    }
+
+    #[test]
+    fn sequential_by_default() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("numbers")
+            .file_from_bytes("a.txt", b"a".to_vec())
+            .file_from_bytes("b.txt", b"b".to_vec())
+            // 0 would end the file table
+            .file_number(0);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        assert_eq!(numbers(&buffer), vec![1, 2]);
+    }
+
+    #[test]
+    fn nonstandard_numbers() {
+        let buffer = build(&[5, 5, 2]);
+        assert_eq!(numbers(&buffer), vec![5, 5, 2]);
+        let compact = LoadOptions::new()
+            .compact(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let compact: Vec<u32> = compact.entry_views().map(|e| e.file_number()).collect();
+        assert_eq!(compact, vec![5, 5, 2]);
+    }
+
+    #[test]
+    fn strict_file_numbers() {
+        LoadOptions::new()
+            .strict_file_numbers(true)
+            .load_from_memory(&build(&[1, 3, 10]))
+            .unwrap();
+        let buffer = build(&[5, 5, 2]);
+        let result = LoadOptions::new()
+            .strict_file_numbers(true)
+            .load_from_memory(&buffer);
+        assert!(matches!(
+            result,
+            Err(gma::Error::NonMonotonicFileNumber {
+                index: 1,
+                previous: 5,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn preserved_by_rename() {
+        let buffer = build(&[9, 4, 7]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let builder =
+            transform::rename_entries(&archive, |name: &str| Some(name.to_uppercase())).unwrap();
+        let mut renamed = Vec::new();
+        builder.write_to(Cursor::new(&mut renamed)).unwrap();
+        assert_eq!(numbers(&renamed), vec![9, 4, 7]);
+    }
+}
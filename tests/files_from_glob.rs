@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    const ADDON_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/addon");
+
+    fn entry_names(builder: GMABuilder) -> Vec<String> {
+        let mut buffer: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        archive.entries().map(|e| e.filename().to_owned()).collect()
+    }
+
+    #[test]
+    fn files_from_glob() {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("glob")
+            .files_from_glob(ADDON_DIR, &["lua/*.lua"])
+            .unwrap();
+        assert_eq!(entry_names(builder), vec!["lua/hello.lua"]);
+    }
+
+    #[test]
+    fn files_from_glob_multiple_patterns() {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("glob")
+            .files_from_glob(ADDON_DIR, &["*.JSON", "*/hello.*"])
+            .unwrap();
+        assert_eq!(entry_names(builder), vec!["addon.json", "lua/hello.lua"]);
+    }
+
+    #[test]
+    fn files_from_glob_missing_root() {
+        let mut builder = GMABuilder::new();
+        assert!(builder
+            .files_from_glob(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/tests/missing"),
+                &["*"]
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// needs /dev/fuse and fusermount, like the gmad tests need gmad
+#[cfg(all(test, feature = "fuse", target_os = "linux"))]
+mod test {
+    use gma::GMABuilder;
+    use std::{
+        fs,
+        io::Cursor,
+        path::Path,
+        process::Command,
+        thread,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    fn build_archive(timestamp: u64, files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("fuse").timestamp(timestamp);
+        for (name, contents) in files {
+            builder.file_from_bytes(*name, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn wait_until_mounted(mountpoint: &Path) {
+        for _ in 0..100 {
+            if mountpoint.join("lua").exists() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("{} was not mounted", mountpoint.display());
+    }
+
+    // fusermount for regular users, umount when running as root without fuse's utilities
+    fn unmount(mountpoint: &Path) {
+        let unmounted = [
+            ("fusermount", "-u"),
+            ("fusermount3", "-u"),
+            ("umount", "-l"),
+        ]
+        .iter()
+        .any(|(program, flag)| {
+            Command::new(program)
+                .arg(flag)
+                .arg(mountpoint)
+                .status()
+                .is_ok_and(|status| status.success())
+        });
+        assert!(unmounted, "failed to unmount {}", mountpoint.display());
+    }
+
+    // mounts the archive in `buffer` while `check` runs, the archive must have a `lua` directory
+    fn with_mounted(name: &str, buffer: &[u8], check: impl FnOnce(&Path) + std::panic::UnwindSafe) {
+        let mountpoint =
+            std::env::temp_dir().join(format!("gma-fuse-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&mountpoint).unwrap();
+
+        thread::scope(|scope| {
+            let mounted = scope.spawn(|| {
+                let archive = gma::load_from_memory(buffer).unwrap();
+                gma::fuse::mount(&archive, &mountpoint)
+            });
+            wait_until_mounted(&mountpoint);
+
+            let result = std::panic::catch_unwind(|| check(&mountpoint));
+
+            unmount(&mountpoint);
+            mounted.join().unwrap().unwrap();
+            result.unwrap();
+        });
+        fs::remove_dir_all(&mountpoint).unwrap();
+    }
+
+    fn list(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn mount_read_only() {
+        let buffer = build_archive(
+            1_600_000_000,
+            &[
+                ("lua/autorun/a.lua", b"print('a')"),
+                ("materials/b.vmt", b"\"VertexLitGeneric\""),
+            ],
+        );
+        with_mounted("read-only", &buffer, |mountpoint| {
+            assert_eq!(list(mountpoint), ["lua", "materials"]);
+            assert_eq!(
+                fs::read(mountpoint.join("lua/autorun/a.lua")).unwrap(),
+                b"print('a')"
+            );
+            let metadata = fs::metadata(mountpoint.join("materials/b.vmt")).unwrap();
+            assert_eq!(metadata.len(), 18);
+            assert!(metadata.permissions().readonly());
+            assert_eq!(
+                metadata.modified().unwrap(),
+                UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+            );
+            assert!(fs::write(mountpoint.join("lua/c.lua"), b"").is_err());
+        });
+    }
+
+    #[test]
+    fn unrepresentable_timestamp() {
+        let buffer = build_archive(u64::MAX, &[("lua/a.lua", b"print('a')")]);
+        with_mounted("timestamp", &buffer, |mountpoint| {
+            let metadata = fs::metadata(mountpoint.join("lua/a.lua")).unwrap();
+            assert_eq!(metadata.modified().unwrap(), UNIX_EPOCH);
+        });
+    }
+
+    #[test]
+    fn file_and_directory_conflicts() {
+        // the file comes first, the entry that needs it as a directory is left out
+        let buffer = build_archive(0, &[("lua/a", b"file"), ("lua/a/b.lua", b"print('b')")]);
+        with_mounted("file-first", &buffer, |mountpoint| {
+            assert_eq!(list(mountpoint), ["lua"]);
+            assert_eq!(list(&mountpoint.join("lua")), ["a"]);
+            assert_eq!(fs::read(mountpoint.join("lua/a")).unwrap(), b"file");
+        });
+
+        // the directory comes first, the file with its name is left out
+        let buffer = build_archive(0, &[("lua/a/b.lua", b"print('b')"), ("lua/a", b"file")]);
+        with_mounted("directory-first", &buffer, |mountpoint| {
+            assert_eq!(list(mountpoint), ["lua"]);
+            assert_eq!(list(&mountpoint.join("lua")), ["a"]);
+            assert_eq!(list(&mountpoint.join("lua/a")), ["b.lua"]);
+            assert_eq!(
+                fs::read(mountpoint.join("lua/a/b.lua")).unwrap(),
+                b"print('b')"
+            );
+        });
+    }
+}
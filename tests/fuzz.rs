@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, LoadOptions};
+    use std::{
+        io::{sink, Cursor},
+        sync::Arc,
+    };
+
+    const GENUINE: &[u8] = include_bytes!("genuine.gma");
+    const ADDON: &[u8] = include_bytes!("addon.gma");
+
+    // small deterministic prng so failures are reproducible
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    fn compressed() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("fuzz")
+            .compression(true)
+            .file_from_bytes("lua/autorun/a.lua", b"print(\"a\")".to_vec())
+            .file_from_bytes("materials/b.vmt", vec![b'b'; 64]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    // every public api reachable from a loaded archive must return instead of panicking
+    fn exercise(data: &[u8]) {
+        // lenient archives skip the bounds checks and keep whatever the file table claims
+        for (trace, lenient) in [(false, false), (true, false), (false, true)].iter() {
+            let archive = match LoadOptions::new()
+                .trace(*trace)
+                .lenient(*lenient)
+                .load_compressed(Cursor::new(data))
+            {
+                Ok(archive) => archive,
+                Err(_) => continue,
+            };
+            for entry in archive.entries() {
+                let _ = archive
+                    .try_read_entry(entry, |_, reader| Ok(std::io::copy(reader, &mut sink())?));
+                let _ = archive.read_entry_verified(entry, |_, reader| {
+                    std::io::copy(reader, &mut sink()).ok()
+                });
+                let _ = archive.sniff(entry);
+                let _ = archive.read_entry_seekable(entry, |_, reader| {
+                    std::io::copy(reader, &mut sink()).ok()
+                });
+            }
+            let _ = archive.read_entries(archive.entries());
+            let _ = archive.stats();
+            let _ = archive.tree();
+            let _ = archive.metadata();
+            let _ = archive.cache_key();
+            let _ = archive.non_ascii_entries();
+            let _ = gma::analysis::lua_stats(&archive);
+            let _ = archive.stream_subset_as_zip(archive.entries(), sink());
+        }
+    }
+
+    fn fuzz(original: &[u8], seed: u64) {
+        let mut rng = XorShift(seed);
+        for len in 0..original.len().min(256) {
+            exercise(&original[..len]);
+        }
+        for _ in 0..1000 {
+            let mut data = original.to_vec();
+            for _ in 0..1 + rng.below(8) {
+                let index = rng.below(data.len());
+                data[index] = rng.next() as u8;
+            }
+            let len = data.len() - rng.below(data.len() / 4 + 1);
+            exercise(&data[..len]);
+        }
+    }
+
+    #[test]
+    fn mutated_genuine() {
+        fuzz(GENUINE, 0x9e37_79b9_7f4a_7c15);
+    }
+
+    #[test]
+    fn mutated_addon() {
+        fuzz(ADDON, 0x2545_f491_4f6c_dd1d);
+    }
+
+    #[test]
+    fn mutated_compressed() {
+        fuzz(&compressed(), 0xdead_beef_cafe_f00d);
+    }
+
+    #[test]
+    fn missing_name() {
+        let builder = GMABuilder::new();
+        let result = builder.write_to(Cursor::new(Vec::new()));
+        assert!(matches!(result, Err(gma::Error::MissingName)));
+    }
+
+    #[test]
+    fn lenient_overflowing_offsets() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("fuzz")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        // the size of the first entry, every later offset saturates
+        let at = buffer
+            .windows(10)
+            .position(|w| w == b"lua/a.lua\0")
+            .unwrap()
+            + 10;
+        buffer[at..at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let mut options = LoadOptions::new();
+        options.lenient(true);
+        let archive = options.load_from_memory(&buffer).unwrap();
+        let second = &archive["lua/b.lua"];
+        let truncated = |result: gma::Result<()>| match result {
+            Err(gma::Error::Truncated { .. }) => {}
+            Err(gma::Error::InEntry { source, .. })
+                if matches!(*source, gma::Error::Truncated { .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        };
+        truncated(archive.read_entry(second, |_, _| ()));
+        truncated(archive.read_entry_seekable(second, |_, _| ()));
+        truncated(archive.read_entries(Some(second)).map(drop));
+
+        let archive = options.load_concurrent(Arc::new(buffer)).unwrap();
+        truncated(archive.read_entry_concurrent(&archive["lua/b.lua"], |_, _| ()));
+    }
+}
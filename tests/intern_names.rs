@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, Interner, LoadOptions};
+    use std::io::Cursor;
+
+    fn build(names: &[&str]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("interned");
+        for name in names {
+            builder.file_from_bytes(*name, b"contents".to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn names_shared_across_archives() {
+        let first = build(&["lua/autorun/client/a.lua", "lua/autorun/shared.lua"]);
+        let second = build(&["lua/autorun/shared.lua", "materials/b.vmt"]);
+        let interner = Interner::new();
+        let mut options = LoadOptions::new();
+        options.intern_names(&interner);
+
+        let first = options.load_from_memory(&first).unwrap();
+        let second = options.load_from_memory(&second).unwrap();
+        assert_eq!(interner.len(), 3);
+        assert_eq!(
+            first["lua/autorun/shared.lua"].filename(),
+            "lua/autorun/shared.lua"
+        );
+        assert_eq!(
+            second.entries().nth(1).unwrap().filename(),
+            "materials/b.vmt"
+        );
+
+        drop(first);
+        assert_eq!(interner.purge(), 1);
+        drop(second);
+        assert_eq!(interner.purge(), 2);
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn interning_is_opt_in() {
+        let buffer = build(&["lua/autorun/shared.lua"]);
+        let interner = Interner::new();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entries().count(), 1);
+        assert!(interner.is_empty());
+    }
+}
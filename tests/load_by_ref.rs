@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::{Cursor, Seek, SeekFrom, Write};
+
+    #[test]
+    fn load_by_ref() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("by ref")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive_len = buffer.len() as u64;
+
+        let mut reader = Cursor::new(buffer);
+        {
+            let archive = gma::load(&mut reader).unwrap();
+            let entry = archive.get("lua/a.lua").unwrap();
+            let contents = archive
+                .read_entry(entry, |_, reader| {
+                    let mut contents = Vec::new();
+                    reader.read_to_end(&mut contents).map(|_| contents)
+                })
+                .unwrap()
+                .unwrap();
+            assert_eq!(contents, b"print('a')");
+        }
+
+        // the reader is still ours once the archive is dropped
+        reader.seek(SeekFrom::End(0)).unwrap();
+        reader.write_all(b"trailing").unwrap();
+        assert_eq!(reader.get_ref().len() as u64, archive_len + 8);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let archive = gma::load(&mut reader).unwrap();
+        assert_eq!(archive.name(), "by ref");
+    }
+}
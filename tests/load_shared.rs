@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder};
+    use std::{io::Cursor, sync::Arc};
+
+    fn build(compression: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("shared").compression_format(compression);
+        for i in 0..8 {
+            builder.file_from_bytes(format!("lua/{}.lua", i), vec![i as u8; 100 * (i + 1)]);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn concurrent_reads() {
+        let archive = Arc::new(gma::load_shared(Arc::from(build(Compression::None))).unwrap());
+        assert_send_sync(&archive);
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let archive = archive.clone();
+                std::thread::spawn(move || {
+                    for (i, entry) in archive.entries().enumerate() {
+                        let bytes = archive.entry_bytes(entry).unwrap();
+                        assert_eq!(bytes, &vec![i as u8; 100 * (i + 1)][..]);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // the reader is still usable
+        let entry = archive.get("lua/3.lua").unwrap();
+        let contents = archive
+            .read_entry_verified(entry, |_, reader| {
+                let mut contents = Vec::new();
+                reader.read_to_end(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(contents, archive.entry_bytes(entry).unwrap());
+    }
+
+    #[test]
+    fn compressed() {
+        let archive = gma::load_shared(Arc::from(build(Compression::Lzma))).unwrap();
+        assert_eq!(archive.compression(), Compression::Lzma);
+        let entry = archive.get("lua/1.lua").unwrap();
+        assert_eq!(archive.entry_bytes(entry).unwrap(), &[1u8; 200][..]);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("lookup")
+            .file_from_bytes("lua/init.lua", b"init".to_vec())
+            .file_from_bytes("materials/a.vmt", b"vmt".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn into_iterator() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let mut names = Vec::new();
+        for entry in &archive {
+            names.push(entry.filename());
+        }
+        assert_eq!(names, vec!["lua/init.lua", "materials/a.vmt"]);
+    }
+
+    #[test]
+    fn get_and_index() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.get("materials/a.vmt").unwrap().size(), 3);
+        assert!(archive.get("LUA/init.lua").is_none());
+        assert_eq!(archive["lua/init.lua"].size(), 4);
+    }
+
+    #[test]
+    fn ignore_case() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.entry_ignore_case("LUA/Init.lua").unwrap();
+        assert_eq!(entry.filename(), "lua/init.lua");
+        assert!(archive.entry_ignore_case("lua/init.lu").is_none());
+
+        let archive = gma::LoadOptions::new()
+            .case_insensitive_lookups(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(archive.get("MATERIALS/A.vmt").unwrap().size(), 3);
+        assert_eq!(archive["Lua/Init.LUA"].size(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_missing() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let _ = &archive["lua/missing.lua"];
+    }
+}
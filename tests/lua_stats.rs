@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod test {
+    use gma::{analysis::Realm, GMABuilder};
+    use std::io::Cursor;
+
+    const SERVER_LUA: &str = "-- server side\n\
+        --[[ block\n\
+        comment ]]\n\
+        \n\
+        local function greet(ply)\n\
+            if IsValid(ply) then\n\
+                for i = 1, 3 do print(i) end -- inline\n\
+            end\n\
+        end\n";
+
+    #[test]
+    fn lua_stats() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("lua stats")
+            .file_from_bytes("lua/autorun/server/sv.lua", SERVER_LUA.as_bytes().to_vec())
+            .file_from_bytes("lua/autorun/client/cl.lua", b"print('hi')\n".to_vec())
+            .file_from_bytes("lua/autorun/sh.lua", b"function A() end\n".to_vec())
+            .file_from_bytes("materials/a.vmt", b"\"VertexLitGeneric\"".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let stats = gma::analysis::lua_stats(&archive).unwrap();
+        assert_eq!(stats.files().len(), 3);
+        assert_eq!(stats.file_count(Realm::Server), 1);
+        assert_eq!(stats.file_count(Realm::Client), 1);
+        assert_eq!(stats.file_count(Realm::Shared), 1);
+
+        let server = &stats.files()[0];
+        assert_eq!(server.lines(), 9);
+        assert_eq!(server.comment_lines(), 3);
+        assert_eq!(server.blank_lines(), 1);
+        assert_eq!(server.code_lines(), 5);
+        assert_eq!(server.functions(), 1);
+        assert_eq!(server.branches(), 2);
+
+        assert_eq!(stats.code_lines(), 7);
+        assert_eq!(stats.functions(), 2);
+        assert_eq!(
+            stats.largest_files()[0].filename(),
+            "lua/autorun/server/sv.lua"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build(files: usize, compressed: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("memory").compression(compressed);
+        for i in 0..files {
+            builder.file_from_bytes(format!("lua/file_{}.lua", i), vec![b'a'; 1000]);
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn memory_usage_grows_with_entries() {
+        let small = build(1, false);
+        let large = build(100, false);
+        let small = gma::load_from_memory(&small).unwrap();
+        let large = gma::load_from_memory(&large).unwrap();
+        assert!(large.approx_memory_usage() > small.approx_memory_usage());
+        // uncompressed contents are not held in memory
+        assert!(large.approx_memory_usage() < 100 * 1000);
+
+        let index = large.build_name_index();
+        assert!(index.approx_memory_usage() > 100 * "lua/file_0.lua".len());
+    }
+
+    #[test]
+    fn memory_usage_counts_decompressed_buffer() {
+        let compressed = build(100, true);
+        let archive = gma::load_from_memory(&compressed).unwrap();
+        assert!(archive.compressed());
+        assert!(archive.approx_memory_usage() > 100 * 1000);
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test {
+    use gma::{AddonTag, AddonType, GMABuilder};
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn build(timestamp: u64, compression: bool, contents: &[u8]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("cache key")
+            .created_at(UNIX_EPOCH + Duration::from_secs(timestamp))
+            .compression(compression)
+            .file_from_bytes("lua/init.lua", contents.to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn metadata() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        let metadata = archive.metadata();
+        assert_eq!(metadata.name, "My Test Addon");
+        assert_eq!(metadata.addon_type, Some(AddonType::Gamemode));
+        assert_eq!(metadata.addon_tags, vec![AddonTag::Fun, AddonTag::Cartoon]);
+        assert_eq!(metadata.timestamp, 1595515015);
+        assert_eq!(metadata.entries.len(), 1);
+        assert_eq!(metadata.entries[0].filename, "lua/hello.lua");
+        assert_eq!(metadata.entries[0].size, 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn metadata_serialize() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        let json = serde_json::to_value(archive.metadata()).unwrap();
+        assert_eq!(json["name"], "My Test Addon");
+        assert_eq!(json["author_steamid"], 0);
+        assert_eq!(json["addon_type"], "Gamemode");
+        assert_eq!(json["addon_tags"][1], "Cartoon");
+        assert_eq!(json["entries"][0]["filename"], "lua/hello.lua");
+        assert_eq!(json["entries"][0]["size"], 3);
+    }
+
+    #[test]
+    fn cache_key_ignores_timestamp_and_compression() {
+        let first = build(1000, false, b"print(1)");
+        let second = build(2000, true, b"print(1)");
+        let changed = build(1000, false, b"print(2)");
+        let first = gma::load_from_memory(&first).unwrap();
+        let second = gma::load_from_memory(&second).unwrap();
+        let changed = gma::load_from_memory(&changed).unwrap();
+        assert_eq!(first.cache_key(), second.cache_key());
+        assert_ne!(first.cache_key(), changed.cache_key());
+        assert_eq!(first.cache_key().len(), 16);
+    }
+
+    #[test]
+    fn stable_hash_is_stable() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        // pinned, changing the value breaks existing caches
+        assert_eq!(archive.metadata().stable_hash(), 1290955920376980305);
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod test {
+    use gma::{AddonTag, AddonType, GMABuilder, MetadataFormat};
+    use std::io::Cursor;
+
+    fn build(format: MetadataFormat, description: &str) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("My Test Addon")
+            .description(description)
+            .author("Author Name")
+            .timestamp(1595515015)
+            .addon_type(AddonType::Gamemode)
+            .addon_tag(AddonTag::Fun)
+            .addon_tag(AddonTag::Cartoon)
+            .metadata_format(format)
+            .file_from_bytes("lua/hello.lua", b"hi\n".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn gmad_compatible_matches_genuine() {
+        let genuine = &include_bytes!("genuine.gma")[..];
+        let buffer = build(MetadataFormat::GmadCompatible, "My Description");
+        // gmad also appends the crc of the whole archive, which the builder doesn't write
+        assert_eq!(&buffer[..], &genuine[..genuine.len() - 4]);
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let description = "line one\nwith \"quotes\", a \\ and a\ttab";
+        for format in [
+            MetadataFormat::Compact,
+            MetadataFormat::Canonical,
+            MetadataFormat::GmadCompatible,
+        ] {
+            let buffer = build(format, description);
+            let archive = gma::load_from_memory(&buffer).unwrap();
+            assert_eq!(archive.description(), description, "{:?}", format);
+            assert_eq!(archive.addon_type(), Some(AddonType::Gamemode));
+            assert!(archive.contains_tag(AddonTag::Cartoon));
+        }
+    }
+
+    #[test]
+    fn canonical_layout() {
+        let buffer = build(MetadataFormat::Canonical, "My Description");
+        let expected = b"{\n\t\"title\": \"My Test Addon\",\n\t\"description\": \"My Description\",\n\t\"type\": \"gamemode\",\n\t\"tags\": [\n\t\t\"fun\",\n\t\t\"cartoon\"\n\t]\n}\0";
+        assert!(buffer.windows(expected.len()).any(|w| w == expected));
+    }
+}
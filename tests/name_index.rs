@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn name_index() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("index")
+            .file_from_bytes("materials/Ünïcode/A.vmt", b"1".to_vec())
+            .file_from_bytes("lua/autorun/init.lua", b"2".to_vec())
+            .file_from_bytes("materials/b.vmt", b"3".to_vec())
+            .file_from_bytes("LUA/autorun/init.lua", b"4".to_vec())
+            .file_from_bytes("materialsX.txt", b"5".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let index = archive.build_name_index();
+        assert_eq!(index.len(), 5);
+
+        assert_eq!(index.get("Lua/AutoRun/INIT.lua").unwrap().index(), 1);
+        let all: Vec<usize> = index
+            .get_all("lua/autorun/init.lua")
+            .map(|e| e.index())
+            .collect();
+        assert_eq!(all, vec![1, 3]);
+        assert!(index.contains("MATERIALS/ÜNÏCODE/a.vmt"));
+        assert!(!index.contains("lua/autorun"));
+
+        let materials: Vec<&str> = index
+            .with_prefix("Materials/")
+            .map(|e| e.filename())
+            .collect();
+        assert_eq!(
+            materials,
+            vec!["materials/b.vmt", "materials/Ünïcode/A.vmt"]
+        );
+        assert_eq!(index.with_prefix("sound/").count(), 0);
+    }
+}
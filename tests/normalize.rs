@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod test {
+    use gma::{AddonTag, AddonType, GMABuilder, NormalizeOptions};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("normalize")
+            .description("desc")
+            .author("author")
+            .timestamp(1234)
+            .addon_type(AddonType::Weapon)
+            .addon_tag(AddonTag::Fun)
+            .addon_tag(AddonTag::Build)
+            .file_from_bytes("materials/B.vmt", b"b".to_vec())
+            .file_from_bytes("LUA/autorun/a.lua", b"a".to_vec())
+            .file_from_bytes("readme.txt", b"junk".to_vec())
+            .compression(true);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn normalize() {
+        let input = build_archive();
+        let mut output = Vec::new();
+        let report = gma::normalize(
+            Cursor::new(&input),
+            Cursor::new(&mut output),
+            &NormalizeOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(report.stripped(), &["readme.txt".to_owned()]);
+
+        let archive = gma::load_from_memory(&output).unwrap();
+        assert!(!archive.compressed());
+        assert_eq!(archive.name(), "normalize");
+        assert_eq!(archive.description(), "desc");
+        assert_eq!(archive.author(), "author");
+        assert_eq!(archive.timestamp(), 1234);
+        assert_eq!(archive.addon_type(), Some(AddonType::Weapon));
+        assert_eq!(archive.addon_tags(), &[AddonTag::Fun, AddonTag::Build]);
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, vec!["lua/autorun/a.lua", "materials/b.vmt"]);
+        for entry in archive.entries() {
+            archive.read_entry_verified(entry, |_, _| ()).unwrap();
+        }
+    }
+
+    #[test]
+    fn normalize_disabled_steps() {
+        let input = build_archive();
+        let mut output = Vec::new();
+        let report = gma::normalize(
+            Cursor::new(&input),
+            Cursor::new(&mut output),
+            NormalizeOptions::new()
+                .lowercase_paths(false)
+                .sort_entries(false)
+                .strip_disallowed(false),
+        )
+        .unwrap();
+        assert!(report.stripped().is_empty());
+
+        let archive = gma::load_from_memory(&output).unwrap();
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(
+            names,
+            vec!["materials/B.vmt", "LUA/autorun/a.lua", "readme.txt"]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn absolute_offsets() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("offsets")
+            .file_from_bytes("first.txt", b"first".to_vec())
+            .file_from_bytes("second.txt", b"second".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.data_offset(), (buffer.len() - 11) as u64);
+        for entry in archive.entries() {
+            assert_eq!(
+                entry.absolute_offset(),
+                archive.data_offset() + entry.offset()
+            );
+            let start = entry.absolute_offset() as usize;
+            let end = start + entry.size() as usize;
+            let contents = archive
+                .read_entry(entry, |_, reader| {
+                    let mut contents = Vec::new();
+                    reader.read_to_end(&mut contents).unwrap();
+                    contents
+                })
+                .unwrap();
+            assert_eq!(&buffer[start..end], contents.as_slice());
+        }
+    }
+
+    #[test]
+    fn header_and_entry_table_ranges() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("ranges")
+            .file_from_bytes("first.txt", b"first".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let header = archive.header_range();
+        let table = archive.entry_table_range();
+        assert_eq!(header.start, 0);
+        assert_eq!(header.end, table.start);
+        assert_eq!(table.end, archive.data_offset());
+        // number, "first.txt\0", size, crc and the terminator
+        assert_eq!(table.end - table.start, 4 + 10 + 8 + 4 + 4);
+
+        let raw_header = archive.raw_header().unwrap();
+        assert_eq!(
+            raw_header,
+            &buffer[header.start as usize..header.end as usize]
+        );
+        assert!(raw_header.starts_with(b"GMAD"));
+        let raw_table = archive.raw_entry_table().unwrap();
+        assert_eq!(raw_table, &buffer[table.start as usize..table.end as usize]);
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, Error, GMABuilder, OpenOptions};
+    use std::io::Cursor;
+
+    // offset of the name in archives without required content
+    const NAME_OFFSET: usize = 22;
+
+    fn build_archive(format: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("options")
+            .compression_format(format)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn read_all(archive: &gma::GMAFile<Cursor<&[u8]>>, name: &str) -> gma::Result<Vec<u8>> {
+        let entry = archive.get(name).unwrap();
+        archive.try_read_entry(entry, |_, reader| {
+            let mut contents = Vec::new();
+            reader.read_to_end(&mut contents)?;
+            Ok(contents)
+        })
+    }
+
+    #[test]
+    fn defaults_match_load() {
+        let buffer = build_archive(Compression::None);
+        let archive = OpenOptions::new().load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entry_count(), 2);
+        assert_eq!(read_all(&archive, "lua/a.lua").unwrap(), b"print('a')");
+    }
+
+    #[test]
+    fn verify_crc() {
+        let mut buffer = build_archive(Compression::None);
+        let at = buffer.windows(10).position(|w| w == b"print('b')").unwrap();
+        buffer[at] = b'P';
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(read_all(&archive, "lua/b.lua").unwrap(), b"Print('b')");
+
+        let archive = OpenOptions::new()
+            .verify_crc(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(read_all(&archive, "lua/a.lua").unwrap(), b"print('a')");
+        assert!(matches!(
+            read_all(&archive, "lua/b.lua"),
+            Err(Error::CrcMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn max_entries() {
+        let buffer = build_archive(Compression::None);
+        OpenOptions::new()
+            .max_entries(2)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert!(matches!(
+            OpenOptions::new().max_entries(1).load_from_memory(&buffer),
+            Err(Error::TooManyEntries(1))
+        ));
+    }
+
+    #[test]
+    fn max_decompressed_size() {
+        let plain = build_archive(Compression::None);
+        let buffer = build_archive(Compression::Lzma);
+        OpenOptions::new()
+            .max_decompressed_size(plain.len() as u64)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert!(matches!(
+            OpenOptions::new()
+                .max_decompressed_size(16)
+                .load_from_memory(&buffer),
+            Err(Error::DecompressedTooLarge(16))
+        ));
+        assert!(matches!(
+            OpenOptions::new()
+                .max_decompressed_size(16)
+                .load_compressed(&buffer[..]),
+            Err(Error::DecompressedTooLarge(16))
+        ));
+    }
+
+    #[test]
+    fn lenient_version_and_strings() {
+        let mut buffer = build_archive(Compression::None);
+        buffer[4] = 9;
+        buffer[NAME_OFFSET] = 0xff;
+        assert!(matches!(
+            gma::load_from_memory(&buffer),
+            Err(Error::InvalidVersion(9))
+        ));
+
+        let archive = OpenOptions::new()
+            .lenient(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(archive.version().as_u8(), 9);
+        assert_eq!(archive.name(), "\u{fffd}ptions");
+        assert_eq!(read_all(&archive, "lua/b.lua").unwrap(), b"print('b')");
+    }
+
+    #[test]
+    fn lenient_truncated() {
+        let buffer = build_archive(Compression::None);
+        // cuts the last entry short
+        let buffer = &buffer[..buffer.len() - 7];
+        assert!(matches!(
+            gma::load_from_memory(buffer),
+            Err(Error::Truncated { .. })
+        ));
+
+        let archive = OpenOptions::new()
+            .lenient(true)
+            .load_from_memory(buffer)
+            .unwrap();
+        assert_eq!(read_all(&archive, "lua/a.lua").unwrap(), b"print('a')");
+        assert_eq!(read_all(&archive, "lua/b.lua").unwrap(), b"pri");
+    }
+
+    #[test]
+    fn lenient_oversized_entry() {
+        let mut buffer = build_archive(Compression::None);
+        // the size right after the last filename in the file table
+        let at = buffer
+            .windows(10)
+            .position(|w| w == b"lua/b.lua\0")
+            .unwrap()
+            + 10;
+        buffer[at..at + 8].copy_from_slice(&(1i64 << 62).to_le_bytes());
+
+        let archive = OpenOptions::new()
+            .lenient(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let entry = archive.get("lua/b.lua").unwrap();
+        assert_eq!(entry.size(), 1 << 62);
+        let text = archive.read_entry_text(entry).unwrap();
+        assert_eq!(text.text(), "print('b')");
+        let contents: Vec<_> = archive
+            .entries_with_contents()
+            .map(|result| result.unwrap().1)
+            .collect();
+        assert_eq!(contents, [&b"print('a')"[..], &b"print('b')"[..]]);
+    }
+}
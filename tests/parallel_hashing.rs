@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder};
+    use std::{
+        io::{Cursor, Read, Seek, SeekFrom, Write},
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    // changes the contents of `path`, keeping its size, when it is first read
+    struct Changes {
+        path: PathBuf,
+        contents: &'static [u8],
+    }
+
+    impl Read for Changes {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let contents = std::mem::take(&mut self.contents);
+            if !contents.is_empty() {
+                std::fs::write(&self.path, b"print('after!')")?;
+                // filesystems with a coarse mtime could otherwise keep the old one
+                std::fs::File::options()
+                    .write(true)
+                    .open(&self.path)?
+                    .set_modified(SystemTime::now() + Duration::from_secs(60))?;
+            }
+            let len = contents.len().min(buf.len());
+            buf[..len].copy_from_slice(&contents[..len]);
+            Ok(len)
+        }
+    }
+
+    // counts the seeks back into what was already written, like the ones patching the file table
+    struct Patches {
+        inner: Cursor<Vec<u8>>,
+        patches: usize,
+    }
+
+    impl Write for Patches {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl Seek for Patches {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            if let SeekFrom::Start(_) = pos {
+                self.patches += 1;
+            }
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn parallel_hashing() {
+        let dir = std::env::temp_dir().join(format!("gma-test-hashing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let names: Vec<String> = (0..20).map(|i| format!("lua/f{}.lua", i)).collect();
+        for i in 0..names.len() {
+            let contents = format!("print({})", "x".repeat(i * 1000));
+            std::fs::write(dir.join(format!("f{}.lua", i)), contents).unwrap();
+        }
+
+        let build = |parallel: bool| {
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut builder = GMABuilder::new();
+            builder
+                .name("hashing")
+                .timestamp(1_000)
+                .parallel_hashing(parallel)
+                .file_from_bytes("lua/memory.lua", b"print('memory')".to_vec());
+            for (i, name) in names.iter().enumerate() {
+                builder
+                    .file_with_name(dir.join(format!("f{}.lua", i)), name.as_str())
+                    .unwrap();
+            }
+            builder.write_to(Cursor::new(&mut buffer)).unwrap();
+            buffer
+        };
+
+        let buffer = build(true);
+        assert_eq!(buffer, build(false));
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entry_count(), 21);
+        for entry in archive.entries() {
+            archive.read_entry_verified(entry, |_, _| ()).unwrap();
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn contents_changed() {
+        let dir =
+            std::env::temp_dir().join(format!("gma-test-hashing-changed-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("b.lua");
+        std::fs::write(&path, b"print('before')").unwrap();
+
+        // b.lua is hashed before a.lua is written, which changes it
+        let mut builder = GMABuilder::new();
+        builder
+            .name("hashing")
+            .parallel_hashing(true)
+            .file_from_reader(
+                "lua/a.lua",
+                Changes {
+                    path: path.clone(),
+                    contents: b"print('a')",
+                },
+            );
+        builder.file_with_name(&path, "lua/b.lua").unwrap();
+        match builder.write_to(Cursor::new(Vec::new())) {
+            Err(Error::SourceChanged(name)) => assert_eq!(name, "lua/b.lua"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_table_written_once() {
+        let dir =
+            std::env::temp_dir().join(format!("gma-test-hashing-table-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..10 {
+            std::fs::write(dir.join(format!("f{}.lua", i)), format!("print({})", i)).unwrap();
+        }
+
+        let build = |parallel: bool| {
+            let mut builder = GMABuilder::new();
+            builder
+                .name("hashing")
+                .timestamp(1_000)
+                .parallel_hashing(parallel);
+            for i in 0..10 {
+                let name = format!("lua/f{}.lua", i);
+                builder
+                    .file_with_name(dir.join(format!("f{}.lua", i)), name.as_str())
+                    .unwrap();
+            }
+            let mut writer = Patches {
+                inner: Cursor::new(Vec::new()),
+                patches: 0,
+            };
+            builder.write_to(&mut writer).unwrap();
+            writer
+        };
+
+        // every file comes from disk, the hashes are known before the file table is written
+        let parallel = build(true);
+        let serial = build(false);
+        assert_eq!(parallel.patches, 0);
+        assert!(serial.patches > 0);
+        let buffer = parallel.inner.into_inner();
+        assert_eq!(buffer, serial.inner.into_inner());
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        for entry in archive.entries() {
+            archive.read_entry_verified(entry, |_, _| ()).unwrap();
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -18,6 +18,7 @@ mod tests {
         assert!(archive.contains_tag(AddonTag::Cartoon));
         //this fields isnt currently used and is hardcoded to this
         assert_eq!(archive.author(), "Author Name");
+        assert_eq!(archive.addon_version(), 1);
 
         let entry = archive
             .entries()
@@ -27,4 +28,21 @@ mod tests {
         assert_eq!(entry.size(), 3);
         //assert_eq!(entry.crc(), 0);
     }
+
+    #[test]
+    fn parse_genuine_max_string_length() {
+        let genuine = include_bytes!("genuine.gma");
+
+        let archive = gma::LoadOptions::new()
+            .max_string_length(4096)
+            .load_from_memory(genuine)
+            .unwrap();
+        assert_eq!(archive.name(), "My Test Addon");
+
+        //the name is the first string longer than this
+        let result = gma::LoadOptions::new()
+            .max_string_length(12)
+            .load_from_memory(genuine);
+        assert!(matches!(result, Err(gma::Error::StringTooLong(12))));
+    }
 }
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod test {
+    #[test]
+    fn parse_trace_disabled() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        assert!(archive.parse_trace().is_none());
+    }
+
+    #[test]
+    fn parse_trace_records_fields() {
+        let archive = gma::LoadOptions::new()
+            .trace(true)
+            .load_from_memory(include_bytes!("genuine.gma"))
+            .unwrap();
+        let trace = archive.parse_trace().unwrap();
+
+        let ident = trace.field("ident").unwrap();
+        assert_eq!(ident.offset(), 0);
+        assert_eq!(ident.raw(), b"GMAD");
+
+        let version = trace.field("version").unwrap();
+        assert_eq!(version.offset(), 4);
+        assert_eq!(version.raw(), &[3]);
+        assert_eq!(version.value(), Some("3"));
+
+        let name = trace.field("name").unwrap();
+        assert_eq!(name.raw(), b"My Test Addon\0");
+        assert_eq!(name.value(), Some("\"My Test Addon\""));
+
+        let filename = trace.field("entries[0].filename").unwrap();
+        assert_eq!(filename.value(), Some("\"lua/hello.lua\""));
+        let terminator = trace.field("entries[1].number").unwrap();
+        assert_eq!(terminator.value(), Some("0"));
+        assert_eq!(trace.last().unwrap().name(), "archive_crc");
+    }
+
+    #[test]
+    fn parse_trace_on_error() {
+        let result = gma::LoadOptions::new()
+            .trace(true)
+            .max_string_length(12)
+            .load_from_memory(include_bytes!("genuine.gma"));
+        let error = result.err().unwrap();
+        let trace = error.trace().unwrap();
+        let last = trace.last().unwrap();
+        assert_eq!(last.name(), "name");
+        assert_eq!(last.value(), None);
+        assert!(matches!(
+            error,
+            gma::Error::Traced { error, .. } if matches!(*error, gma::Error::StringTooLong(12))
+        ));
+    }
+
+    #[test]
+    fn parse_trace_on_table_error() {
+        let error = gma::LoadOptions::new()
+            .trace(true)
+            .max_entries(0)
+            .load_from_memory(include_bytes!("genuine.gma"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.trace().unwrap().last().unwrap().name(),
+            "entries[0].number"
+        );
+        assert!(matches!(
+            error,
+            gma::Error::Traced { error, .. } if matches!(*error, gma::Error::TooManyEntries(0))
+        ));
+    }
+}
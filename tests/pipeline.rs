@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod test {
+    use gma::pipeline::{
+        build_and_validate, CompressionChoice, Lint, PipelineConfig, PipelineOutcome, Rejection,
+    };
+    use gma::{ContentKind, ValidationIssue};
+    use std::path::PathBuf;
+
+    fn addon_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gma-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("addon/lua/autorun")).unwrap();
+        std::fs::write(
+            dir.join("addon/addon.json"),
+            r#"{ "title": "Pipeline", "type": "tool", "tags": ["fun"], "ignore": ["*.psd"] }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("addon/lua/autorun/a.lua"),
+            "print('hello')\n".repeat(200),
+        )
+        .unwrap();
+        std::fs::write(dir.join("addon/art.psd"), b"ignored").unwrap();
+        dir
+    }
+
+    #[test]
+    fn publish() {
+        let dir = addon_dir("pipeline-publish");
+        let output = dir.join("out.gma");
+        let mut config = PipelineConfig::new(dir.join("addon"), &output);
+        config.name("Renamed");
+        let published = match build_and_validate(&config) {
+            PipelineOutcome::Published(published) => published,
+            outcome => panic!("{:?}", outcome),
+        };
+        assert_eq!(published.path(), output);
+        assert_eq!(published.compression(), gma::Compression::None);
+        // addon.json and the ignored art.psd
+        assert_eq!(published.build_report().skipped().len(), 2);
+        let archive = gma::open(&output).unwrap();
+        assert_eq!(archive.name(), "Renamed");
+        assert_eq!(archive.entries().count(), 1);
+        assert_eq!(std::fs::metadata(&output).unwrap().len(), published.size());
+        assert!(published.lints().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn not_whitelisted() {
+        let dir = addon_dir("pipeline-whitelist");
+        std::fs::write(dir.join("addon/notes.txt"), b"notes").unwrap();
+        let output = dir.join("out.gma");
+        let mut config = PipelineConfig::new(dir.join("addon"), &output);
+        match build_and_validate(&config) {
+            PipelineOutcome::Rejected(Rejection::Invalid(report)) => assert_eq!(
+                report.issues(),
+                &[ValidationIssue::NotWhitelisted {
+                    filename: "notes.txt".to_string()
+                }]
+            ),
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(!output.exists());
+
+        config.strip_disallowed(true);
+        match build_and_validate(&config) {
+            PipelineOutcome::Published(published) => {
+                assert_eq!(published.stripped(), &["notes.txt".to_string()])
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(output.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compression_and_budget() {
+        let dir = addon_dir("pipeline-budget");
+        let output = dir.join("out.gma");
+        let mut config = PipelineConfig::new(dir.join("addon"), &output);
+        config
+            .compression(CompressionChoice::Smallest)
+            .size_budget(64);
+        match build_and_validate(&config) {
+            PipelineOutcome::Rejected(Rejection::OverBudget { budget, .. }) => {
+                assert_eq!(budget, 64)
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(!output.exists());
+
+        config.size_budget(1024 * 1024);
+        match build_and_validate(&config) {
+            PipelineOutcome::Published(published) => {
+                assert_ne!(published.compression(), gma::Compression::None)
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+        let archive = gma::open(&output).unwrap();
+        assert!(archive.compressed());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_folder() {
+        let config = PipelineConfig::new("/nonexistent/gma-addon", "/nonexistent/out.gma");
+        let outcome = build_and_validate(&config);
+        assert!(matches!(outcome, PipelineOutcome::Failed(_)));
+        assert!(!outcome.is_published());
+    }
+
+    #[test]
+    fn lints() {
+        let dir = addon_dir("pipeline-lints");
+        std::fs::write(dir.join("addon/lua/autorun/empty.lua"), b"").unwrap();
+        std::fs::create_dir_all(dir.join("addon/materials")).unwrap();
+        std::fs::write(dir.join("addon/materials/a.vtf"), b"\x89PNG\r\n\x1a\n").unwrap();
+        let output = dir.join("out.gma");
+        let mut config = PipelineConfig::new(dir.join("addon"), &output);
+        let expected = [
+            Lint::EmptyFile {
+                filename: "lua/autorun/empty.lua".to_string(),
+            },
+            Lint::MismatchedContent {
+                filename: "materials/a.vtf".to_string(),
+                kind: ContentKind::Png,
+            },
+        ];
+        match build_and_validate(&config) {
+            PipelineOutcome::Published(published) => assert_eq!(published.lints(), &expected),
+            outcome => panic!("{:?}", outcome),
+        }
+        std::fs::remove_file(&output).unwrap();
+
+        config.deny_lints(true);
+        match build_and_validate(&config) {
+            PipelineOutcome::Rejected(Rejection::Lints(lints)) => assert_eq!(lints, expected),
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(!output.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
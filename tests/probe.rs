@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder};
+    use std::io::{Cursor, Seek};
+
+    fn build_archive(name: &str, format: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name(name)
+            .compression_format(format)
+            .file_from_bytes("lua/a.lua", vec![b'a'; 4096]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn plain_and_compressed() {
+        let formats = vec![
+            Compression::None,
+            Compression::Lzma,
+            #[cfg(feature = "brotli")]
+            Compression::Brotli,
+        ];
+        for format in formats {
+            let buffer = build_archive("probed", format);
+            let mut reader = Cursor::new(&buffer[..]);
+            let info = gma::probe(&mut reader).unwrap();
+            assert_eq!(reader.stream_position().unwrap(), 0);
+            assert!(info.is_gma());
+            assert_eq!(info.compression(), Some(format));
+            assert_eq!(info.version().unwrap().as_u8(), 3);
+            assert_eq!(info.name(), Some("probed"));
+        }
+    }
+
+    #[test]
+    fn genuine() {
+        let buffer = include_bytes!("genuine.gma");
+        let archive = gma::load_from_memory(buffer).unwrap();
+        let info = gma::probe(Cursor::new(&buffer[..])).unwrap();
+        assert_eq!(info.compression(), Some(Compression::None));
+        assert_eq!(info.name(), Some(archive.name()));
+    }
+
+    #[test]
+    fn not_an_archive() {
+        let info = gma::probe(Cursor::new(&b"PK\x03\x04 definitely a zip"[..])).unwrap();
+        assert!(!info.is_gma());
+        assert_eq!(info.compression(), None);
+        assert_eq!(info.version(), None);
+        assert_eq!(info.name(), None);
+    }
+
+    #[test]
+    fn long_name_is_not_read() {
+        let buffer = build_archive(&"n".repeat(1000), Compression::None);
+        let info = gma::probe(Cursor::new(&buffer[..])).unwrap();
+        assert!(info.is_gma());
+        assert_eq!(info.name(), None);
+    }
+}
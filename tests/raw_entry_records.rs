@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("records")
+            .file_from_bytes("lua/a.lua", b"aaa".to_vec())
+            .file_from_bytes("materials/b.vmt", b"bb".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn records_match_entries() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let records: Vec<gma::RawEntryRecord> = archive.raw_entry_records().unwrap().collect();
+        assert_eq!(records.len(), 2);
+        for (record, entry) in records.iter().zip(archive.entries()) {
+            assert_eq!(record.filename_bytes(), entry.filename().as_bytes());
+            assert_eq!(record.size(), entry.size());
+            assert_eq!(record.crc(), entry.crc());
+            assert_eq!(record.file_number() as usize, entry.index() + 1);
+            let offset = record.offset() as usize;
+            assert_eq!(&buffer[offset..offset + record.raw().len()], record.raw());
+        }
+        assert_eq!(records[0].offset(), archive.entry_table_range().start);
+        assert_eq!(
+            records[1].offset(),
+            records[0].offset() + records[0].raw().len() as u64
+        );
+    }
+
+    #[test]
+    fn keeps_file_numbers_as_stored() {
+        let mut buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let offset = archive.entry_table_range().start as usize;
+        drop(archive);
+        // the game only cares that the number isn't 0
+        buffer[offset..offset + 4].copy_from_slice(&77u32.to_le_bytes());
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let first = archive.raw_entry_records().unwrap().next().unwrap();
+        assert_eq!(first.file_number(), 77);
+        assert_eq!(archive.entries().next().unwrap().filename(), "lua/a.lua");
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod test {
+    use gma::AddonTag;
+
+    fn raw_archive(description: &str) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(b"GMAD");
+        buffer.push(3);
+        buffer.extend_from_slice(&0u64.to_le_bytes());
+        buffer.extend_from_slice(&0u64.to_le_bytes());
+        buffer.push(0);
+        buffer.extend_from_slice(b"raw\0");
+        buffer.extend_from_slice(description.as_bytes());
+        buffer.push(0);
+        buffer.extend_from_slice(b"author\0");
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(&0u32.to_le_bytes());
+        buffer
+    }
+
+    #[test]
+    fn raw_type_and_tags() {
+        let buffer = raw_archive(
+            r#"{"description":"desc","type":"ServerAddon","tags":["fun","memes","Scenic"]}"#,
+        );
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.addon_type(), None);
+        assert_eq!(archive.addon_type_raw(), Some("ServerAddon"));
+        assert_eq!(archive.addon_tags(), &[AddonTag::Fun]);
+        assert_eq!(archive.tags_raw(), &["fun", "memes", "Scenic"]);
+    }
+
+    #[test]
+    fn raw_type_and_tags_plain_description() {
+        let buffer = raw_archive("not json");
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.description(), "not json");
+        assert_eq!(archive.addon_type_raw(), None);
+        assert!(archive.tags_raw().is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, ReadAt};
+    use std::cell::RefCell;
+    use std::io::Cursor;
+
+    // Remote source that records every requested range
+    struct RangeSource {
+        data: Vec<u8>,
+        requests: RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl ReadAt for RangeSource {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.data.read_at(offset, buf)?;
+            self.requests.borrow_mut().push((offset, n));
+            Ok(n)
+        }
+
+        fn size(&self) -> std::io::Result<u64> {
+            Ok(self.data.len() as u64)
+        }
+    }
+
+    #[test]
+    fn load_read_at_fetches_on_demand() {
+        let big = vec![7u8; 1024 * 1024];
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("remote")
+            .file_from_bytes("big.bin", big.clone())
+            .file_from_bytes("small.txt", b"small".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let source = RangeSource {
+            data: buffer,
+            requests: RefCell::new(Vec::new()),
+        };
+        let archive = gma::load_read_at(&source).unwrap();
+        assert_eq!(archive.name(), "remote");
+        let fetched: usize = source.requests.borrow().iter().map(|(_, n)| n).sum();
+        assert!(fetched < big.len());
+
+        source.requests.borrow_mut().clear();
+        let small = archive
+            .read_entry(&archive["small.txt"], |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(small, "small");
+        let requests = source.requests.borrow();
+        assert_eq!(requests[0].0, archive["small.txt"].absolute_offset());
+        drop(requests);
+
+        let mut contents = Vec::new();
+        archive
+            .read_entry_to_writer(&archive["big.bin"], &mut contents)
+            .unwrap();
+        assert_eq!(contents, big);
+    }
+
+    #[test]
+    fn load_read_at_file() {
+        let file = std::fs::File::open("tests/genuine.gma").unwrap();
+        let archive = gma::load_read_at(file).unwrap();
+        assert_eq!(archive.name(), "My Test Addon");
+    }
+}
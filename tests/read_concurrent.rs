@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, Error, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn build_archive(format: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("concurrent").compression_format(format);
+        for i in 0..16 {
+            builder.file_from_bytes(
+                format!("lua/{}.lua", i),
+                format!("print({})", i).into_bytes(),
+            );
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn read_string<R: gma::ReadAt>(
+        archive: &gma::GMAFile<gma::ReadAtReader<Arc<R>>>,
+        entry: &gma::FileEntry,
+    ) -> gma::Result<String> {
+        archive.read_entry_concurrent(entry, |_, reader| {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents).unwrap();
+            contents
+        })
+    }
+
+    #[test]
+    fn reads_while_reader_is_locked() {
+        let path = std::env::temp_dir().join(format!(
+            "gma-test-read-concurrent-{}.gma",
+            std::process::id()
+        ));
+        std::fs::write(&path, build_archive(Compression::None)).unwrap();
+        let archive = gma::open_concurrent(&path).unwrap();
+
+        let first = archive.entries().next().unwrap();
+        let lines = archive.entry_lines(first).unwrap();
+        assert!(matches!(
+            archive.read_entry(first, |_, _| ()),
+            Err(Error::ReaderInUse)
+        ));
+        let entry = archive.get("lua/7.lua").unwrap();
+        assert_eq!(read_string(&archive, entry).unwrap(), "print(7)");
+
+        drop(lines);
+        drop(archive);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn many_threads() {
+        let archive =
+            Arc::new(gma::load_concurrent(Arc::new(build_archive(Compression::None))).unwrap());
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let archive = archive.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        for entry in archive.entries().skip(worker).step_by(4) {
+                            let expected = entry
+                                .filename()
+                                .replace("lua/", "print(")
+                                .replace(".lua", ")");
+                            assert_eq!(read_string(&archive, entry).unwrap(), expected);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn verify_crc() {
+        let mut buffer = build_archive(Compression::None);
+        let at = buffer.windows(8).position(|w| w == b"print(3)").unwrap();
+        buffer[at] = b'P';
+        let archive = LoadOptions::new()
+            .verify_crc(true)
+            .load_concurrent(Arc::new(buffer))
+            .unwrap();
+        assert!(read_string(&archive, archive.get("lua/2.lua").unwrap()).is_ok());
+        assert!(matches!(
+            read_string(&archive, archive.get("lua/3.lua").unwrap()),
+            Err(Error::CrcMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn compressed_falls_back() {
+        let archive = gma::load_concurrent(Arc::new(build_archive(Compression::Lzma))).unwrap();
+        let entry = archive.get("lua/5.lua").unwrap();
+        assert_eq!(read_string(&archive, entry).unwrap(), "print(5)");
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder, LoadOptions};
+    use std::io::{BufReader, Cursor};
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("read_entries")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("materials/big.vtf", vec![7u8; 100_000])
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec())
+            .file_from_bytes("lua/c.lua", b"print('c')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn read_entries() {
+        let buffer = build_archive();
+        let archive = gma::load(BufReader::new(Cursor::new(&buffer))).unwrap();
+        // out of order, with a duplicate and a large gap between a.lua and b.lua
+        let wanted = vec![
+            archive.get("lua/c.lua").unwrap(),
+            archive.get("lua/a.lua").unwrap(),
+            archive.get("lua/b.lua").unwrap(),
+            archive.get("lua/a.lua").unwrap(),
+        ];
+        let contents = archive.read_entries(wanted).unwrap();
+        assert_eq!(contents.len(), 3);
+        for (name, expected) in [
+            ("lua/a.lua", &b"print('a')"[..]),
+            ("lua/b.lua", b"print('b')"),
+            ("lua/c.lua", b"print('c')"),
+        ] {
+            let entry = archive.get(name).unwrap();
+            assert_eq!(contents[&entry.index()], expected);
+        }
+
+        let all = archive.read_entries(&archive).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[&1], vec![7u8; 100_000]);
+        assert!(archive.read_entries(Vec::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_entries_verify_crc() {
+        let mut buffer = build_archive();
+        let at = buffer.windows(10).position(|w| w == b"print('c')").unwrap();
+        buffer[at] = b'P';
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.read_entries(&archive).unwrap()[&3], b"Print('c')");
+
+        let archive = LoadOptions::new()
+            .verify_crc(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let a = archive.get("lua/a.lua").unwrap();
+        archive.read_entries(vec![a]).unwrap();
+        assert!(matches!(
+            archive.read_entries(&archive),
+            Err(Error::CrcMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::cell::Cell;
+    use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
+    use std::rc::Rc;
+
+    // Cursor whose seeks fail while `fail` is set
+    struct FlakySeek {
+        inner: Cursor<Vec<u8>>,
+        fail: Rc<Cell<bool>>,
+    }
+
+    impl Read for FlakySeek {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl BufRead for FlakySeek {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            self.inner.fill_buf()
+        }
+        fn consume(&mut self, amt: usize) {
+            self.inner.consume(amt)
+        }
+    }
+
+    impl Seek for FlakySeek {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            if self.fail.get() {
+                return Err(std::io::Error::other("seek failed"));
+            }
+            self.inner.seek(pos)
+        }
+    }
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("errors")
+            .file_from_bytes("a.txt", b"first".to_vec())
+            .file_from_bytes("b.txt", b"second".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn read_string(
+        archive: &gma::GMAFile<impl BufRead + Seek>,
+        filename: &str,
+    ) -> gma::Result<String> {
+        archive.read_entry(&archive[filename], |_, reader| {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents).unwrap();
+            contents
+        })
+    }
+
+    #[test]
+    fn read_entry_after_seek_error() {
+        let fail = Rc::new(Cell::new(false));
+        let archive = gma::load(FlakySeek {
+            inner: Cursor::new(build()),
+            fail: fail.clone(),
+        })
+        .unwrap();
+
+        fail.set(true);
+        assert!(matches!(
+            read_string(&archive, "a.txt"),
+            Err(gma::Error::IOError(_))
+        ));
+        fail.set(false);
+        assert_eq!(read_string(&archive, "a.txt").unwrap(), "first");
+        assert_eq!(read_string(&archive, "b.txt").unwrap(), "second");
+    }
+
+    #[test]
+    fn read_entry_reentrant() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let inner = archive
+            .read_entry(&archive["a.txt"], |_, _| read_string(&archive, "b.txt"))
+            .unwrap();
+        assert!(matches!(inner, Err(gma::Error::ReaderInUse)));
+        assert_eq!(read_string(&archive, "b.txt").unwrap(), "second");
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_entry_to_writer() {
+        let contents: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("to writer")
+            .file_from_bytes("first.bin", b"first".to_vec())
+            .file_from_bytes("second.bin", contents.clone());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let mut written = Vec::new();
+        let size = archive
+            .read_entry_to_writer(&archive["second.bin"], &mut written)
+            .unwrap();
+        assert_eq!(size, contents.len() as u64);
+        assert_eq!(written, contents);
+    }
+}
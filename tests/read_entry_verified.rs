@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("verified")
+            .file_from_bytes("lua/hello.lua", b"print('hello')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn read_entry_verified() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.entries().next().unwrap();
+        let contents = archive
+            .read_entry_verified(entry, |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(contents, "print('hello')");
+
+        //partial reads are still verified
+        archive
+            .read_entry_verified(entry, |_, reader| {
+                let mut byte = [0u8; 1];
+                reader.read_exact(&mut byte).unwrap();
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn read_entry_verified_genuine() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        let entry = archive.entries().next().unwrap();
+        archive.read_entry_verified(entry, |_, _| ()).unwrap();
+    }
+
+    #[test]
+    fn read_entry_verified_corrupt() {
+        let mut buffer = build_archive();
+        //the entry contents are the last bytes in the archive
+        let last = buffer.len() - 1;
+        buffer[last] = b'!';
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let entry = archive.entries().next().unwrap();
+        let result = archive.read_entry_verified(entry, |_, reader| {
+            let mut contents = Vec::new();
+            reader.read_to_end(&mut contents).unwrap();
+        });
+        match result {
+            Err(Error::CrcMismatch { filename, .. }) => assert_eq!(filename, "lua/hello.lua"),
+            _ => panic!("expected a crc mismatch"),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder, LossReason};
+    use std::io::Cursor;
+
+    fn build(compression: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("recover")
+            .description("damaged")
+            .author("someone")
+            .steamid(76561198000000000u64)
+            .compression_format(compression)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec())
+            .file_from_bytes("lua/c.lua", b"print('c')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn filenames(recovery: &gma::Recovery) -> Vec<&str> {
+        recovery.entries().iter().map(|e| e.filename()).collect()
+    }
+
+    #[test]
+    fn intact() {
+        let recovery = gma::recover(&build(Compression::None)[..]).unwrap();
+        assert!(recovery.is_complete());
+        assert_eq!(recovery.name(), Some("recover"));
+        assert_eq!(recovery.description(), Some("damaged"));
+        assert_eq!(recovery.author(), Some("someone"));
+        assert_eq!(recovery.author_steamid(), Some(76561198000000000));
+        assert_eq!(
+            filenames(&recovery),
+            ["lua/a.lua", "lua/b.lua", "lua/c.lua"]
+        );
+        assert_eq!(recovery.entries()[1].contents(), b"print('b')");
+    }
+
+    #[test]
+    fn truncated_contents() {
+        let buffer = build(Compression::None);
+        let recovery = gma::recover(&buffer[..buffer.len() - 3]).unwrap();
+        assert!(!recovery.is_complete());
+        assert_eq!(filenames(&recovery), ["lua/a.lua", "lua/b.lua"]);
+        assert_eq!(recovery.lost().len(), 1);
+        assert_eq!(recovery.lost()[0].filename(), "lua/c.lua");
+        assert_eq!(recovery.lost()[0].reason(), &LossReason::Truncated);
+    }
+
+    #[test]
+    fn corrupted_contents() {
+        let mut buffer = build(Compression::None);
+        let position = buffer.len() - 12;
+        buffer[position] ^= 0xff;
+        let recovery = gma::recover(&buffer[..]).unwrap();
+        assert_eq!(filenames(&recovery), ["lua/a.lua", "lua/c.lua"]);
+        assert!(matches!(
+            recovery.lost()[0].reason(),
+            LossReason::CrcMismatch { .. }
+        ));
+
+        let mut clean = Vec::new();
+        recovery.write_to(Cursor::new(&mut clean)).unwrap();
+        let archive = gma::load_from_memory(&clean).unwrap();
+        assert_eq!(archive.name(), "recover");
+        assert_eq!(archive.author_steamid(), 76561198000000000);
+        let names: Vec<&str> = archive.entries().map(|e| e.filename()).collect();
+        assert_eq!(names, ["lua/a.lua", "lua/c.lua"]);
+        for entry in archive.entries() {
+            archive.read_entry_verified(entry, |_, _| ()).unwrap();
+        }
+    }
+
+    #[test]
+    fn damaged_file_table() {
+        let buffer = build(Compression::None);
+        let table_start = buffer
+            .windows(b"lua/b.lua".len())
+            .position(|w| w == b"lua/b.lua")
+            .unwrap();
+        let recovery = gma::recover(&buffer[..table_start + 4]).unwrap();
+        assert_eq!(recovery.name(), Some("recover"));
+        assert!(recovery.entries().is_empty());
+        assert_eq!(recovery.lost().len(), 1);
+        assert_eq!(recovery.lost()[0].reason(), &LossReason::DamagedFileTable);
+    }
+
+    #[test]
+    fn damaged_header() {
+        let buffer = build(Compression::None);
+        let recovery = gma::recover(&buffer[..26]).unwrap();
+        assert!(recovery.version().is_some());
+        assert!(recovery.timestamp().is_some());
+        assert_eq!(recovery.name(), None);
+        assert!(!recovery.is_complete());
+    }
+
+    #[test]
+    fn compressed() {
+        let recovery = gma::recover(&build(Compression::Lzma)[..]).unwrap();
+        assert_eq!(recovery.compression(), Compression::Lzma);
+        assert!(recovery.is_complete());
+        assert_eq!(recovery.entries().len(), 3);
+    }
+
+    #[test]
+    fn not_an_archive() {
+        assert!(matches!(
+            gma::recover(&b"definitely not an archive"[..]),
+            Err(gma::Error::InvalidIdent)
+        ));
+    }
+}
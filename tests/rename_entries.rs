@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod test {
+    use gma::{transform, GMABuilder};
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("rename")
+            .author("someone")
+            .file_from_bytes("materials/old/a.vmt", b"a".to_vec())
+            .file_from_bytes("materials/old/sub/b.vmt", b"b".to_vec())
+            .file_from_bytes("materials/older.vmt", b"c".to_vec())
+            .file_from_bytes("lua/init.lua", b"d".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn filenames(buffer: &[u8]) -> Vec<String> {
+        let archive = gma::load_from_memory(buffer).unwrap();
+        archive.entries().map(|e| e.filename().to_owned()).collect()
+    }
+
+    #[test]
+    fn rename_with_map() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let mut renames = HashMap::new();
+        renames.insert("materials/old/".to_owned(), "materials/new/".to_owned());
+        renames.insert("materials/old/sub/".to_owned(), "materials/sub/".to_owned());
+        let builder = transform::rename_entries(&archive, renames).unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut output)).unwrap();
+        assert_eq!(
+            filenames(&output),
+            [
+                "materials/new/a.vmt",
+                "materials/sub/b.vmt",
+                "materials/older.vmt",
+                "lua/init.lua"
+            ]
+        );
+        let renamed = gma::load_from_memory(&output).unwrap();
+        assert_eq!(renamed.name(), "rename");
+        assert_eq!(renamed.author(), "someone");
+        let contents = renamed
+            .read_entry(&renamed["materials/sub/b.vmt"], |_, reader| {
+                let mut contents = Vec::new();
+                reader.read_to_end(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(contents, b"b");
+    }
+
+    #[test]
+    fn rename_with_fn() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let builder = transform::rename_entries(&archive, |name: &str| {
+            name.strip_prefix("lua/")
+                .map(|rest| format!("lua/myaddon/{}", rest))
+        })
+        .unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut output)).unwrap();
+        assert_eq!(filenames(&output)[3], "lua/myaddon/init.lua");
+    }
+
+    #[test]
+    fn rename_lua_references() {
+        let init = "include(\"myaddon/util.lua\")\n-- include(\"myaddon/util.lua\")\nlocal icon = Material(\"MyAddon/Icon\")\n";
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("references")
+            .file_from_bytes("lua/autorun/init.lua", init.as_bytes().to_vec())
+            .file_from_bytes("lua/myaddon/util.lua", b"return {}".to_vec())
+            .file_from_bytes("materials/myaddon/icon.png", b"png".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let rename = |name: &str| Some(name.replace("myaddon", "newaddon"));
+        let mut options = transform::RenameOptions::new();
+        options.lua_references(transform::LuaReferences::Report);
+        let (_, report) = transform::rename_entries_with(&archive, rename, &options).unwrap();
+        let references = report.lua_references();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].file(), "lua/autorun/init.lua");
+        assert_eq!(references[0].line(), 1);
+        assert_eq!(references[0].replacement(), "newaddon/util.lua");
+        assert_eq!(references[1].line(), 3);
+        assert_eq!(references[1].literal(), "MyAddon/Icon");
+        assert_eq!(references[1].replacement(), "newaddon/icon");
+        assert!(!references[1].rewritten());
+
+        options.lua_references(transform::LuaReferences::Rewrite);
+        let (builder, report) = transform::rename_entries_with(&archive, rename, &options).unwrap();
+        assert!(report.lua_references()[0].rewritten());
+        let mut output: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut output)).unwrap();
+        let renamed = gma::load_from_memory(&output).unwrap();
+        let contents = renamed
+            .read_entry(&renamed["lua/autorun/init.lua"], |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents).unwrap();
+                contents
+            })
+            .unwrap();
+        assert_eq!(
+            contents,
+            "include(\"newaddon/util.lua\")\n-- include(\"myaddon/util.lua\")\nlocal icon = Material(\"newaddon/icon\")\n"
+        );
+    }
+
+    #[test]
+    fn lua_references_need_a_directory() {
+        let init = "include(\"a.lua\")\nlocal font = \"gui\"\n";
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("references")
+            .file_from_bytes("lua/init.lua", init.as_bytes().to_vec())
+            .file_from_bytes("lua/a.lua", b"return {}".to_vec())
+            .file_from_bytes("materials/gui.png", b"png".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let rename = |name: &str| match name {
+            "lua/a.lua" => Some("lua/b.lua".to_owned()),
+            "materials/gui.png" => Some("materials/hud.png".to_owned()),
+            _ => None,
+        };
+        let mut options = transform::RenameOptions::new();
+        options.lua_references(transform::LuaReferences::Rewrite);
+        let (_, report) = transform::rename_entries_with(&archive, rename, &options).unwrap();
+        assert!(report.lua_references().is_empty());
+    }
+}
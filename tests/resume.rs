@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder};
+    use std::io::{Cursor, Read};
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("the source went away"))
+        }
+    }
+
+    fn builder(first: &[u8], second: Option<&'static [u8]>) -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("resume")
+            .file_from_bytes("lua/a.lua", first.to_vec());
+        match second {
+            Some(contents) => builder.file_from_reader("lua/b.lua", contents),
+            None => builder.file_from_reader("lua/b.lua", Failing),
+        };
+        builder.file_from_bytes("lua/c.lua", b"print('c')".to_vec());
+        builder
+    }
+
+    #[test]
+    fn resumes_after_failure() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let token =
+            match builder(b"print('a')", None).write_to_with_report(Cursor::new(&mut buffer)) {
+                Err(Error::BuildInterrupted { error, report }) => {
+                    match *error {
+                        Error::InEntry { name, source } => {
+                            assert_eq!(name, "lua/b.lua");
+                            assert!(matches!(*source, Error::IOError(_)));
+                        }
+                        other => panic!("unexpected error {:?}", other),
+                    }
+                    assert_eq!(report.files().len(), 1);
+                    report.resume_token().unwrap().clone()
+                }
+                other => panic!("unexpected result {:?}", other),
+            };
+        assert_eq!(token.files()[0].filename(), "lua/a.lua");
+
+        // the files already written are not read again
+        let report = builder(b"XXXXXXXXXX", Some(b"print('b')"))
+            .resume(token, Cursor::new(&mut buffer))
+            .unwrap();
+        assert_eq!(report.files().len(), 3);
+        assert!(report.resume_token().is_none());
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        for (name, contents) in [
+            ("lua/a.lua", "print('a')"),
+            ("lua/b.lua", "print('b')"),
+            ("lua/c.lua", "print('c')"),
+        ] {
+            let entry = archive.get(name).unwrap();
+            let read = archive
+                .read_entry_verified(entry, |_, reader| {
+                    let mut read = String::new();
+                    reader.read_to_string(&mut read).map(|_| read)
+                })
+                .unwrap()
+                .unwrap();
+            assert_eq!(read, contents);
+        }
+    }
+
+    #[test]
+    fn rejects_different_builder() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let token = match builder(b"print('a')", None)
+            .write_to_with_report(Cursor::new(&mut buffer))
+        {
+            Err(Error::BuildInterrupted { report, .. }) => report.resume_token().unwrap().clone(),
+            other => panic!("unexpected result {:?}", other),
+        };
+        let mut different = builder(b"print('a')", Some(b"print('b')"));
+        different.name("other");
+        assert!(matches!(
+            different.resume(token, Cursor::new(&mut buffer)),
+            Err(Error::ResumeMismatch)
+        ));
+    }
+
+    #[test]
+    fn compressed_builds_are_not_resumable() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut failing = builder(b"print('a')", None);
+        failing.compression(true);
+        let error = failing.write_to(Cursor::new(&mut buffer)).unwrap_err();
+        assert_eq!(error.entry(), Some("lua/b.lua"));
+        assert!(error.to_string().starts_with("'lua/b.lua' : "));
+    }
+
+    #[test]
+    fn only_resumable_outputs_are_interrupted() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let error = builder(b"print('a')", None)
+            .write_to(Cursor::new(&mut buffer))
+            .unwrap_err();
+        assert!(matches!(error, Error::InEntry { .. }));
+
+        // the partial temporary file is removed, there is nothing to resume
+        let dir = std::env::temp_dir().join(format!("gma-test-resume-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let error = builder(b"print('a')", None)
+            .write_to_path(dir.join("addon.gma"))
+            .unwrap_err();
+        assert!(matches!(error, Error::InEntry { .. }));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("sample");
+        for i in 0..50 {
+            builder.file_from_bytes(
+                format!("lua/{}.lua", i),
+                format!("print({})", i).into_bytes(),
+            );
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn indices(sample: &[&gma::FileEntry]) -> Vec<usize> {
+        sample.iter().map(|e| e.index()).collect()
+    }
+
+    #[test]
+    fn deterministic() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let sample = indices(&archive.sample_entries(10, 1));
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample, indices(&archive.sample_entries(10, 1)));
+        let other = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(sample, indices(&other.sample_entries(10, 1)));
+        assert_ne!(sample, indices(&archive.sample_entries(10, 2)));
+    }
+
+    #[test]
+    fn small_archives() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.sample_entries(100, 3).len(), 50);
+        assert!(archive.sample_entries(0, 3).is_empty());
+    }
+
+    #[test]
+    fn verify_sample() {
+        let mut buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        archive.verify_sample(10, 5).unwrap();
+        let entry = archive.sample_entries(1, 5)[0];
+        let (position, corrupted) = (
+            entry.absolute_offset() as usize,
+            entry.filename().to_owned(),
+        );
+        drop(archive);
+
+        buffer[position] ^= 0xff;
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        match archive.verify_sample(1, 5) {
+            Err(gma::Error::CrcMismatch { filename, .. }) => {
+                assert_eq!(filename, corrupted)
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod test {
+    use gma::{AddonTag, AddonType};
+    use std::io::Cursor;
+
+    // a header like the ones sharpGMad writes, with one entry
+    fn sharpgmad_archive(metadata: &str) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(b"GMAD");
+        buffer.push(3);
+        buffer.extend_from_slice(&0u64.to_le_bytes());
+        buffer.extend_from_slice(&1_595_515_015u64.to_le_bytes());
+        buffer.push(0);
+        buffer.extend_from_slice(b"sharp\0");
+        buffer.extend_from_slice(metadata.as_bytes());
+        buffer.push(0);
+        buffer.extend_from_slice(b"Author Name\0");
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(b"addon.json\0");
+        buffer.extend_from_slice(&2u64.to_le_bytes());
+        buffer.extend_from_slice(&0u32.to_le_bytes());
+        buffer.extend_from_slice(&0u32.to_le_bytes());
+        buffer.extend_from_slice(b"{}");
+        buffer
+    }
+
+    const CAPITALIZED: &str = "{\r\n\t\"description\": \"desc\",\r\n\t\"type\": \"Tool\",\r\n\t\"tags\": [ \"Fun\", \"Build\" ],\r\n\t\"ignore\": [ \"*.psd\" ]\r\n}";
+
+    #[test]
+    fn capitalized_and_extra_keys() {
+        let buffer = sharpgmad_archive(CAPITALIZED);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.description(), "desc");
+        assert_eq!(archive.addon_type(), Some(AddonType::Tool));
+        assert_eq!(archive.addon_type_raw(), Some("Tool"));
+        assert_eq!(archive.addon_tags(), &[AddonTag::Fun, AddonTag::Build]);
+        assert_eq!(archive.tags_raw(), &["Fun", "Build"]);
+        assert_eq!(archive.metadata_json(), CAPITALIZED);
+        assert!(archive.get("addon.json").is_some());
+    }
+
+    #[test]
+    fn missing_fields() {
+        let buffer = sharpgmad_archive(r#"{"type":"map"}"#);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.description(), "");
+        assert_eq!(archive.addon_type(), Some(AddonType::Map));
+        assert!(archive.addon_tags().is_empty());
+    }
+
+    #[test]
+    fn round_trip_keeps_metadata() {
+        let buffer = sharpgmad_archive(CAPITALIZED);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let builder = gma::transform::rename_entries(&archive, |_: &str| None).unwrap();
+        let mut output: Vec<u8> = Vec::new();
+        builder.write_to(Cursor::new(&mut output)).unwrap();
+
+        let copy = gma::load_from_memory(&output).unwrap();
+        assert_eq!(copy.metadata_json(), CAPITALIZED);
+        assert_eq!(copy.author(), "Author Name");
+        assert_eq!(copy.timestamp(), 1_595_515_015);
+        let entry = copy.get("addon.json").unwrap();
+        let mut contents = String::new();
+        copy.read_entry(entry, |_, reader| reader.read_to_string(&mut contents))
+            .unwrap()
+            .unwrap();
+        assert_eq!(contents, "{}");
+    }
+}
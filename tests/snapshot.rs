@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("snapshot").timestamp(1_000);
+        for (name, contents) in files {
+            builder.file_from_bytes(*name, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn snapshot_matches_archive() {
+        let buffer = build_archive(&[("lua/a.lua", b"print('a')"), ("lua/b.lua", b"b")]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let snapshot = archive.snapshot().unwrap();
+        assert_eq!(snapshot.metadata(), &archive.metadata());
+        assert_eq!(snapshot.entries().len(), 2);
+        assert_eq!(snapshot.get("lua/b.lua").unwrap().size, 1);
+        assert!(snapshot.get("lua/c.lua").is_none());
+
+        let clone = snapshot.clone();
+        assert_eq!(clone.generation(), snapshot.generation());
+        assert!(clone.is_current(&archive).unwrap());
+        assert_eq!(
+            archive.snapshot().unwrap().generation(),
+            snapshot.generation()
+        );
+    }
+
+    #[test]
+    fn rewritten_file() {
+        let path =
+            std::env::temp_dir().join(format!("gma-test-snapshot-{}.gma", std::process::id()));
+        std::fs::write(&path, build_archive(&[("lua/a.lua", b"print('a')")])).unwrap();
+        let archive = gma::open(&path).unwrap();
+        let snapshot = archive.snapshot().unwrap();
+        assert!(snapshot.is_current(&archive).unwrap());
+
+        std::fs::write(
+            &path,
+            build_archive(&[("lua/a.lua", b"print('rewritten')")]),
+        )
+        .unwrap();
+        assert!(!snapshot.is_current(&archive).unwrap());
+        assert_eq!(snapshot.entries().len(), 1);
+        assert_eq!(snapshot.get("lua/a.lua").unwrap().size, 10);
+        assert_ne!(
+            archive.snapshot().unwrap().generation(),
+            snapshot.generation()
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
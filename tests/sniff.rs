@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod test {
+    use gma::{ContentKind, GMABuilder};
+    use std::io::Cursor;
+
+    #[test]
+    fn sniff() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("sniff")
+            .file_from_bytes("materials/fake.vmt", b"VTF\0\x07\0\0\0\x02\0\0\0".to_vec())
+            .file_from_bytes("lua/autorun/init.lua", b"\x1bLJ\x02\x0a".to_vec())
+            .file_from_bytes("lua/autorun/plain.lua", b"print('hi')".to_vec())
+            .file_from_bytes("empty.txt", Vec::new());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let kinds: Vec<ContentKind> = archive
+            .entries()
+            .map(|e| archive.sniff(e).unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ContentKind::Vtf,
+                ContentKind::CompiledLua,
+                ContentKind::Unknown,
+                ContentKind::Unknown
+            ]
+        );
+    }
+}
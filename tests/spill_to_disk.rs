@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder, LoadOptions};
+    use std::io::Cursor;
+
+    fn spill_files() -> usize {
+        let prefix = format!(".gma-spill-{}-", std::process::id());
+        std::fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with(&prefix)
+            })
+            .count()
+    }
+
+    #[test]
+    fn spill_to_disk() {
+        let contents: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("spill")
+            .compression_format(Compression::Lzma)
+            .file_from_bytes("maps/big.bsp", contents.clone())
+            .file_from_bytes("lua/init.lua", b"print('spill')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let in_memory = gma::load_from_memory(&buffer).unwrap();
+        let spilled = LoadOptions::new()
+            .spill_to_disk(64 * 1024)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(spill_files(), 1);
+        assert_eq!(spilled.compression(), Compression::Lzma);
+        assert_eq!(spilled.decompressed_size(), in_memory.decompressed_size());
+        assert!(spilled.approx_memory_usage() + 200_000 < in_memory.approx_memory_usage());
+
+        let big = spilled.get("maps/big.bsp").unwrap();
+        let read = spilled
+            .read_entry_verified(big, |_, reader| {
+                let mut data = Vec::new();
+                reader.read_to_end(&mut data).unwrap();
+                data
+            })
+            .unwrap();
+        assert_eq!(read, contents);
+        spilled.verify_sample(2, 0).unwrap();
+
+        let buffered = spilled.buffer_all().unwrap();
+        assert_eq!(spill_files(), 0);
+        assert_eq!(buffered.entry_count(), 2);
+
+        // small archives stay in memory
+        LoadOptions::new()
+            .spill_to_disk(1024 * 1024)
+            .load_from_memory(&buffer)
+            .unwrap();
+        assert_eq!(spill_files(), 0);
+    }
+}
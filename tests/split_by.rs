@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test {
+    use gma::transform::{split_by, ByDirectory, LuaAndContent};
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("split")
+            .author("someone")
+            .file_from_bytes("lua/autorun/init.lua", b"a".to_vec())
+            .file_from_bytes("materials/split/icon.png", b"b".to_vec())
+            .file_from_bytes("LUA/weapons/gun.lua", b"c".to_vec())
+            .file_from_bytes("models/split/prop.mdl", b"d".to_vec())
+            .file_from_bytes("materials/other.vmt", b"e".to_vec())
+            .file_from_bytes("readme.txt", b"f".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    fn groups<C: gma::transform::Classifier>(classifier: C) -> Vec<(String, Vec<String>)> {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        split_by(&archive, classifier)
+            .unwrap()
+            .into_iter()
+            .map(|(group, builder)| {
+                let mut output: Vec<u8> = Vec::new();
+                builder.write_to(Cursor::new(&mut output)).unwrap();
+                let split = gma::load_from_memory(&output).unwrap();
+                assert_eq!(split.name(), "split");
+                assert_eq!(split.author(), "someone");
+                let names = split.entries().map(|e| e.filename().to_owned()).collect();
+                (group, names)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_lua_and_content() {
+        assert_eq!(
+            groups(LuaAndContent),
+            [
+                (
+                    "content".to_owned(),
+                    vec![
+                        "materials/split/icon.png".to_owned(),
+                        "models/split/prop.mdl".to_owned(),
+                        "materials/other.vmt".to_owned(),
+                        "readme.txt".to_owned(),
+                    ]
+                ),
+                (
+                    "lua".to_owned(),
+                    vec![
+                        "lua/autorun/init.lua".to_owned(),
+                        "LUA/weapons/gun.lua".to_owned(),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_by_directory() {
+        let groups: Vec<(String, usize)> = groups(ByDirectory(1))
+            .into_iter()
+            .map(|(group, names)| (group, names.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("".to_owned(), 1),
+                ("LUA".to_owned(), 1),
+                ("lua".to_owned(), 1),
+                ("materials".to_owned(), 2),
+                ("models".to_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_with_closure() {
+        let groups = groups(|filename: &str| filename.len().to_string());
+        assert_eq!(groups.iter().map(|(_, n)| n.len()).sum::<usize>(), 6);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test {
+    use gma::{ContentCategory, GMABuilder};
+    use std::io::Cursor;
+
+    #[test]
+    fn stats() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("stats")
+            .file_from_bytes("lua/autorun/init.lua", vec![0; 10])
+            .file_from_bytes("gamemodes/gm/gamemode/init.lua", vec![0; 20])
+            .file_from_bytes("models/props/box.mdl", vec![0; 300])
+            .file_from_bytes("gamemodes/gm/content/materials/a.vtf", vec![0; 50])
+            .file_from_bytes("materials/b.vmt", vec![0; 5])
+            .file_from_bytes("sound/a.wav", vec![0; 100])
+            .file_from_bytes("maps/gm_test.bsp", vec![0; 1000])
+            .file_from_bytes("readme.txt", vec![0; 1]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let stats = archive.stats();
+        assert_eq!(stats.entry_count(), 8);
+        assert_eq!(stats.total_size(), 1486);
+
+        let lua = stats.category(ContentCategory::Lua);
+        assert_eq!((lua.count(), lua.total_size()), (2, 30));
+        let materials = stats.category(ContentCategory::Materials);
+        assert_eq!((materials.count(), materials.total_size()), (2, 55));
+        assert_eq!(stats.category(ContentCategory::Models).total_size(), 300);
+        assert_eq!(stats.category(ContentCategory::Sounds).total_size(), 100);
+        assert_eq!(stats.category(ContentCategory::Maps).total_size(), 1000);
+        assert_eq!(stats.category(ContentCategory::Other).count(), 1);
+        assert_eq!(stats.categories().count(), ContentCategory::ALL.len());
+
+        let largest: Vec<&str> = stats
+            .largest_entries()
+            .iter()
+            .take(2)
+            .map(|e| e.filename())
+            .collect();
+        assert_eq!(largest, vec!["maps/gm_test.bsp", "models/props/box.mdl"]);
+    }
+}
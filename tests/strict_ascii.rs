@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn builder() -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("ascii")
+            .file_from_bytes("lua/init.lua", b"print(1)".to_vec())
+            .file_from_bytes("materials/café.vmt", b"material".to_vec());
+        builder
+    }
+
+    #[test]
+    fn strict_ascii_rejects() {
+        let mut builder = builder();
+        builder.strict_ascii(true);
+        let mut buffer: Vec<u8> = Vec::new();
+        match builder.write_to(Cursor::new(&mut buffer)) {
+            Err(gma::Error::NonAsciiFilenames(names)) => {
+                assert_eq!(names.len(), 1);
+                assert_eq!(names[0].filename(), "materials/café.vmt");
+                assert_eq!(names[0].suggestion(), "materials/cafe.vmt");
+            }
+            other => panic!("expected NonAsciiFilenames, got {:?}", other.err()),
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn non_ascii_entries() {
+        let mut buffer: Vec<u8> = Vec::new();
+        builder().write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let names = archive.non_ascii_entries();
+        assert_eq!(names.len(), 1);
+        assert_eq!(names[0].filename(), "materials/café.vmt");
+        assert_eq!(names[0].suggestion(), "materials/cafe.vmt");
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn builder() -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("whitelist")
+            .file_from_bytes("lua/init.lua", b"print(1)".to_vec())
+            .file_from_bytes("addon.json", b"{}".to_vec())
+            .file_from_bytes("materials/a.vmt", b"material".to_vec())
+            .file_from_bytes("lua/run.exe", b"MZ".to_vec());
+        builder
+    }
+
+    #[test]
+    fn strict_whitelist_rejects() {
+        let mut builder = builder();
+        builder.strict_whitelist(true);
+        let mut buffer: Vec<u8> = Vec::new();
+        match builder.write_to(Cursor::new(&mut buffer)) {
+            Err(gma::Error::NotWhitelisted(filenames)) => {
+                assert_eq!(filenames, vec!["addon.json", "lua/run.exe"]);
+            }
+            other => panic!("expected NotWhitelisted, got {:?}", other.err()),
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn strict_whitelist_disabled() {
+        let mut buffer: Vec<u8> = Vec::new();
+        builder().write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.entry_count(), 4);
+    }
+}
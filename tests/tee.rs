@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use gma::{Compression, GMABuilder};
+    use std::io::Cursor;
+
+    fn builder(format: Compression) -> GMABuilder {
+        let mut builder = GMABuilder::new();
+        builder
+            .name("tee")
+            .timestamp(1_595_515_015)
+            .compression_format(format)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder
+    }
+
+    #[test]
+    fn same_output_as_write_to() {
+        for format in [Compression::None, Compression::Lzma] {
+            let mut expected: Vec<u8> = Vec::new();
+            builder(format)
+                .write_to(Cursor::new(&mut expected))
+                .unwrap();
+
+            let mut local = Cursor::new(Vec::new());
+            // the second writer already has data before the archive
+            let mut upload = Cursor::new(b"prefix".to_vec());
+            upload.set_position(6);
+            let report = builder(format)
+                .write_to_multi(&mut [&mut local, &mut upload])
+                .unwrap();
+            assert_eq!(report.files().len(), 2);
+            assert_eq!(local.get_ref(), &expected);
+            assert_eq!(&upload.get_ref()[..6], b"prefix");
+            assert_eq!(&upload.get_ref()[6..], &expected[..]);
+        }
+    }
+
+    #[test]
+    fn entries_are_patched() {
+        let mut local = Cursor::new(Vec::new());
+        let mut upload = Cursor::new(vec![0u8; 3]);
+        upload.set_position(3);
+        builder(Compression::None)
+            .write_to_multi(&mut [&mut local, &mut upload])
+            .unwrap();
+        let archive = gma::load_from_memory(&upload.get_ref()[3..]).unwrap();
+        let entry = archive.get("lua/b.lua").unwrap();
+        assert_eq!(entry.size(), 10);
+        archive.read_entry_verified(entry, |_, _| ()).unwrap();
+    }
+}
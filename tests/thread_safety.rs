@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod test {
+    use gma::{
+        BuildReport, EntryView, Error, FileEntry, GMAFile, Interner, LoadOptions, ParseTrace,
+        SharedCursor,
+    };
+    use std::{
+        fs::File,
+        io::{BufReader, Cursor},
+        sync::{mpsc, Arc},
+        time::Duration,
+    };
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    // fails to compile if any of these stops being Send or Sync
+    const _: fn() = || {
+        assert_send::<GMAFile<BufReader<File>>>();
+        assert_sync::<GMAFile<BufReader<File>>>();
+        assert_send::<GMAFile<Cursor<Vec<u8>>>>();
+        assert_sync::<GMAFile<Cursor<Vec<u8>>>>();
+        assert_send::<GMAFile<Cursor<&'static [u8]>>>();
+        assert_sync::<GMAFile<Cursor<&'static [u8]>>>();
+        assert_send::<GMAFile<SharedCursor>>();
+        assert_sync::<GMAFile<SharedCursor>>();
+        assert_send::<FileEntry>();
+        assert_sync::<FileEntry>();
+        assert_send::<EntryView<'static>>();
+        assert_sync::<EntryView<'static>>();
+        assert_send::<LoadOptions>();
+        assert_sync::<LoadOptions>();
+        assert_send::<Interner>();
+        assert_sync::<Interner>();
+        assert_send::<ParseTrace>();
+        assert_sync::<ParseTrace>();
+        assert_send::<BuildReport>();
+        assert_sync::<BuildReport>();
+        assert_send::<Error>();
+        assert_sync::<Error>();
+    };
+
+    #[test]
+    fn archive_moves_to_worker_thread() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/addon.gma");
+        let archive = gma::open(path).unwrap();
+        let worker = std::thread::spawn(move || {
+            let entry = archive.entries().next().unwrap();
+            archive.read_entry(entry, |entry, _| entry.size()).unwrap()
+        });
+        assert!(worker.join().unwrap() > 0);
+    }
+
+    #[test]
+    fn archive_shared_between_threads() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/addon.gma");
+        let archive = Arc::new(gma::open(path).unwrap());
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let archive = archive.clone();
+                std::thread::spawn(move || {
+                    let mut total = 0;
+                    // the reader is shared, reads wait for the other threads
+                    for entry in archive.entries() {
+                        total += archive
+                            .read_entry_to_writer(entry, &mut std::io::sink())
+                            .unwrap();
+                    }
+                    total
+                })
+            })
+            .collect();
+        let expected: u64 = archive.entries().map(FileEntry::size).sum();
+        for worker in workers {
+            assert_eq!(worker.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn reads_wait_for_other_threads() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/addon.gma");
+        let archive = gma::open(path).unwrap();
+        let entry = archive.entries().next().unwrap();
+        let (started, wait) = mpsc::channel();
+        std::thread::scope(|scope| {
+            let lines = archive.entry_lines(entry).unwrap();
+            let reader = scope.spawn(|| {
+                started.send(()).unwrap();
+                archive.read_entry(entry, |entry, _| entry.size())
+            });
+            wait.recv().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            // only reads on the thread holding the reader fail
+            assert!(matches!(
+                archive.read_entry(entry, |_, _| ()),
+                Err(Error::ReaderInUse)
+            ));
+            drop(lines);
+            assert_eq!(reader.join().unwrap().unwrap(), entry.size());
+        });
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::{
+        io::Cursor,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn created_at() {
+        let time = UNIX_EPOCH + Duration::from_secs(1595515015);
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("timestamps").created_at(time);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.timestamp(), 1595515015);
+        assert_eq!(archive.created_at(), Some(time));
+    }
+
+    #[test]
+    fn created_at_before_epoch() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("timestamps")
+            .created_at(UNIX_EPOCH - Duration::from_secs(10));
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(archive.timestamp(), 0);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn created_at_utc() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        let date = archive.created_at_utc().unwrap();
+        assert_eq!(date.to_rfc3339(), "2020-07-23T14:36:55+00:00");
+    }
+}
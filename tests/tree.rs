@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn tree() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("tree")
+            .file_from_bytes("lua/autorun/server/sv.lua", b"12".to_vec())
+            .file_from_bytes("lua/autorun/client/cl.lua", b"123".to_vec())
+            .file_from_bytes("lua/autorun/b.lua", b"1".to_vec())
+            .file_from_bytes("lua/autorun/a.lua", b"1".to_vec())
+            .file_from_bytes("addon.txt", b"1234".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let root = archive.tree();
+        assert_eq!(root.name(), "");
+        assert_eq!(root.total_size(), 11);
+        assert_eq!(root.files().len(), 1);
+        assert_eq!(root.files()[0].filename(), "addon.txt");
+        assert_eq!(root.directories().len(), 1);
+
+        let autorun = root.directory("lua/autorun").unwrap();
+        assert_eq!(autorun.name(), "autorun");
+        assert_eq!(autorun.total_size(), 7);
+        let dirs: Vec<&str> = autorun.directories().iter().map(|d| d.name()).collect();
+        assert_eq!(dirs, vec!["client", "server"]);
+        let files: Vec<&str> = autorun.files().iter().map(|f| f.file_name()).collect();
+        assert_eq!(files, vec!["a.lua", "b.lua"]);
+
+        assert!(root.directory("lua/missing").is_none());
+    }
+}
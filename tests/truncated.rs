@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    fn build() -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("truncated")
+            .file_from_bytes("a.txt", vec![b'a'; 100])
+            .file_from_bytes("b.txt", vec![b'b'; 100]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn truncated_header() {
+        let buffer = build();
+        // ends in the middle of the steamid
+        let result = gma::load_from_memory(&buffer[..8]);
+        assert!(matches!(
+            result,
+            Err(gma::Error::Truncated {
+                expected: 13,
+                at: 8
+            })
+        ));
+        // ends in the middle of the name
+        let result = gma::load_from_memory(&buffer[..26]);
+        assert!(matches!(
+            result,
+            Err(gma::Error::Truncated {
+                expected: 27,
+                at: 26
+            })
+        ));
+    }
+
+    #[test]
+    fn truncated_contents() {
+        let buffer = build();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let data_end = archive.data_offset() + 200;
+        let truncated = &buffer[..data_end as usize - 50];
+        match gma::load_from_memory(truncated) {
+            Err(gma::Error::Truncated { expected, at }) => {
+                assert_eq!(expected, data_end);
+                assert_eq!(at, truncated.len() as u64);
+            }
+            other => panic!("expected a truncated error, got {:?}", other.err()),
+        }
+        assert!(gma::load_from_memory(&buffer[..data_end as usize]).is_ok());
+    }
+
+    #[test]
+    fn truncated_traced() {
+        let buffer = build();
+        let error = gma::LoadOptions::new()
+            .trace(true)
+            .load_from_memory(&buffer[..8])
+            .err()
+            .unwrap();
+        assert_eq!(error.trace().unwrap().last().unwrap().name(), "steamid");
+        assert!(matches!(
+            error,
+            gma::Error::Traced { error, .. } if matches!(*error, gma::Error::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_contents_traced() {
+        let buffer = build();
+        let truncated = &buffer[..buffer.len() - 50];
+        let error = gma::LoadOptions::new()
+            .trace(true)
+            .load_from_memory(truncated)
+            .err()
+            .unwrap();
+        // the entry table was read completely, the contents are what is missing
+        assert_eq!(
+            error.trace().unwrap().last().unwrap().name(),
+            "entries[2].number"
+        );
+        assert!(matches!(
+            error,
+            gma::Error::Traced { error, .. } if matches!(*error, gma::Error::Truncated { .. })
+        ));
+    }
+}
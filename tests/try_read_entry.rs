@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn try_read_entry() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("try")
+            .file_from_bytes("text.txt", b"hello".to_vec())
+            .file_from_bytes("binary.bin", vec![0xff, 0xfe]);
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let read_string = |filename: &str| {
+            archive.try_read_entry(&archive[filename], |_, reader| {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents)?;
+                Ok(contents)
+            })
+        };
+        assert_eq!(read_string("text.txt").unwrap(), "hello");
+        match read_string("binary.bin") {
+            Err(gma::Error::IOError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+            other => panic!("expected an io error, got {:?}", other),
+        }
+
+        let custom = archive.try_read_entry(&archive["text.txt"], |entry, _| -> gma::Result<()> {
+            Err(gma::Error::UnsafePath(entry.filename().to_owned()))
+        });
+        assert!(matches!(custom, Err(gma::Error::UnsafePath(name)) if name == "text.txt"));
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod test {
+    use gma::{GMABuilder, ValidationIssue};
+    use std::io::Cursor;
+
+    fn build(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder.name("validate");
+        for (filename, contents) in files {
+            builder.file_from_bytes(*filename, contents.to_vec());
+        }
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn valid_archive() {
+        let buffer = build(&[
+            ("lua/autorun/a.lua", b"print(1)"),
+            ("materials/b.vmt", b"b"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive.validate().unwrap();
+        assert!(report.is_valid(), "{}", report);
+    }
+
+    #[test]
+    fn genuine_archive() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        assert!(archive.validate().unwrap().is_valid());
+    }
+
+    #[test]
+    fn filenames() {
+        let buffer = build(&[
+            ("lua/a.lua", b"a"),
+            ("LUA/A.lua", b"a"),
+            ("notes.txt", b"b"),
+            ("lua/../c.lua", b"c"),
+        ]);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive.validate().unwrap();
+        let issues = report.issues();
+        assert!(issues.contains(&ValidationIssue::NotWhitelisted {
+            filename: "notes.txt".to_string()
+        }));
+        assert!(issues.contains(&ValidationIssue::DuplicatePath(vec![
+            "lua/a.lua".to_string(),
+            "LUA/A.lua".to_string()
+        ])));
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            ValidationIssue::IllegalFilename { filename, .. } if filename == "lua/../c.lua"
+        )));
+        assert_eq!(issues.len(), 3);
+    }
+
+    #[test]
+    fn crc_mismatch() {
+        let mut buffer = build(&[("lua/a.lua", b"print(1)")]);
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xff;
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive.validate().unwrap();
+        assert!(matches!(
+            report.issues(),
+            [ValidationIssue::CrcMismatch { filename, .. }] if filename == "lua/a.lua"
+        ));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        // loading rejects archives that are already truncated, so truncate it after opening
+        let buffer = build(&[("lua/a.lua", b"a"), ("lua/b.lua", b"print(1)")]);
+        let dir = std::env::temp_dir().join(format!("gma-test-validate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("truncated.gma");
+        std::fs::write(&path, &buffer).unwrap();
+        let archive = gma::open(&path).unwrap();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(buffer.len() as u64 - 4)
+            .unwrap();
+        let report = archive.validate().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            report.issues(),
+            [ValidationIssue::OutOfBounds { filename, .. }] if filename == "lua/b.lua"
+        ));
+    }
+
+    #[test]
+    fn long_strings() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("n".repeat(gma::MAX_NAME_LENGTH + 1))
+            .file_from_bytes("lua/a.lua", b"a".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let report = archive.validate().unwrap();
+        assert_eq!(
+            report.issues(),
+            &[ValidationIssue::StringTooLong {
+                field: "name".to_string(),
+                len: gma::MAX_NAME_LENGTH + 1,
+                max: gma::MAX_NAME_LENGTH
+            }]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder};
+    use std::io::Cursor;
+
+    fn build_archive(compressed: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("view")
+            .compression(compressed)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn borrowed_contents() {
+        let buffer = build_archive(false);
+        let contents = {
+            let view = gma::load_view(&buffer).unwrap();
+            assert_eq!(view.archive().name(), "view");
+            let entry = view.archive().get("lua/b.lua").unwrap();
+            view.entry_bytes(entry).unwrap()
+        };
+        // the slice outlives the view and points into the buffer
+        assert_eq!(contents, b"print('b')");
+        let range = buffer.as_ptr_range();
+        assert!(range.contains(&contents.as_ptr()));
+
+        let view = gma::load_view(&buffer).unwrap();
+        let all: Vec<(&str, &[u8])> = view
+            .entries_with_bytes()
+            .map(|(entry, contents)| (entry.filename(), contents))
+            .collect();
+        assert_eq!(
+            all,
+            vec![
+                ("lua/a.lua", &b"print('a')"[..]),
+                ("lua/b.lua", &b"print('b')"[..])
+            ]
+        );
+    }
+
+    #[test]
+    fn compressed_archive() {
+        let buffer = build_archive(true);
+        assert!(matches!(
+            gma::load_view(&buffer),
+            Err(Error::CompressedArchive)
+        ));
+        assert!(gma::load_from_memory(&buffer).is_ok());
+    }
+}
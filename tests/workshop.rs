@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test {
+    use crc::{Crc, CRC_32_ISO_HDLC};
+    use gma::workshop::{verify_local, LocalStatus, WorkshopManifest};
+    use gma::{Compression, GMABuilder};
+    use std::io::Cursor;
+
+    fn build_archive() -> Vec<u8> {
+        build_archive_with(Compression::None)
+    }
+
+    fn build_archive_with(compression: Compression) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("workshop")
+            .timestamp(1_000)
+            .compression_format(compression)
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn size_and_time() {
+        let buffer = build_archive();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let size = buffer.len() as u64;
+        let status = |size, time_updated| {
+            verify_local(&WorkshopManifest::new(size, time_updated), &archive).unwrap()
+        };
+        assert_eq!(status(size, 2_000), LocalStatus::UpToDate);
+        assert_eq!(status(size + 1, 2_000), LocalStatus::Outdated);
+        assert_eq!(status(size + 1, 500), LocalStatus::Modified);
+    }
+
+    #[test]
+    fn corrupted_entry() {
+        let mut buffer = build_archive();
+        let at = buffer.windows(10).position(|w| w == b"print('a')").unwrap();
+        buffer[at] = b'P';
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let manifest = WorkshopManifest::new(buffer.len() as u64, 2_000);
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::Corrupted
+        );
+    }
+
+    #[test]
+    fn archive_crc() {
+        let mut buffer = include_bytes!("genuine.gma").to_vec();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        let crc = archive.archive_crc().unwrap();
+        let mut manifest = WorkshopManifest::new(buffer.len() as u64, u64::MAX);
+        manifest.crc(crc);
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::UpToDate
+        );
+        manifest.crc(!crc);
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::Outdated
+        );
+
+        // the stored crc no longer matches the contents
+        let len = buffer.len();
+        buffer[len - 5] ^= 0xff;
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::Corrupted
+        );
+    }
+
+    #[test]
+    fn compressed_size() {
+        // the published size is the one of the compressed file
+        let buffer = build_archive_with(Compression::Lzma);
+        let archive = gma::load_from_memory(&buffer).unwrap();
+        assert!(archive.decompressed_size().unwrap() != buffer.len() as u64);
+        let manifest = WorkshopManifest::new(buffer.len() as u64, 2_000);
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::UpToDate
+        );
+
+        // a decompressed copy only matches by crc
+        let decompressed = build_archive();
+        let archive = gma::load_from_memory(&decompressed).unwrap();
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::Outdated
+        );
+        let mut manifest = manifest;
+        manifest.crc(Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&decompressed));
+        assert_eq!(
+            verify_local(&manifest, &archive).unwrap(),
+            LocalStatus::UpToDate
+        );
+    }
+}
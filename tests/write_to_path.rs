@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod test {
+    use gma::GMABuilder;
+    use std::fs;
+
+    #[test]
+    fn write_to_path() {
+        let dir = std::env::temp_dir().join(format!("gma-test-write-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("addon.gma");
+
+        let mut builder = GMABuilder::new();
+        builder
+            .name("write to path")
+            .file_from_bytes("lua/init.lua", b"print('hi')".to_vec());
+        assert!(builder.estimated_size() > 11);
+        let report = builder.write_to_path(&path).unwrap();
+        assert_eq!(report.files().len(), 1);
+
+        let archive = gma::open(&path).unwrap();
+        assert_eq!(archive.name(), "write to path");
+        //only the final archive is left in the directory
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_to_path_missing_directory() {
+        let path = std::env::temp_dir()
+            .join(format!("gma-test-missing-{}", std::process::id()))
+            .join("addon.gma");
+        let mut builder = GMABuilder::new();
+        builder.name("missing");
+        assert!(builder.write_to_path(&path).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod test {
+    use gma::{Error, GMABuilder, OpenOptions};
+    use std::io::Cursor;
+
+    fn u16_at(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&data[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    // Reads (filename, crc, contents) of every file through the central directory
+    fn read_zip(zip: &[u8]) -> Vec<(String, u32, Vec<u8>)> {
+        let eocd = zip.len() - 22;
+        assert_eq!(u32_at(zip, eocd), 0x06054b50);
+        let count = u16_at(zip, eocd + 10) as usize;
+        let mut central = u32_at(zip, eocd + 16) as usize;
+        let mut files = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(zip, central), 0x02014b50);
+            let crc = u32_at(zip, central + 16);
+            let size = u32_at(zip, central + 24) as usize;
+            let name_len = u16_at(zip, central + 28) as usize;
+            let local = u32_at(zip, central + 42) as usize;
+            let name = String::from_utf8(zip[central + 46..central + 46 + name_len].to_vec());
+
+            assert_eq!(u32_at(zip, local), 0x04034b50);
+            let data = local + 30 + u16_at(zip, local + 26) as usize;
+            files.push((name.unwrap(), crc, zip[data..data + size].to_vec()));
+            central += 46 + name_len;
+        }
+        files
+    }
+
+    #[test]
+    fn stream_subset_as_zip() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("zip")
+            .file_from_bytes("lua/autorun/init.lua", b"print('init')".to_vec())
+            .file_from_bytes("materials/a.vmt", b"material".to_vec())
+            .file_from_bytes("lua/empty.lua", Vec::new());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let mut zip = Vec::new();
+        let lua = archive
+            .entries()
+            .filter(|e| e.filename().starts_with("lua/"));
+        let written = archive.stream_subset_as_zip(lua, &mut zip).unwrap();
+        assert_eq!(written, zip.len() as u64);
+
+        let files = read_zip(&zip);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, "lua/autorun/init.lua");
+        assert_eq!(files[0].1, archive["lua/autorun/init.lua"].crc());
+        assert_eq!(files[0].2, b"print('init')");
+        assert_eq!(files[1].0, "lua/empty.lua");
+        assert!(files[1].2.is_empty());
+    }
+
+    #[test]
+    fn stream_empty_zip() {
+        let archive = gma::load_from_memory(include_bytes!("genuine.gma")).unwrap();
+        let mut zip = Vec::new();
+        archive.stream_subset_as_zip(None, &mut zip).unwrap();
+        assert_eq!(zip.len(), 22);
+        assert!(read_zip(&zip).is_empty());
+    }
+
+    #[test]
+    fn checked_before_writing() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut builder = GMABuilder::new();
+        builder
+            .name("zip")
+            .file_from_bytes("lua/a.lua", b"print('a')".to_vec())
+            .file_from_bytes("lua/../../evil.lua", b"print('evil')".to_vec())
+            .file_from_bytes("lua/b.lua", b"print('b')".to_vec());
+        builder.write_to(Cursor::new(&mut buffer)).unwrap();
+        let archive = gma::load_from_memory(&buffer).unwrap();
+
+        let mut zip = Vec::new();
+        match archive.stream_subset_as_zip(archive.entries(), &mut zip) {
+            Err(Error::UnsafePath(name)) => assert_eq!(name, "lua/../../evil.lua"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(zip.is_empty());
+
+        // an entry that claims to be larger than a zip can hold, its contents end much earlier
+        let at = buffer
+            .windows(10)
+            .position(|w| w == b"lua/b.lua\0")
+            .unwrap()
+            + 10;
+        buffer[at..at + 8].copy_from_slice(&(1i64 << 33).to_le_bytes());
+        let archive = OpenOptions::new()
+            .lenient(true)
+            .load_from_memory(&buffer)
+            .unwrap();
+        let selected = [&archive["lua/a.lua"], &archive["lua/b.lua"]];
+        assert!(matches!(
+            archive.stream_subset_as_zip(selected, &mut zip),
+            Err(Error::ZipLimitExceeded)
+        ));
+        assert!(zip.is_empty());
+    }
+}